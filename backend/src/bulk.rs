@@ -0,0 +1,310 @@
+use chrono::{Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::error::ApiError;
+use crate::{live, lock, unmet_prerequisites, validate_employee_input, webhooks, AppState, Employee, EmployeeInput, Session};
+
+pub const MAX_BULK_ITEMS: usize = 1000;
+
+#[derive(Deserialize)]
+pub struct BulkCreateRequest {
+    pub items: Vec<EmployeeInput>,
+}
+
+#[derive(Deserialize)]
+pub struct BulkTarget {
+    pub id: u32,
+    // Pflicht wie bei PATCH; fehlt sie, wird das Element mit 428 abgelehnt
+    #[serde(default)]
+    pub version: Option<u64>,
+}
+
+/// Gemeinsame Änderung für alle Ziele von `PATCH /api/employees/bulk`.
+#[derive(Deserialize)]
+pub struct BulkChanges {
+    pub status: Option<String>,
+    pub training: Option<String>,
+    pub duedate: Option<String>,
+    // Fälligkeit um n Tage verschieben (negativ = vorziehen)
+    pub shift_days: Option<i64>,
+    #[serde(default)]
+    pub add_tags: Vec<String>,
+    #[serde(default)]
+    pub remove_tags: Vec<String>,
+    // Leere Werte entfernen das Feld
+    #[serde(default)]
+    pub custom_fields: BTreeMap<String, String>,
+}
+
+#[derive(Deserialize)]
+pub struct BulkUpdateRequest {
+    pub items: Vec<BulkTarget>,
+    pub changes: BulkChanges,
+}
+
+#[derive(Deserialize)]
+pub struct BulkDeleteRequest {
+    pub ids: Vec<u32>,
+}
+
+/// Ergebnis je Element, in der Reihenfolge der Anfrage.
+#[derive(Debug, Serialize)]
+pub struct BulkItemResult {
+    pub index: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<u32>,
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub fields: BTreeMap<String, String>,
+    // Fehlende Voraussetzungen – bei ok=true nur als Warnung
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub missing: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct BulkResponse {
+    pub items: Vec<BulkItemResult>,
+}
+
+impl BulkItemResult {
+    fn ok(index: usize, id: u32, warnings: Vec<String>) -> Self {
+        BulkItemResult {
+            index,
+            id: Some(id),
+            ok: true,
+            code: None,
+            error: None,
+            fields: BTreeMap::new(),
+            missing: warnings,
+        }
+    }
+
+    fn failed(index: usize, id: Option<u32>, err: ApiError) -> Self {
+        let code = err.code();
+        let error = err.to_string();
+        let (fields, missing) = match err {
+            ApiError::Validation(fields) => (fields, Vec::new()),
+            ApiError::PrerequisitesMissing(missing) => (BTreeMap::new(), missing),
+            _ => (BTreeMap::new(), Vec::new()),
+        };
+        BulkItemResult {
+            index,
+            id,
+            ok: false,
+            code: Some(code),
+            error: Some(error),
+            fields,
+            missing,
+        }
+    }
+}
+
+fn check_size(len: usize) -> Result<(), ApiError> {
+    if len == 0 {
+        return Err(ApiError::bad_request("empty_bulk", "Keine Einträge angegeben"));
+    }
+    if len > MAX_BULK_ITEMS {
+        return Err(ApiError::bad_request(
+            "too_many_items",
+            format!("Höchstens {} Einträge pro Anfrage", MAX_BULK_ITEMS),
+        ));
+    }
+    Ok(())
+}
+
+/// Führt alle Operationen auf einer Kopie aus und übernimmt sie nur, wenn
+/// jede einzelne gelingt. Sonst bleibt der Bestand unverändert und der
+/// Bericht kommt als `bulk_rejected` zurück.
+fn run_transaction<T>(
+    data: &AppState,
+    items: Vec<T>,
+    mut op: impl FnMut(&mut Vec<Employee>, &mut u32, T) -> Result<(u32, Vec<String>), (Option<u32>, ApiError)>,
+) -> Result<BulkResponse, ApiError> {
+    check_size(items.len())?;
+
+    let mut employees = lock(&data.employees);
+    let mut next_id = lock(&data.next_employee_id);
+    let mut working = employees.clone();
+    let mut working_next_id = *next_id;
+
+    let results: Vec<BulkItemResult> = items
+        .into_iter()
+        .enumerate()
+        .map(|(index, item)| match op(&mut working, &mut working_next_id, item) {
+            Ok((id, warnings)) => BulkItemResult::ok(index, id, warnings),
+            Err((id, err)) => BulkItemResult::failed(index, id, err),
+        })
+        .collect();
+
+    if results.iter().any(|r| !r.ok) {
+        return Err(ApiError::BulkRejected(results));
+    }
+
+    let before = std::mem::replace(&mut *employees, working);
+    *next_id = working_next_id;
+    let after = employees.clone();
+    drop(employees);
+    drop(next_id);
+
+    data.save_employees()?;
+    live::employees_changed(data, &before, &after);
+    webhooks::employees_changed(data, &before, &after);
+    Ok(BulkResponse { items: results })
+}
+
+pub fn create(data: &AppState, session: &Session, request: BulkCreateRequest) -> Result<BulkResponse, ApiError> {
+    let catalog = lock(&data.catalog).clone();
+
+    run_transaction(data, request.items, |working, next_id, mut input| {
+        let status = validate_employee_input(data, &mut input).map_err(|e| (None, e))?;
+
+        // Frühere Elemente derselben Anfrage zählen als erfüllte Voraussetzung
+        let (unmet, strict) = unmet_prerequisites(&catalog, working, &input.name, &input.training);
+        if strict && !unmet.is_empty() {
+            return Err((None, ApiError::PrerequisitesMissing(unmet)));
+        }
+
+        let id = *next_id;
+        *next_id += 1;
+        working.push(Employee {
+            id,
+            name: input.name,
+            training: input.training,
+            duedate: input.duedate,
+            status,
+            created_by: session.username.clone(),
+            custom_fields: input.custom_fields,
+            tags: input.tags,
+            version: 1,
+        });
+        Ok((id, unmet))
+    })
+}
+
+impl BulkChanges {
+    fn validate(&self) -> Result<(), ApiError> {
+        let empty = self.status.is_none()
+            && self.training.is_none()
+            && self.duedate.is_none()
+            && self.shift_days.is_none()
+            && self.add_tags.is_empty()
+            && self.remove_tags.is_empty()
+            && self.custom_fields.is_empty();
+        if empty {
+            return Err(ApiError::bad_request("no_changes", "Keine Änderungen angegeben"));
+        }
+        if self.duedate.is_some() && self.shift_days.is_some() {
+            let mut errors = BTreeMap::new();
+            errors.insert(
+                "shift_days".to_string(),
+                "Nicht zusammen mit duedate möglich".to_string(),
+            );
+            return Err(ApiError::Validation(errors));
+        }
+        Ok(())
+    }
+
+    fn apply(&self, current: &Employee) -> Result<EmployeeInput, ApiError> {
+        let duedate = match (&self.duedate, self.shift_days) {
+            (Some(duedate), _) => duedate.clone(),
+            (None, Some(days)) => {
+                let shifted = NaiveDate::parse_from_str(&current.duedate, "%Y-%m-%d")
+                    .ok()
+                    .and_then(|d| d.checked_add_signed(Duration::days(days)));
+                let Some(shifted) = shifted else {
+                    let mut errors = BTreeMap::new();
+                    errors.insert("duedate".to_string(), "Fälligkeit lässt sich nicht verschieben".to_string());
+                    return Err(ApiError::Validation(errors));
+                };
+                shifted.format("%Y-%m-%d").to_string()
+            }
+            (None, None) => current.duedate.clone(),
+        };
+
+        let mut tags: Vec<String> = current
+            .tags
+            .iter()
+            .filter(|t| !self.remove_tags.contains(t))
+            .cloned()
+            .collect();
+        tags.extend(self.add_tags.iter().cloned());
+
+        let mut custom_fields = current.custom_fields.clone();
+        for (key, value) in &self.custom_fields {
+            if value.trim().is_empty() {
+                custom_fields.remove(key);
+            } else {
+                custom_fields.insert(key.clone(), value.clone());
+            }
+        }
+
+        Ok(EmployeeInput {
+            name: current.name.clone(),
+            training: self.training.clone().unwrap_or_else(|| current.training.clone()),
+            duedate,
+            status: self
+                .status
+                .clone()
+                .unwrap_or_else(|| current.status.as_str().to_string()),
+            custom_fields,
+            tags,
+            version: None,
+        })
+    }
+}
+
+pub fn update(data: &AppState, request: BulkUpdateRequest) -> Result<BulkResponse, ApiError> {
+    request.changes.validate()?;
+    let changes = request.changes;
+    let catalog = lock(&data.catalog).clone();
+
+    run_transaction(data, request.items, |working, _, target| {
+        let fail = |e| (Some(target.id), e);
+        let pos = working
+            .iter()
+            .position(|e| e.id == target.id)
+            .ok_or_else(|| fail(ApiError::NotFound("Eintrag")))?;
+
+        let Some(version) = target.version else {
+            return Err(fail(ApiError::PreconditionRequired));
+        };
+        if version != working[pos].version {
+            return Err(fail(ApiError::VersionConflict(Box::new(working[pos].clone()))));
+        }
+
+        let mut input = changes.apply(&working[pos]).map_err(fail)?;
+        let status = validate_employee_input(data, &mut input).map_err(fail)?;
+
+        if input.training != working[pos].training {
+            let (unmet, strict) = unmet_prerequisites(&catalog, working, &input.name, &input.training);
+            if strict && !unmet.is_empty() {
+                return Err(fail(ApiError::PrerequisitesMissing(unmet)));
+            }
+        }
+
+        let existing = &mut working[pos];
+        existing.training = input.training;
+        existing.duedate = input.duedate;
+        existing.status = status;
+        existing.custom_fields = input.custom_fields;
+        existing.tags = input.tags;
+        existing.version += 1;
+        Ok((existing.id, Vec::new()))
+    })
+}
+
+pub fn delete(data: &AppState, request: BulkDeleteRequest) -> Result<BulkResponse, ApiError> {
+    run_transaction(data, request.ids, |working, _, id| {
+        let pos = working
+            .iter()
+            .position(|e| e.id == id)
+            .ok_or((Some(id), ApiError::NotFound("Eintrag")))?;
+        working.remove(pos);
+        Ok((id, Vec::new()))
+    })
+}
@@ -0,0 +1,153 @@
+use chrono::{Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::ApiError;
+use crate::{same_person, CustomFieldDef, Employee, Role};
+
+pub const FEEDS_FILE: &str = "calendar_feeds.json";
+// Erinnerungen in Tagen vor Fälligkeit, wenn ?alarms fehlt
+const DEFAULT_ALARMS: [u32; 2] = [7, 1];
+const MAX_ALARM_DAYS: u32 = 365;
+
+/// Abo-Token eines Benutzers. Gespeichert wird der Username, da Benutzer-IDs
+/// beim Neustart neu vergeben werden.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarFeed {
+    pub token: String,
+    pub username: String,
+    pub created_at: String,
+}
+
+#[derive(Serialize)]
+pub struct FeedInfo {
+    pub url: String,
+    pub created_at: String,
+}
+
+#[derive(Deserialize)]
+pub struct FeedParams {
+    // Kommagetrennte Tage vor Fälligkeit, z.B. ?alarms=30,7 – leer = keine
+    pub alarms: Option<String>,
+}
+
+impl FeedParams {
+    pub fn alarm_days(&self) -> Result<Vec<u32>, ApiError> {
+        let Some(raw) = &self.alarms else {
+            return Ok(DEFAULT_ALARMS.to_vec());
+        };
+        let mut days = Vec::new();
+        for part in raw.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            match part.parse::<u32>() {
+                Ok(d) if d <= MAX_ALARM_DAYS => days.push(d),
+                _ => {
+                    return Err(ApiError::bad_request(
+                        "invalid_query",
+                        format!("alarms: Tage zwischen 0 und {} erwartet", MAX_ALARM_DAYS),
+                    ))
+                }
+            }
+        }
+        days.sort_unstable_by(|a, b| b.cmp(a));
+        days.dedup();
+        Ok(days)
+    }
+}
+
+/// Manager und Admins sehen alle Einträge, Benutzer nur eigene – selbst
+/// angelegte oder solche, die auf ihren Namen laufen.
+pub fn visible(role: &Role, username: &str, e: &Employee) -> bool {
+    match role {
+        Role::Admin | Role::Manager => true,
+        Role::User => e.created_by == username || same_person(&e.name, username),
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Schreibt eine Inhaltszeile und faltet sie nach RFC 5545 bei 75 Oktetten.
+fn push_line(out: &mut String, line: &str) {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+fn trigger(days: u32) -> String {
+    if days == 0 {
+        "PT0S".to_string()
+    } else {
+        format!("-P{}D", days)
+    }
+}
+
+pub fn render(username: &str, employees: &[&Employee], defs: &[CustomFieldDef], alarms: &[u32]) -> String {
+    let stamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let mut out = String::new();
+    for line in [
+        "BEGIN:VCALENDAR",
+        "VERSION:2.0",
+        "PRODID:-//Training Dashboard//Schulungen//DE",
+        "CALSCALE:GREGORIAN",
+        "METHOD:PUBLISH",
+        "REFRESH-INTERVAL;VALUE=DURATION:PT1H",
+        "X-PUBLISHED-TTL:PT1H",
+    ] {
+        push_line(&mut out, line);
+    }
+    push_line(&mut out, &format!("X-WR-CALNAME:{}", escape(&format!("Schulungen ({})", username))));
+
+    for e in employees {
+        // Einträge ohne gültiges Datum lassen sich nicht eintragen
+        let Ok(due) = NaiveDate::parse_from_str(&e.duedate, "%Y-%m-%d") else {
+            continue;
+        };
+        let summary = format!("{} fällig – {}", e.training, e.name);
+
+        let mut description = vec![format!("Status: {}", e.status.label())];
+        if !e.tags.is_empty() {
+            description.push(format!("Tags: {}", e.tags.join(", ")));
+        }
+        description.extend(
+            defs.iter()
+                .filter_map(|d| e.custom_fields.get(&d.name).map(|v| format!("{}: {}", d.label, v))),
+        );
+
+        push_line(&mut out, "BEGIN:VEVENT");
+        push_line(&mut out, &format!("UID:training-{}@training-dashboard", e.id));
+        push_line(&mut out, &format!("DTSTAMP:{}", stamp));
+        // Version als SEQUENCE, damit Kalender Änderungen übernehmen
+        push_line(&mut out, &format!("SEQUENCE:{}", e.version));
+        push_line(&mut out, &format!("DTSTART;VALUE=DATE:{}", due.format("%Y%m%d")));
+        push_line(&mut out, &format!("DTEND;VALUE=DATE:{}", (due + Duration::days(1)).format("%Y%m%d")));
+        push_line(&mut out, &format!("SUMMARY:{}", escape(&summary)));
+        push_line(&mut out, &format!("DESCRIPTION:{}", escape(&description.join("\n"))));
+        if !e.tags.is_empty() {
+            let categories: Vec<String> = e.tags.iter().map(|t| escape(t)).collect();
+            push_line(&mut out, &format!("CATEGORIES:{}", categories.join(",")));
+        }
+        push_line(&mut out, "TRANSP:TRANSPARENT");
+        for days in alarms {
+            push_line(&mut out, "BEGIN:VALARM");
+            push_line(&mut out, "ACTION:DISPLAY");
+            push_line(&mut out, &format!("TRIGGER:{}", trigger(*days)));
+            push_line(&mut out, &format!("DESCRIPTION:{}", escape(&summary)));
+            push_line(&mut out, "END:VALARM");
+        }
+        push_line(&mut out, "END:VEVENT");
+    }
+
+    push_line(&mut out, "END:VCALENDAR");
+    out
+}
+
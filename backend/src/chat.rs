@@ -0,0 +1,301 @@
+use chrono::{Local, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::{BTreeMap, HashSet};
+use uuid::Uuid;
+
+use crate::error::ApiError;
+use crate::reminders::{self, SentReminder};
+use crate::{lock, AppState, CustomFieldDef, Employee};
+
+pub const SETTINGS_FILE: &str = "chat.json";
+pub const SENT_FILE: &str = "chat_sent.json";
+// Einträge pro Nachricht, der Rest wird nur gezählt
+const MAX_LINES: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChannelKind {
+    // Slack-kompatibler Incoming Webhook (auch Mattermost, Rocket.Chat)
+    Slack,
+    Matrix,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChatTemplates {
+    // Platzhalter: {department}, {overdue}, {upcoming}
+    pub title: String,
+    // Platzhalter wie bei E-Mails: {name}, {training}, {duedate}, {days}, {status}
+    pub upcoming: String,
+    pub overdue: String,
+}
+
+impl Default for ChatTemplates {
+    fn default() -> Self {
+        ChatTemplates {
+            title: "📋 Schulungen {department}: {overdue} überfällig, {upcoming} bald fällig".to_string(),
+            upcoming: "• {name} – {training}: fällig am {duedate} (in {days} Tagen)".to_string(),
+            overdue: "• {name} – {training}: seit {days} Tagen überfällig ({duedate})".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatChannel {
+    // Eindeutiger Name, dient auch als Schlüssel im Versandprotokoll
+    pub name: String,
+    pub kind: ChannelKind,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    // Wert des Abteilungsfelds, leer = alle Einträge
+    #[serde(default)]
+    pub department: String,
+    // Slack: Webhook-URL; Matrix: Homeserver, z.B. https://matrix.example.com
+    pub url: String,
+    #[serde(default)]
+    pub room_id: String,
+    #[serde(default)]
+    pub access_token: String,
+    #[serde(default)]
+    pub templates: ChatTemplates,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Chat-Benachrichtigungen (`chat.json`). Vorlaufzeiten und Überfälligkeit
+/// kommen aus den Einstellungen der E-Mail-Erinnerungen.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChatSettings {
+    // Zusatzfeld mit der Abteilung eines Eintrags
+    pub department_field: String,
+    pub channels: Vec<ChatChannel>,
+}
+
+/// Antwort von `GET /api/chat/settings` – ohne Zugangstokens.
+#[derive(Serialize)]
+pub struct SettingsView {
+    pub department_field: String,
+    pub channels: Vec<ChannelView>,
+}
+
+#[derive(Serialize)]
+pub struct ChannelView {
+    #[serde(flatten)]
+    pub channel: ChatChannel,
+    pub access_token_set: bool,
+}
+
+impl From<&ChatSettings> for SettingsView {
+    fn from(settings: &ChatSettings) -> Self {
+        let channels = settings
+            .channels
+            .iter()
+            .map(|c| {
+                let mut channel = c.clone();
+                let access_token_set = !channel.access_token.is_empty();
+                channel.access_token.clear();
+                ChannelView { channel, access_token_set }
+            })
+            .collect();
+        SettingsView { department_field: settings.department_field.clone(), channels }
+    }
+}
+
+#[derive(Serialize)]
+pub struct ChannelReport {
+    pub channel: String,
+    pub entries: usize,
+    // Nur gesetzt, wenn etwas zu melden war
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    pub sent: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct RunReport {
+    pub dry_run: bool,
+    pub channels: Vec<ChannelReport>,
+}
+
+#[derive(Deserialize)]
+pub struct TestRequest {
+    pub channel: String,
+}
+
+impl ChatSettings {
+    pub fn validate(&self, defs: &[CustomFieldDef]) -> Result<(), ApiError> {
+        let mut errors = BTreeMap::new();
+        if !self.department_field.is_empty() && !defs.iter().any(|d| d.name == self.department_field) {
+            errors.insert(
+                "department_field".to_string(),
+                format!("Unbekanntes Zusatzfeld: {}", self.department_field),
+            );
+        }
+        let mut names = HashSet::new();
+        for (index, channel) in self.channels.iter().enumerate() {
+            let key = |field: &str| format!("channels.{}.{}", index, field);
+            if channel.name.trim().is_empty() {
+                errors.insert(key("name"), "Name fehlt".to_string());
+            } else if !names.insert(channel.name.trim()) {
+                errors.insert(key("name"), "Name doppelt".to_string());
+            }
+            if !matches!(reqwest::Url::parse(channel.url.trim()), Ok(u) if u.scheme() == "http" || u.scheme() == "https") {
+                errors.insert(key("url"), "http(s)-URL erwartet".to_string());
+            }
+            if channel.kind == ChannelKind::Matrix && !channel.room_id.starts_with('!') {
+                errors.insert(key("room_id"), "Raum-ID wie !abc:example.com erwartet".to_string());
+            }
+            if !channel.department.is_empty() && self.department_field.is_empty() {
+                errors.insert(key("department"), "department_field ist nicht gesetzt".to_string());
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ApiError::Validation(errors))
+        }
+    }
+}
+
+fn matches_department(settings: &ChatSettings, channel: &ChatChannel, e: &Employee) -> bool {
+    if channel.department.is_empty() {
+        return true;
+    }
+    e.custom_fields
+        .get(&settings.department_field)
+        .is_some_and(|d| d.trim().eq_ignore_ascii_case(channel.department.trim()))
+}
+
+fn sent_key(channel: &ChatChannel, e: &Employee, stage: &str) -> String {
+    format!("chat:{}:{}:{}:{}", channel.name, e.id, e.duedate, stage)
+}
+
+async fn post(client: &reqwest::Client, channel: &ChatChannel, text: &str) -> Result<(), String> {
+    let request = match channel.kind {
+        ChannelKind::Slack => client.post(channel.url.trim()).json(&json!({ "text": text })),
+        ChannelKind::Matrix => {
+            let mut url = reqwest::Url::parse(channel.url.trim()).map_err(|e| e.to_string())?;
+            url.path_segments_mut()
+                .map_err(|_| "Ungültige Homeserver-URL".to_string())?
+                .pop_if_empty()
+                .extend(["_matrix", "client", "v3", "rooms", &channel.room_id, "send", "m.room.message"])
+                .push(&Uuid::new_v4().to_string());
+            client
+                .put(url)
+                .bearer_auth(&channel.access_token)
+                .json(&json!({ "msgtype": "m.text", "body": text }))
+        }
+    };
+    let response = request.send().await.map_err(|e| e.to_string())?;
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(format!("HTTP {}", response.status()))
+    }
+}
+
+/// Sammelt je Kanal die Einträge, die seit der letzten Meldung eine neue
+/// Erinnerungsstufe erreicht haben, und postet eine Zusammenfassung.
+pub async fn run(data: &AppState, dry_run: bool) -> Result<RunReport, ApiError> {
+    let Ok(_running) = data.chat_run.try_lock() else {
+        return Err(ApiError::conflict("chat_running", "Chat-Meldungen werden gerade verschickt"));
+    };
+
+    let dry_run = dry_run || !data.config.notifications.enabled;
+    let settings = lock(&data.chat_settings).clone();
+    let reminder_settings = lock(&data.reminder_settings).clone();
+    let sent: HashSet<String> = lock(&data.chat_sent).iter().map(|s| s.key.clone()).collect();
+    let employees = lock(&data.employees).clone();
+    let today = Local::now().date_naive();
+    let language = reminder_settings.language.as_str();
+
+    let mut report = RunReport { dry_run, channels: Vec::new() };
+    let mut records = Vec::new();
+    for channel in settings.channels.iter().filter(|c| c.enabled) {
+        let mut due: Vec<(&Employee, NaiveDate, i64, String)> = employees
+            .iter()
+            .filter(|e| matches_department(&settings, channel, e))
+            .filter_map(|e| {
+                let due = NaiveDate::parse_from_str(&e.duedate, "%Y-%m-%d").ok()?;
+                let days_left = (due - today).num_days();
+                let stage = reminders::stage(&reminder_settings, days_left)?;
+                (!sent.contains(&sent_key(channel, e, &stage))).then_some((e, due, days_left, stage))
+            })
+            .collect();
+        due.sort_by_key(|(_, due, _, _)| *due);
+
+        let mut item = ChannelReport {
+            channel: channel.name.clone(),
+            entries: due.len(),
+            message: None,
+            sent: false,
+            error: None,
+        };
+        if due.is_empty() {
+            report.channels.push(item);
+            continue;
+        }
+
+        let overdue = due.iter().filter(|(_, _, days, _)| *days < 0).count();
+        let department = if channel.department.is_empty() { "(alle)" } else { channel.department.as_str() };
+        let mut lines = vec![channel
+            .templates
+            .title
+            .replace("{department}", department)
+            .replace("{overdue}", &overdue.to_string())
+            .replace("{upcoming}", &(due.len() - overdue).to_string())];
+        for (e, date, days, _) in due.iter().take(MAX_LINES) {
+            let template = if *days < 0 { &channel.templates.overdue } else { &channel.templates.upcoming };
+            lines.push(reminders::render(template, e, language, *date, *days));
+        }
+        if due.len() > MAX_LINES {
+            lines.push(format!("… und {} weitere", due.len() - MAX_LINES));
+        }
+        let text = lines.join("\n");
+
+        if !dry_run {
+            match post(&data.http, channel, &text).await {
+                Ok(()) => {
+                    item.sent = true;
+                    let now = Utc::now().to_rfc3339();
+                    records.extend(due.iter().map(|(e, _, _, stage)| SentReminder {
+                        key: sent_key(channel, e, stage),
+                        employee_id: e.id,
+                        stage: stage.clone(),
+                        to: vec![channel.name.clone()],
+                        sent_at: now.clone(),
+                    }));
+                }
+                Err(err) => item.error = Some(err),
+            }
+        }
+        item.message = Some(text);
+        report.channels.push(item);
+    }
+
+    if !records.is_empty() {
+        lock(&data.chat_sent).extend(records);
+        data.save_chat_sent()?;
+    }
+    Ok(report)
+}
+
+/// Schickt eine Testnachricht in einen Kanal.
+pub async fn send_test(data: &AppState, name: &str) -> Result<(), ApiError> {
+    data.require_notifications()?;
+    let channel = lock(&data.chat_settings)
+        .channels
+        .iter()
+        .find(|c| c.name == name)
+        .cloned()
+        .ok_or(ApiError::NotFound("Kanal"))?;
+    post(&data.http, &channel, "Training Dashboard – Testnachricht")
+        .await
+        .map_err(|e| ApiError::bad_gateway("chat_failed", format!("Versand fehlgeschlagen: {}", e)))
+}
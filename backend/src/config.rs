@@ -0,0 +1,478 @@
+use chrono::{DateTime, Duration, Utc};
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+
+use crate::reminders::SmtpSettings;
+
+// Wird ohne --config gelesen, sofern vorhanden
+pub const DEFAULT_FILE: &str = "backend.toml";
+const LOG_LEVELS: [&str; 6] = ["off", "error", "warn", "info", "debug", "trace"];
+const LOG_FORMATS: [&str; 2] = ["text", "json"];
+const LOG_ROTATIONS: [&str; 3] = ["daily", "hourly", "never"];
+const MIN_METRICS_TOKEN_LEN: usize = 16;
+
+/// Kommandozeile. Jede Option lässt sich auch über die angegebene
+/// Umgebungsvariable setzen; die Kommandozeile hat Vorrang.
+#[derive(Parser)]
+#[command(name = "training-backend", version, about = "Training Dashboard Backend")]
+pub struct Cli {
+    /// Konfigurationsdatei (TOML), Standard: ./backend.toml
+    #[arg(long, short, env = "TRAINING_CONFIG")]
+    pub config: Option<PathBuf>,
+    /// Adresse, an die der Server bindet
+    #[arg(long, env = "TRAINING_BIND")]
+    bind: Option<String>,
+    /// Port, Standard 8080
+    #[arg(long, env = "TRAINING_PORT")]
+    port: Option<u16>,
+    /// Zertifikatskette (PEM); mit --tls-key wird HTTPS aktiviert
+    #[arg(long, env = "TRAINING_TLS_CERT")]
+    tls_cert: Option<PathBuf>,
+    /// Privater Schlüssel (PEM)
+    #[arg(long, env = "TRAINING_TLS_KEY")]
+    tls_key: Option<PathBuf>,
+    /// Zusätzlicher HTTP-Port, der auf HTTPS weiterleitet
+    #[arg(long, env = "TRAINING_TLS_REDIRECT_PORT")]
+    tls_redirect_port: Option<u16>,
+    /// CA (PEM), deren Client-Zertifikate als Anmeldung gelten
+    #[arg(long, env = "TRAINING_TLS_CLIENT_CA")]
+    tls_client_ca: Option<PathBuf>,
+    /// Verzeichnis für employees.csv, die JSON-Dateien und Backups
+    #[arg(long, env = "TRAINING_DATA_DIR")]
+    data_dir: Option<PathBuf>,
+    /// Erlaubte Browser-Origins, kommagetrennt; "*" erlaubt alle
+    #[arg(long, env = "TRAINING_CORS_ORIGINS", value_delimiter = ',')]
+    cors_origins: Option<Vec<String>>,
+    /// z.B. "info" oder "warn,training_backend::scheduler=debug"
+    #[arg(long, env = "TRAINING_LOG_LEVEL")]
+    log_level: Option<String>,
+    /// "text" oder "json"
+    #[arg(long, env = "TRAINING_LOG_FORMAT")]
+    log_format: Option<String>,
+    /// Verzeichnis für rotierende Logdateien statt Standardausgabe
+    #[arg(long, env = "TRAINING_LOG_DIR")]
+    log_dir: Option<PathBuf>,
+    /// Höchstdauer einer Sitzung ab Anmeldung
+    #[arg(long, env = "TRAINING_SESSION_MAX_AGE_HOURS")]
+    session_max_age_hours: Option<u32>,
+    /// 0 = keine Abmeldung bei Inaktivität
+    #[arg(long, env = "TRAINING_SESSION_IDLE_MINUTES")]
+    session_idle_minutes: Option<u32>,
+    /// Aufwand für Passwort-Hashes (4–31)
+    #[arg(long, env = "TRAINING_BCRYPT_COST")]
+    bcrypt_cost: Option<u32>,
+    /// true startet im Wartungsmodus (nur lesen)
+    #[arg(long, env = "TRAINING_READ_ONLY")]
+    read_only: Option<bool>,
+    /// false schaltet E-Mails, Chat-Nachrichten und Webhooks ab
+    #[arg(long, env = "TRAINING_NOTIFICATIONS")]
+    notifications: Option<bool>,
+    /// Eigener Port für /metrics, /health und /ready, 0 = nur Hauptport
+    #[arg(long, env = "TRAINING_METRICS_PORT")]
+    metrics_port: Option<u16>,
+    /// Bearer-Token, mit dem Prometheus /metrics abruft
+    #[arg(long, env = "TRAINING_METRICS_TOKEN", hide_env_values = true)]
+    metrics_token: Option<String>,
+    /// Wirksame Konfiguration ausgeben und beenden
+    #[arg(long)]
+    pub print_config: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub bind: String,
+    pub port: u16,
+    // Leer = keine Zugriffe aus dem Browser von fremden Seiten
+    pub cors_origins: Vec<String>,
+    // Im Wartungsmodus starten, z.B. für eine Migration
+    pub read_only: bool,
+    // So lange dürfen laufende Anfragen beim Beenden noch dauern
+    pub shutdown_timeout_seconds: u64,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        ServerConfig {
+            bind: "127.0.0.1".to_string(),
+            port: 8080,
+            cors_origins: Vec::new(),
+            read_only: false,
+            shutdown_timeout_seconds: 30,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TlsConfig {
+    // PEM-Dateien; ohne beide läuft der Server nur mit HTTP
+    pub cert: PathBuf,
+    pub key: PathBuf,
+    // Zusätzlicher HTTP-Port, der auf HTTPS weiterleitet, 0 = aus
+    pub redirect_port: u16,
+    // CA für Client-Zertifikate von Maschinen-Clients, leer = kein mTLS
+    pub client_ca: PathBuf,
+    // "optional": Zertifikat oder Token, "required": nur mit Zertifikat
+    pub client_auth: String,
+}
+
+impl Default for TlsConfig {
+    fn default() -> Self {
+        TlsConfig {
+            cert: PathBuf::new(),
+            key: PathBuf::new(),
+            redirect_port: 0,
+            client_ca: PathBuf::new(),
+            client_auth: "optional".to_string(),
+        }
+    }
+}
+
+impl TlsConfig {
+    pub fn enabled(&self) -> bool {
+        !self.cert.as_os_str().is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StorageConfig {
+    pub data_dir: PathBuf,
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        StorageConfig { data_dir: PathBuf::from(".") }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogConfig {
+    // Syntax wie RUST_LOG, Stufen auch je Modul
+    pub level: String,
+    // "text" für die Konsole, "json" für Log-Sammler
+    pub format: String,
+    // Leer = Standardausgabe, sonst backend.JJJJ-MM-TT.log in diesem Verzeichnis
+    pub dir: PathBuf,
+    // "daily", "hourly" oder "never"
+    pub rotation: String,
+    // Ältere Dateien werden gelöscht, 0 = alle behalten
+    pub max_files: usize,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        LogConfig {
+            level: "info".to_string(),
+            format: "text".to_string(),
+            dir: PathBuf::new(),
+            rotation: "daily".to_string(),
+            max_files: 14,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SessionConfig {
+    // Höchstdauer ab Anmeldung
+    pub max_age_hours: u32,
+    // Abmeldung nach so langer Inaktivität, 0 = aus
+    pub idle_minutes: u32,
+    // Beim Beenden speichern, damit ein Neustart niemanden abmeldet
+    pub persist: bool,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        SessionConfig { max_age_hours: 24, idle_minutes: 0, persist: false }
+    }
+}
+
+impl SessionConfig {
+    pub fn expired(&self, created_at: &str, last_seen: &str, now: DateTime<Utc>) -> bool {
+        let parse = |value: &str| DateTime::parse_from_rfc3339(value).map(|t| t.with_timezone(&Utc));
+        let (Ok(created_at), Ok(last_seen)) = (parse(created_at), parse(last_seen)) else {
+            return true;
+        };
+        now - created_at > Duration::hours(self.max_age_hours.into())
+            || (self.idle_minutes > 0 && now - last_seen > Duration::minutes(self.idle_minutes.into()))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SecurityConfig {
+    pub bcrypt_cost: u32,
+}
+
+impl Default for SecurityConfig {
+    fn default() -> Self {
+        SecurityConfig { bcrypt_cost: bcrypt::DEFAULT_COST }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NotificationConfig {
+    // false = nichts verschicken, etwa auf Test-Systemen
+    pub enabled: bool,
+    // Hat Vorrang vor den SMTP-Einstellungen aus reminders.json
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub smtp: Option<SmtpSettings>,
+}
+
+impl Default for NotificationConfig {
+    fn default() -> Self {
+        NotificationConfig { enabled: true, smtp: None }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MetricsConfig {
+    // Eigener Listener nur für Monitoring, z.B. 127.0.0.1:9090; 0 = aus
+    pub bind: String,
+    pub port: u16,
+    // Ohne Token ist /metrics auf dem Hauptport nur für Admins abrufbar
+    pub token: String,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        MetricsConfig {
+            bind: "127.0.0.1".to_string(),
+            port: 0,
+            token: String::new(),
+        }
+    }
+}
+
+/// Startkonfiguration: eingebaute Standardwerte, darüber die TOML-Datei,
+/// Umgebungsvariablen und zuletzt die Kommandozeile.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub server: ServerConfig,
+    pub tls: TlsConfig,
+    pub storage: StorageConfig,
+    pub log: LogConfig,
+    pub sessions: SessionConfig,
+    pub security: SecurityConfig,
+    pub notifications: NotificationConfig,
+    pub metrics: MetricsConfig,
+}
+
+impl Config {
+    pub fn load(cli: &Cli) -> Result<Config, BTreeMap<String, String>> {
+        let mut config = match &cli.config {
+            Some(path) => Self::read(path)?,
+            None if Path::new(DEFAULT_FILE).exists() => Self::read(Path::new(DEFAULT_FILE))?,
+            None => Config::default(),
+        };
+
+        if let Some(bind) = &cli.bind {
+            config.server.bind = bind.clone();
+        }
+        if let Some(port) = cli.port {
+            config.server.port = port;
+        }
+        if let Some(origins) = &cli.cors_origins {
+            config.server.cors_origins = origins.iter().filter(|o| !o.is_empty()).cloned().collect();
+        }
+        if let Some(cert) = &cli.tls_cert {
+            config.tls.cert = cert.clone();
+        }
+        if let Some(key) = &cli.tls_key {
+            config.tls.key = key.clone();
+        }
+        if let Some(port) = cli.tls_redirect_port {
+            config.tls.redirect_port = port;
+        }
+        if let Some(client_ca) = &cli.tls_client_ca {
+            config.tls.client_ca = client_ca.clone();
+        }
+        if let Some(dir) = &cli.log_dir {
+            config.log.dir = dir.clone();
+        }
+        // Das Arbeitsverzeichnis wechselt später ins Datenverzeichnis
+        for path in [&mut config.tls.cert, &mut config.tls.key, &mut config.tls.client_ca, &mut config.log.dir] {
+            if !path.as_os_str().is_empty() {
+                *path = std::path::absolute(&*path).unwrap_or_else(|_| path.clone());
+            }
+        }
+        if let Some(data_dir) = &cli.data_dir {
+            config.storage.data_dir = data_dir.clone();
+        }
+        if let Some(level) = &cli.log_level {
+            config.log.level = level.clone();
+        }
+        if let Some(format) = &cli.log_format {
+            config.log.format = format.clone();
+        }
+        if let Some(hours) = cli.session_max_age_hours {
+            config.sessions.max_age_hours = hours;
+        }
+        if let Some(minutes) = cli.session_idle_minutes {
+            config.sessions.idle_minutes = minutes;
+        }
+        if let Some(cost) = cli.bcrypt_cost {
+            config.security.bcrypt_cost = cost;
+        }
+        if let Some(read_only) = cli.read_only {
+            config.server.read_only = read_only;
+        }
+        if let Some(enabled) = cli.notifications {
+            config.notifications.enabled = enabled;
+        }
+        if let Some(port) = cli.metrics_port {
+            config.metrics.port = port;
+        }
+        if let Some(token) = &cli.metrics_token {
+            config.metrics.token = token.clone();
+        }
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn read(path: &Path) -> Result<Config, BTreeMap<String, String>> {
+        let error = |message: String| BTreeMap::from([(path.display().to_string(), message)]);
+        let text = std::fs::read_to_string(path).map_err(|e| error(e.to_string()))?;
+        toml::from_str(&text).map_err(|e| error(e.to_string()))
+    }
+
+    fn validate(&self) -> Result<(), BTreeMap<String, String>> {
+        let mut errors = BTreeMap::new();
+
+        let bind = &self.server.bind;
+        let hostname = !bind.is_empty() && bind.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.');
+        if bind.parse::<IpAddr>().is_err() && !hostname {
+            errors.insert("server.bind".to_string(), "IP-Adresse oder Hostname erwartet".to_string());
+        }
+        if self.server.port == 0 {
+            errors.insert("server.port".to_string(), "Port 1–65535 erwartet".to_string());
+        }
+        if self.server.shutdown_timeout_seconds == 0 {
+            errors.insert("server.shutdown_timeout_seconds".to_string(), "Mindestens 1 Sekunde".to_string());
+        }
+        let origins = &self.server.cors_origins;
+        if origins.iter().any(|o| o == "*") && origins.len() > 1 {
+            errors.insert("server.cors_origins".to_string(), "\"*\" nur allein erlaubt".to_string());
+        }
+        for origin in origins.iter().filter(|o| *o != "*") {
+            // Origin = Schema, Host und ggf. Port, ohne Pfad und Schrägstrich
+            let valid = reqwest::Url::parse(origin)
+                .is_ok_and(|url| matches!(url.scheme(), "http" | "https") && url.origin().ascii_serialization() == *origin);
+            if !valid {
+                errors.insert(
+                    "server.cors_origins".to_string(),
+                    format!("Ungültiger Origin {:?}, erwartet z.B. https://intranet.example.com", origin),
+                );
+            }
+        }
+
+        let tls = &self.tls;
+        if tls.cert.as_os_str().is_empty() != tls.key.as_os_str().is_empty() {
+            errors.insert("tls".to_string(), "cert und key nur zusammen angeben".to_string());
+        }
+        for (key, path) in [("tls.cert", &tls.cert), ("tls.key", &tls.key), ("tls.client_ca", &tls.client_ca)] {
+            if !path.as_os_str().is_empty() && !path.is_file() {
+                errors.insert(key.to_string(), format!("Datei {} nicht gefunden", path.display()));
+            }
+        }
+        if tls.redirect_port != 0 && (!tls.enabled() || tls.redirect_port == self.server.port) {
+            errors.insert(
+                "tls.redirect_port".to_string(),
+                "Nur mit HTTPS und ungleich server.port".to_string(),
+            );
+        }
+        if !tls.client_ca.as_os_str().is_empty() && !tls.enabled() {
+            errors.insert("tls.client_ca".to_string(), "Nur mit HTTPS".to_string());
+        }
+        if !["optional", "required"].contains(&tls.client_auth.as_str()) {
+            errors.insert("tls.client_auth".to_string(), "Erlaubt: optional, required".to_string());
+        }
+
+        if self.storage.data_dir.as_os_str().is_empty() {
+            errors.insert("storage.data_dir".to_string(), "Verzeichnis fehlt".to_string());
+        } else if self.storage.data_dir.exists() && !self.storage.data_dir.is_dir() {
+            errors.insert("storage.data_dir".to_string(), "Ist kein Verzeichnis".to_string());
+        }
+
+        // EnvFilter hielte einen Tippfehler wie "infoo" für einen Modulnamen
+        for directive in self.log.level.split(',').filter(|d| !d.trim().is_empty()) {
+            let level = directive.rsplit('=').next().unwrap_or_default().trim();
+            if !LOG_LEVELS.contains(&level.to_lowercase().as_str()) {
+                errors.insert(
+                    "log.level".to_string(),
+                    format!("Ungültige Angabe {:?}, erlaubt: {}", directive, LOG_LEVELS.join(", ")),
+                );
+            }
+        }
+        if let Err(err) = tracing_subscriber::EnvFilter::try_new(&self.log.level) {
+            errors.insert("log.level".to_string(), format!("Ungültige Angabe: {}", err));
+        }
+        if !LOG_FORMATS.contains(&self.log.format.as_str()) {
+            errors.insert("log.format".to_string(), format!("Erlaubt: {}", LOG_FORMATS.join(", ")));
+        }
+        if !LOG_ROTATIONS.contains(&self.log.rotation.as_str()) {
+            errors.insert("log.rotation".to_string(), format!("Erlaubt: {}", LOG_ROTATIONS.join(", ")));
+        }
+        if self.log.dir.exists() && !self.log.dir.is_dir() {
+            errors.insert("log.dir".to_string(), "Ist kein Verzeichnis".to_string());
+        }
+
+        if self.sessions.max_age_hours == 0 {
+            errors.insert("sessions.max_age_hours".to_string(), "Mindestens 1 Stunde".to_string());
+        }
+        if !(4..=31).contains(&self.security.bcrypt_cost) {
+            errors.insert("security.bcrypt_cost".to_string(), "Wert zwischen 4 und 31 erwartet".to_string());
+        }
+        if let Some(smtp) = &self.notifications.smtp {
+            for (field, message) in smtp.validate(true) {
+                errors.insert(format!("notifications.smtp.{}", field), message);
+            }
+        }
+
+        let metrics = &self.metrics;
+        if metrics.bind.parse::<IpAddr>().is_err() {
+            errors.insert("metrics.bind".to_string(), "IP-Adresse erwartet".to_string());
+        }
+        if metrics.port != 0 && (metrics.port == self.server.port || metrics.port == tls.redirect_port) {
+            errors.insert(
+                "metrics.port".to_string(),
+                "Ungleich server.port und tls.redirect_port".to_string(),
+            );
+        }
+        if !metrics.token.is_empty() && metrics.token.len() < MIN_METRICS_TOKEN_LEN {
+            errors.insert(
+                "metrics.token".to_string(),
+                format!("Mindestens {} Zeichen", MIN_METRICS_TOKEN_LEN),
+            );
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// TOML für `--print-config`, Passwörter unkenntlich.
+    pub fn to_toml(&self) -> String {
+        let mut config = self.clone();
+        if let Some(smtp) = config.notifications.smtp.as_mut().filter(|s| !s.password.is_empty()) {
+            smtp.password = "********".to_string();
+        }
+        if !config.metrics.token.is_empty() {
+            config.metrics.token = "********".to_string();
+        }
+        toml::to_string(&config).unwrap_or_else(|e| format!("# Fehler: {}\n", e))
+    }
+}
@@ -0,0 +1,206 @@
+use actix_web::http::StatusCode;
+use actix_web::{HttpResponse, ResponseError};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::bulk::BulkItemResult;
+use crate::Employee;
+
+/// Fehler der REST API. Jede Variante hat einen festen HTTP-Status und einen
+/// maschinenlesbaren `code`, der sich nicht ändert – Clients sollten darauf
+/// prüfen, nicht auf den (deutschen) Meldungstext.
+#[derive(Debug)]
+pub enum ApiError {
+    /// 400 – feldweise Validierungsfehler
+    Validation(BTreeMap<String, String>),
+    /// 400 – sonstige fehlerhafte Anfrage
+    BadRequest { code: &'static str, message: String },
+    /// 401 – kein oder ungültiges Token
+    Unauthorized,
+    /// 401 – Benutzername oder Passwort falsch
+    InvalidCredentials,
+    /// 403 – Rolle reicht nicht aus
+    Forbidden,
+    /// 403 – Benutzer ist deaktiviert
+    UserDisabled,
+    /// 404 – Ressource existiert nicht
+    NotFound(&'static str),
+    /// 409 – Konflikt mit dem aktuellen Datenbestand
+    Conflict { code: &'static str, message: String },
+    /// 409 – Voraussetzungen einer Schulung fehlen
+    PrerequisitesMissing(Vec<String>),
+    /// 409 – veraltete Version; enthält den aktuellen Stand des Eintrags
+    VersionConflict(Box<Employee>),
+    /// 400 – Sammeloperation abgelehnt, nichts wurde übernommen
+    BulkRejected(Vec<BulkItemResult>),
+    /// 428 – Änderung ohne erwartete Version
+    PreconditionRequired,
+    /// 503 – Wartungsmodus oder Server wird beendet; später erneut versuchen
+    Unavailable { code: &'static str, message: String },
+    /// 502 – externer Dienst (z.B. SMTP-Server) nicht erreichbar oder fehlerhaft
+    BadGateway { code: &'static str, message: String },
+    /// 500 – interner Fehler (Speicher, bcrypt, ...)
+    Internal(String),
+}
+
+#[derive(Serialize)]
+struct ErrorBody<'a> {
+    code: &'a str,
+    error: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fields: Option<&'a BTreeMap<String, String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    missing: Option<&'a [String]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    current: Option<&'a Employee>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    items: Option<&'a [BulkItemResult]>,
+}
+
+impl ApiError {
+    pub fn bad_request(code: &'static str, message: impl Into<String>) -> Self {
+        ApiError::BadRequest { code, message: message.into() }
+    }
+
+    pub fn conflict(code: &'static str, message: impl Into<String>) -> Self {
+        ApiError::Conflict { code, message: message.into() }
+    }
+
+    pub fn unavailable(code: &'static str, message: impl Into<String>) -> Self {
+        ApiError::Unavailable { code, message: message.into() }
+    }
+
+    pub fn bad_gateway(code: &'static str, message: impl Into<String>) -> Self {
+        ApiError::BadGateway { code, message: message.into() }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiError::Validation(_) => "validation_failed",
+            ApiError::BadRequest { code, .. } => code,
+            ApiError::Unauthorized => "unauthorized",
+            ApiError::InvalidCredentials => "invalid_credentials",
+            ApiError::Forbidden => "forbidden",
+            ApiError::UserDisabled => "user_disabled",
+            ApiError::NotFound(_) => "not_found",
+            ApiError::Conflict { code, .. } => code,
+            ApiError::PrerequisitesMissing(_) => "prerequisites_missing",
+            ApiError::VersionConflict(_) => "version_conflict",
+            ApiError::BulkRejected(_) => "bulk_rejected",
+            ApiError::PreconditionRequired => "precondition_required",
+            ApiError::Unavailable { code, .. } => code,
+            ApiError::BadGateway { code, .. } => code,
+            ApiError::Internal(_) => "internal_error",
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiError::Validation(_) => write!(f, "Eingaben ungültig"),
+            ApiError::BadRequest { message, .. } => write!(f, "{}", message),
+            ApiError::Unauthorized => write!(f, "Nicht angemeldet"),
+            ApiError::InvalidCredentials => write!(f, "Ungültige Anmeldedaten"),
+            ApiError::Forbidden => write!(f, "Keine Berechtigung"),
+            ApiError::UserDisabled => write!(f, "Benutzer deaktiviert"),
+            ApiError::NotFound(what) => write!(f, "{} nicht gefunden", what),
+            ApiError::Conflict { message, .. } => write!(f, "{}", message),
+            ApiError::PrerequisitesMissing(_) => write!(f, "Voraussetzungen nicht erfüllt"),
+            ApiError::VersionConflict(_) => write!(f, "Eintrag wurde zwischenzeitlich geändert"),
+            ApiError::BulkRejected(items) => {
+                let failed = items.iter().filter(|i| !i.ok).count();
+                write!(f, "{} von {} Einträgen fehlerhaft, nichts übernommen", failed, items.len())
+            }
+            ApiError::PreconditionRequired => write!(f, "Erwartete Version fehlt"),
+            ApiError::Unavailable { message, .. } => write!(f, "{}", message),
+            ApiError::BadGateway { message, .. } => write!(f, "{}", message),
+            // Details landen im Log, nicht beim Client
+            ApiError::Internal(_) => write!(f, "Interner Fehler"),
+        }
+    }
+}
+
+impl ResponseError for ApiError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ApiError::Validation(_) | ApiError::BadRequest { .. } | ApiError::BulkRejected(_) => {
+                StatusCode::BAD_REQUEST
+            }
+            ApiError::Unauthorized | ApiError::InvalidCredentials => StatusCode::UNAUTHORIZED,
+            ApiError::Forbidden | ApiError::UserDisabled => StatusCode::FORBIDDEN,
+            ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::Conflict { .. } | ApiError::PrerequisitesMissing(_) | ApiError::VersionConflict(_) => {
+                StatusCode::CONFLICT
+            }
+            ApiError::PreconditionRequired => StatusCode::PRECONDITION_REQUIRED,
+            ApiError::Unavailable { .. } => StatusCode::SERVICE_UNAVAILABLE,
+            ApiError::BadGateway { .. } => StatusCode::BAD_GATEWAY,
+            ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        if let ApiError::Internal(detail) = self {
+            tracing::error!(detail = %detail, "Interner Fehler");
+        }
+
+        HttpResponse::build(self.status_code()).json(ErrorBody {
+            code: self.code(),
+            error: self.to_string(),
+            fields: match self {
+                ApiError::Validation(fields) => Some(fields),
+                _ => None,
+            },
+            missing: match self {
+                ApiError::PrerequisitesMissing(missing) => Some(missing),
+                _ => None,
+            },
+            current: match self {
+                ApiError::VersionConflict(current) => Some(current),
+                _ => None,
+            },
+            items: match self {
+                ApiError::BulkRejected(items) => Some(items),
+                _ => None,
+            },
+        })
+    }
+}
+
+impl From<std::io::Error> for ApiError {
+    fn from(err: std::io::Error) -> Self {
+        ApiError::Internal(format!("E/A-Fehler: {}", err))
+    }
+}
+
+impl From<csv::Error> for ApiError {
+    fn from(err: csv::Error) -> Self {
+        ApiError::Internal(format!("CSV-Fehler: {}", err))
+    }
+}
+
+impl From<serde_json::Error> for ApiError {
+    fn from(err: serde_json::Error) -> Self {
+        ApiError::Internal(format!("JSON-Fehler: {}", err))
+    }
+}
+
+impl From<rust_xlsxwriter::XlsxError> for ApiError {
+    fn from(err: rust_xlsxwriter::XlsxError) -> Self {
+        ApiError::Internal(format!("XLSX-Fehler: {}", err))
+    }
+}
+
+impl From<printpdf::Error> for ApiError {
+    fn from(err: printpdf::Error) -> Self {
+        ApiError::Internal(format!("PDF-Fehler: {}", err))
+    }
+}
+
+impl From<bcrypt::BcryptError> for ApiError {
+    fn from(err: bcrypt::BcryptError) -> Self {
+        ApiError::Internal(format!("bcrypt-Fehler: {}", err))
+    }
+}
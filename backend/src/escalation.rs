@@ -0,0 +1,462 @@
+use chrono::{Local, NaiveDate};
+use lettre::message::{header::ContentType, Mailbox};
+use lettre::{AsyncTransport, Message};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::error::ApiError;
+use crate::{lock, reminders, AppState, CustomFieldDef, Employee, Status};
+
+pub const SETTINGS_FILE: &str = "escalations.json";
+pub const STATE_FILE: &str = "escalations_state.json";
+const MAX_AFTER_DAYS: u32 = 3650;
+const MAX_COMMENT_LEN: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Recipient {
+    // Adresse aus dem Führungskraft-Feld der Erinnerungen
+    Manager,
+    // Adresse aus `department_head_field`
+    DepartmentHead,
+    // Feste Adressen aus `admin_emails`
+    Admin,
+}
+
+/// Eine Eskalationsstufe: ab `after_days` Tagen Überfälligkeit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EscalationRule {
+    pub after_days: u32,
+    pub notify: Vec<Recipient>,
+}
+
+/// Einstellungen der Eskalation (`escalations.json`). Versand über die
+/// SMTP-Einstellungen der Erinnerungen.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EscalationSettings {
+    pub enabled: bool,
+    // Stufe 1, 2, ... in aufsteigender Reihenfolge von `after_days`
+    pub rules: Vec<EscalationRule>,
+    pub department_head_field: String,
+    pub admin_emails: Vec<String>,
+}
+
+impl Default for EscalationSettings {
+    fn default() -> Self {
+        EscalationSettings {
+            enabled: false,
+            rules: vec![
+                EscalationRule { after_days: 14, notify: vec![Recipient::Manager] },
+                EscalationRule { after_days: 30, notify: vec![Recipient::DepartmentHead, Recipient::Admin] },
+            ],
+            department_head_field: "department_head_email".to_string(),
+            admin_emails: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Note {
+    pub by: String,
+    pub at: String,
+    pub comment: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Action {
+    Escalated,
+    Notified,
+    Acknowledged,
+    Cleared,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Event {
+    pub at: String,
+    pub action: Action,
+    pub level: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub by: Option<String>,
+    pub comment: String,
+}
+
+/// Eskalationsstand eines überfälligen Eintrags. Gilt für genau eine
+/// Fälligkeit – wird der Termin verschoben oder erledigt, entfällt er.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Escalation {
+    pub employee_id: u32,
+    pub duedate: String,
+    pub level: usize,
+    pub escalated_at: String,
+    // Höchste Stufe, zu der die Benachrichtigung rausging
+    pub notified_level: usize,
+    pub acknowledged: Option<Note>,
+    // Aufgehoben: keine weitere Eskalation für diese Fälligkeit
+    pub cleared: Option<Note>,
+    pub events: Vec<Event>,
+}
+
+#[derive(Deserialize)]
+pub struct CommentRequest {
+    pub comment: String,
+}
+
+fn field_error(field: &str, message: impl Into<String>) -> ApiError {
+    let mut errors = BTreeMap::new();
+    errors.insert(field.to_string(), message.into());
+    ApiError::Validation(errors)
+}
+
+impl EscalationSettings {
+    pub fn validate(&self, defs: &[CustomFieldDef]) -> Result<(), ApiError> {
+        let mut errors = BTreeMap::new();
+        let mut previous = 0;
+        for (index, rule) in self.rules.iter().enumerate() {
+            if rule.after_days == 0 || rule.after_days > MAX_AFTER_DAYS {
+                errors.insert(
+                    format!("rules.{}.after_days", index),
+                    format!("Tage zwischen 1 und {} erwartet", MAX_AFTER_DAYS),
+                );
+            } else if rule.after_days <= previous {
+                errors.insert(format!("rules.{}.after_days", index), "Stufen aufsteigend angeben".to_string());
+            }
+            previous = rule.after_days;
+            if rule.notify.is_empty() {
+                errors.insert(format!("rules.{}.notify", index), "Mindestens ein Empfänger".to_string());
+            }
+        }
+        if !self.department_head_field.is_empty() && !defs.iter().any(|d| d.name == self.department_head_field) {
+            errors.insert(
+                "department_head_field".to_string(),
+                format!("Unbekanntes Zusatzfeld: {}", self.department_head_field),
+            );
+        }
+        for (index, address) in self.admin_emails.iter().enumerate() {
+            if address.trim().parse::<Mailbox>().is_err() {
+                errors.insert(format!("admin_emails.{}", index), "Keine gültige Adresse".to_string());
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ApiError::Validation(errors))
+        }
+    }
+
+    /// Erreichte Stufe bei `days` Tagen Überfälligkeit, 0 = keine.
+    fn level(&self, days: i64) -> usize {
+        self.rules.iter().filter(|r| i64::from(r.after_days) <= days).count()
+    }
+}
+
+/// Nach dem Kürzen der Regeln: Stufen, die es nicht mehr gibt, auf die
+/// höchste verbliebene zurücksetzen. `true`, wenn sich etwas geändert hat.
+pub fn clamp_levels(escalations: &mut [Escalation], rules: usize) -> bool {
+    let mut changed = false;
+    for escalation in escalations.iter_mut().filter(|s| s.level > rules) {
+        escalation.level = rules;
+        escalation.notified_level = escalation.notified_level.min(rules);
+        changed = true;
+    }
+    changed
+}
+
+/// Regel der erreichten, aber noch nicht benachrichtigten Stufe.
+fn pending_rule<'a>(settings: &'a EscalationSettings, escalation: &Escalation) -> Option<&'a EscalationRule> {
+    if escalation.cleared.is_some() || escalation.notified_level >= escalation.level {
+        return None;
+    }
+    settings.rules.get(escalation.level.checked_sub(1)?)
+}
+
+pub fn days_overdue(e: &Employee, today: NaiveDate) -> Option<i64> {
+    if e.status != Status::Rot {
+        return None;
+    }
+    let due = NaiveDate::parse_from_str(&e.duedate, "%Y-%m-%d").ok()?;
+    let days = (today - due).num_days();
+    (days >= 0).then_some(days)
+}
+
+fn recipients(
+    settings: &EscalationSettings,
+    manager_field: &str,
+    rule: &EscalationRule,
+    e: &Employee,
+) -> Vec<String> {
+    let field = |name: &str| {
+        (!name.is_empty())
+            .then(|| e.custom_fields.get(name))
+            .flatten()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+    let mut to = Vec::new();
+    for recipient in &rule.notify {
+        match recipient {
+            Recipient::Manager => to.extend(field(manager_field)),
+            Recipient::DepartmentHead => to.extend(field(&settings.department_head_field)),
+            Recipient::Admin => to.extend(settings.admin_emails.iter().map(|a| a.trim().to_string())),
+        }
+    }
+    to.sort();
+    to.dedup();
+    to
+}
+
+fn message(from: &str, to: &[String], e: &Employee, level: usize, days: i64) -> Result<Message, String> {
+    let invalid = |a: &str| format!("Ungültige Adresse: {}", a);
+    let mut builder = Message::builder()
+        .from(from.parse().map_err(|_| invalid(from))?)
+        .subject(format!("Eskalation Stufe {}: „{}“ – {}", level, e.training, e.name))
+        .header(ContentType::TEXT_PLAIN);
+    for address in to {
+        builder = builder.to(address.parse().map_err(|_| invalid(address))?);
+    }
+    let due = NaiveDate::parse_from_str(&e.duedate, "%Y-%m-%d")
+        .map(|d| d.format("%d.%m.%Y").to_string())
+        .unwrap_or_else(|_| e.duedate.clone());
+    builder
+        .body(format!(
+            "Die Schulung „{}“ von {} war am {} fällig und ist seit {} Tagen überfällig.\n\
+             Eskalationsstufe {} – bitte im Training Dashboard zur Kenntnis nehmen oder aufheben.\n\n\
+             Training Dashboard",
+            e.training, e.name, due, days, level
+        ))
+        .map_err(|err| err.to_string())
+}
+
+/// Gleicht den Eskalationsstand mit den Einträgen ab und benachrichtigt bei
+/// neu erreichten Stufen. Fehlgeschlagene Nachrichten werden beim nächsten
+/// Lauf wiederholt.
+pub async fn run(data: &AppState) -> Result<String, ApiError> {
+    let settings = lock(&data.escalation_settings).clone();
+    if !settings.enabled {
+        return Ok("Eskalationen sind deaktiviert".to_string());
+    }
+    let reminder_settings = data.mail_settings();
+    let employees = lock(&data.employees).clone();
+    let today = Local::now().date_naive();
+    let now = Local::now().to_rfc3339();
+
+    let mut escalated = 0;
+    let mut pending = Vec::new();
+    {
+        let mut state = lock(&data.escalations);
+        clamp_levels(&mut state, settings.rules.len());
+        let previous: BTreeMap<u32, Escalation> = state.drain(..).map(|s| (s.employee_id, s)).collect();
+        for e in &employees {
+            let Some(days) = days_overdue(e, today) else {
+                continue;
+            };
+            let current = previous.get(&e.id).filter(|s| s.duedate == e.duedate).cloned();
+            let level = settings.level(days);
+            let mut escalation = match current {
+                Some(escalation) => escalation,
+                None if level == 0 => continue,
+                None => Escalation {
+                    employee_id: e.id,
+                    duedate: e.duedate.clone(),
+                    level: 0,
+                    escalated_at: now.clone(),
+                    notified_level: 0,
+                    acknowledged: None,
+                    cleared: None,
+                    events: Vec::new(),
+                },
+            };
+            if escalation.cleared.is_none() && level > escalation.level {
+                escalation.level = level;
+                escalation.escalated_at = now.clone();
+                // Neue Stufe muss neu zur Kenntnis genommen werden
+                escalation.acknowledged = None;
+                escalation.events.push(Event {
+                    at: now.clone(),
+                    action: Action::Escalated,
+                    level,
+                    by: None,
+                    comment: format!("{} Tage überfällig", days),
+                });
+                escalated += 1;
+            }
+            if let Some(rule) = pending_rule(&settings, &escalation) {
+                let to = recipients(&settings, &reminder_settings.manager_email_field, rule, e);
+                pending.push((e.clone(), escalation.level, days, to));
+            }
+            state.push(escalation);
+        }
+    }
+    data.save_escalations()?;
+
+    let (mut notified, mut failed) = (0, 0);
+    let mut outcomes = Vec::new();
+    // Abgeschaltete Benachrichtigungen: Mails folgen beim nächsten Lauf danach
+    if !pending.is_empty() && data.config.notifications.enabled {
+        let mailer = reminders::transport(&reminder_settings.smtp)?;
+        for (e, level, days, to) in pending {
+            let outcome = if to.is_empty() {
+                Err("Keine E-Mail-Adresse hinterlegt".to_string())
+            } else {
+                match message(&reminder_settings.smtp.from, &to, &e, level, days) {
+                    Ok(message) => mailer.send(message).await.map(|_| ()).map_err(|err| err.to_string()),
+                    Err(err) => Err(err),
+                }
+            };
+            match &outcome {
+                Ok(()) => notified += 1,
+                Err(_) => failed += 1,
+            }
+            outcomes.push((e.id, level, to, outcome));
+        }
+    }
+
+    {
+        let mut state = lock(&data.escalations);
+        for (id, level, to, outcome) in outcomes {
+            let Some(escalation) = state.iter_mut().find(|s| s.employee_id == id) else {
+                continue;
+            };
+            match outcome {
+                Ok(()) => {
+                    escalation.notified_level = level;
+                    escalation.events.push(Event {
+                        at: Local::now().to_rfc3339(),
+                        action: Action::Notified,
+                        level,
+                        by: None,
+                        comment: to.join(", "),
+                    });
+                }
+                // Nur im Job-Ergebnis melden, sonst wächst der Verlauf bei jedem Lauf
+                Err(err) => tracing::warn!(id, error = %err, "Eskalation nicht verschickt"),
+            }
+        }
+    }
+    if notified > 0 {
+        data.save_escalations()?;
+    }
+
+    let message = format!(
+        "{} eskaliert, {} benachrichtigt, {} fehlgeschlagen",
+        escalated, notified, failed
+    );
+    if failed > 0 {
+        return Err(ApiError::bad_gateway("smtp_failed", message));
+    }
+    Ok(message)
+}
+
+/// Vermerkt Kenntnisnahme (`clear == false`) oder hebt die Eskalation für
+/// die aktuelle Fälligkeit auf.
+pub fn annotate(data: &AppState, id: u32, username: &str, comment: &str, clear: bool) -> Result<Escalation, ApiError> {
+    let comment = comment.trim();
+    if comment.is_empty() {
+        return Err(field_error("comment", "Kommentar fehlt"));
+    }
+    if comment.chars().count() > MAX_COMMENT_LEN {
+        return Err(field_error("comment", format!("Höchstens {} Zeichen", MAX_COMMENT_LEN)));
+    }
+
+    let mut state = lock(&data.escalations);
+    let escalation = state
+        .iter_mut()
+        .find(|s| s.employee_id == id)
+        .ok_or(ApiError::NotFound("Eskalation"))?;
+    if escalation.cleared.is_some() {
+        return Err(ApiError::conflict("escalation_cleared", "Eskalation ist bereits aufgehoben"));
+    }
+
+    let note = Note {
+        by: username.to_string(),
+        at: Local::now().to_rfc3339(),
+        comment: comment.to_string(),
+    };
+    escalation.events.push(Event {
+        at: note.at.clone(),
+        action: if clear { Action::Cleared } else { Action::Acknowledged },
+        level: escalation.level,
+        by: Some(note.by.clone()),
+        comment: note.comment.clone(),
+    });
+    if clear {
+        escalation.cleared = Some(note);
+    } else {
+        escalation.acknowledged = Some(note);
+    }
+    let escalation = escalation.clone();
+    drop(state);
+
+    data.save_escalations()?;
+    Ok(escalation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(after_days: &[u32]) -> EscalationSettings {
+        EscalationSettings {
+            enabled: true,
+            rules: after_days
+                .iter()
+                .map(|&after_days| EscalationRule { after_days, notify: vec![Recipient::Admin] })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn escalation(level: usize, notified_level: usize) -> Escalation {
+        Escalation {
+            employee_id: 1,
+            duedate: "2026-01-01".to_string(),
+            level,
+            escalated_at: "2026-02-01T00:00:00+01:00".to_string(),
+            notified_level,
+            acknowledged: None,
+            cleared: None,
+            events: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn level_counts_reached_rules() {
+        let settings = settings(&[14, 30]);
+        assert_eq!(settings.level(13), 0);
+        assert_eq!(settings.level(14), 1);
+        assert_eq!(settings.level(45), 2);
+    }
+
+    #[test]
+    fn shrunk_rules_clamp_unnotified_escalation() {
+        let mut state = vec![escalation(2, 0), escalation(1, 1)];
+        let shrunk = settings(&[14]);
+        // Ohne Abgleich darf die fehlende Stufe nicht zum Absturz führen
+        assert!(pending_rule(&shrunk, &state[0]).is_none());
+
+        assert!(clamp_levels(&mut state, shrunk.rules.len()));
+        assert_eq!((state[0].level, state[0].notified_level), (1, 0));
+        assert_eq!((state[1].level, state[1].notified_level), (1, 1));
+        assert_eq!(pending_rule(&shrunk, &state[0]).map(|r| r.after_days), Some(14));
+        assert!(pending_rule(&shrunk, &state[1]).is_none());
+        assert!(!clamp_levels(&mut state, shrunk.rules.len()));
+    }
+
+    #[test]
+    fn emptied_rules_leave_nothing_to_notify() {
+        let mut state = vec![escalation(2, 1)];
+        let empty = EscalationSettings { department_head_field: String::new(), ..settings(&[]) };
+        assert!(empty.validate(&[]).is_ok());
+        assert!(clamp_levels(&mut state, 0));
+        assert_eq!((state[0].level, state[0].notified_level), (0, 0));
+        assert!(pending_rule(&empty, &state[0]).is_none());
+    }
+
+    #[test]
+    fn cleared_escalation_is_not_notified() {
+        let mut cleared = escalation(1, 0);
+        cleared.cleared = Some(Note { by: "admin".to_string(), at: String::new(), comment: "erledigt".to_string() });
+        assert!(pending_rule(&settings(&[14]), &cleared).is_none());
+    }
+}
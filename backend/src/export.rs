@@ -0,0 +1,125 @@
+use chrono::{Datelike, NaiveDate};
+use rust_xlsxwriter::{ExcelDateTime, Format, Workbook};
+use serde::Deserialize;
+
+use crate::error::ApiError;
+use crate::{CustomFieldDef, Employee, FieldKind};
+
+#[derive(Deserialize)]
+pub struct ExportFormat {
+    // "csv" (Standard), "xlsx" oder "json"
+    pub format: Option<String>,
+}
+
+pub struct ExportFile {
+    pub content_type: &'static str,
+    pub extension: &'static str,
+    pub body: Vec<u8>,
+}
+
+// Überschriften entsprechen den Feldnamen, damit der Import sie wiedererkennt
+const BASE_COLUMNS: [&str; 7] = ["id", "name", "training", "duedate", "status", "created_by", "tags"];
+
+fn base_values(e: &Employee) -> [String; 7] {
+    [
+        e.id.to_string(),
+        e.name.clone(),
+        e.training.clone(),
+        e.duedate.clone(),
+        e.status.as_str().to_string(),
+        e.created_by.clone(),
+        e.tags.join("; "),
+    ]
+}
+
+pub fn export(format: &str, employees: &[&Employee], defs: &[CustomFieldDef]) -> Result<ExportFile, ApiError> {
+    match format {
+        "csv" => Ok(ExportFile {
+            content_type: "text/csv; charset=utf-8",
+            extension: "csv",
+            body: to_csv(employees, defs)?,
+        }),
+        "xlsx" => Ok(ExportFile {
+            content_type: "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+            extension: "xlsx",
+            body: to_xlsx(employees, defs)?,
+        }),
+        "json" => Ok(ExportFile {
+            content_type: "application/json",
+            extension: "json",
+            body: serde_json::to_vec_pretty(employees)?,
+        }),
+        _ => Err(ApiError::bad_request("invalid_format", "Erlaubt: csv, xlsx, json")),
+    }
+}
+
+fn to_csv(employees: &[&Employee], defs: &[CustomFieldDef]) -> Result<Vec<u8>, ApiError> {
+    // BOM, damit Excel Umlaute richtig anzeigt
+    let mut writer = csv::Writer::from_writer(b"\xEF\xBB\xBF".to_vec());
+
+    let header = BASE_COLUMNS
+        .iter()
+        .map(|c| c.to_string())
+        .chain(defs.iter().map(|d| d.label.clone()));
+    writer.write_record(header)?;
+
+    for e in employees {
+        let row = base_values(e)
+            .into_iter()
+            .chain(defs.iter().map(|d| e.custom_fields.get(&d.name).cloned().unwrap_or_default()));
+        writer.write_record(row)?;
+    }
+
+    writer
+        .into_inner()
+        .map_err(|e| ApiError::Internal(format!("CSV-Fehler: {}", e)))
+}
+
+fn excel_date(value: &str) -> Option<ExcelDateTime> {
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()?;
+    ExcelDateTime::from_ymd(date.year().try_into().ok()?, date.month() as u8, date.day() as u8).ok()
+}
+
+fn to_xlsx(employees: &[&Employee], defs: &[CustomFieldDef]) -> Result<Vec<u8>, ApiError> {
+    let mut workbook = Workbook::new();
+    let sheet = workbook.add_worksheet();
+    sheet.set_name("Schulungen")?;
+
+    let bold = Format::new().set_bold();
+    let date = Format::new().set_num_format("yyyy-mm-dd");
+
+    let headers = BASE_COLUMNS.iter().map(|c| c.to_string()).chain(defs.iter().map(|d| d.label.clone()));
+    for (col, header) in headers.enumerate() {
+        sheet.write_string_with_format(0, col as u16, header, &bold)?;
+    }
+
+    for (i, e) in employees.iter().enumerate() {
+        let row = i as u32 + 1;
+        for (col, value) in base_values(e).iter().enumerate() {
+            let col = col as u16;
+            match (BASE_COLUMNS[col as usize], excel_date(value)) {
+                ("id", _) => sheet.write_number(row, col, e.id)?,
+                ("duedate", Some(d)) => sheet.write_date_with_format(row, col, &d, &date)?,
+                _ => sheet.write_string(row, col, value)?,
+            };
+        }
+
+        for (j, def) in defs.iter().enumerate() {
+            let Some(value) = e.custom_fields.get(&def.name) else {
+                continue;
+            };
+            let col = (BASE_COLUMNS.len() + j) as u16;
+            let number = (def.kind == FieldKind::Number).then(|| value.parse::<f64>().ok()).flatten();
+            let day = (def.kind == FieldKind::Date).then(|| excel_date(value)).flatten();
+            match (number, day) {
+                (Some(n), _) => sheet.write_number(row, col, n)?,
+                (_, Some(d)) => sheet.write_date_with_format(row, col, &d, &date)?,
+                _ => sheet.write_string(row, col, value)?,
+            };
+        }
+    }
+
+    sheet.set_freeze_panes(1, 0)?;
+    sheet.autofit();
+    Ok(workbook.save_to_buffer()?)
+}
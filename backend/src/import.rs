@@ -0,0 +1,484 @@
+use base64::Engine;
+use calamine::{Data, Reader};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Cursor;
+
+use crate::error::ApiError;
+use crate::{
+    live, lock, same_person, unmet_prerequisites, validate_employee_input, webhooks, AppState, CustomFieldDef, Employee,
+    EmployeeInput, Session,
+};
+
+pub const MAX_IMPORT_ROWS: usize = 5000;
+pub const MAX_IMPORT_BYTES: usize = 16 * 1024 * 1024;
+
+const BASE_FIELDS: [&str; 6] = ["id", "name", "training", "duedate", "status", "tags"];
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Deserialize)]
+pub struct ImportRequest {
+    // "csv" oder "xlsx"
+    pub format: String,
+    // Dateiinhalt als Base64
+    pub data: String,
+    // Feld -> Spaltenüberschrift; leer = automatisch anhand der Überschriften
+    #[serde(default)]
+    pub mapping: BTreeMap<String, String>,
+    // Standard ist die Vorschau – geschrieben wird erst mit dry_run=false
+    #[serde(default = "default_true")]
+    pub dry_run: bool,
+    // Fehlerhafte Zeilen beim Übernehmen auslassen statt abzubrechen
+    #[serde(default)]
+    pub skip_errors: bool,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportAction {
+    Create,
+    Update,
+    Unchanged,
+    Error,
+}
+
+#[derive(Serialize)]
+pub struct ImportRow {
+    // Zeilennummer in der Datei (Überschrift = 1)
+    pub row: usize,
+    pub action: ImportAction,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub employee: Option<Employee>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub fields: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+#[derive(Serialize, Default)]
+pub struct ImportSummary {
+    pub create: usize,
+    pub update: usize,
+    pub unchanged: usize,
+    pub error: usize,
+}
+
+#[derive(Serialize)]
+pub struct ImportReport {
+    pub columns: Vec<String>,
+    pub mapping: BTreeMap<String, String>,
+    pub committed: bool,
+    pub summary: ImportSummary,
+    pub rows: Vec<ImportRow>,
+}
+
+/// Liest die Tabelle als Zeilen von Zellen; die erste Zeile ist die Überschrift.
+fn read_table(format: &str, bytes: Vec<u8>) -> Result<Vec<Vec<String>>, ApiError> {
+    match format {
+        "csv" => read_csv(bytes),
+        "xlsx" => read_xlsx(bytes),
+        _ => Err(ApiError::bad_request("invalid_format", "Erlaubt: csv, xlsx")),
+    }
+}
+
+fn read_csv(bytes: Vec<u8>) -> Result<Vec<Vec<String>>, ApiError> {
+    let text = match String::from_utf8(bytes) {
+        Ok(text) => text,
+        // Excel speichert CSV unter Windows gern als Latin-1
+        Err(err) => err.into_bytes().iter().map(|&b| b as char).collect(),
+    };
+    let text = text.trim_start_matches('\u{feff}');
+
+    // Deutsches Excel trennt mit Semikolon
+    let first_line = text.lines().next().unwrap_or("");
+    let delimiter = if first_line.matches(';').count() > first_line.matches(',').count() {
+        b';'
+    } else {
+        b','
+    };
+
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(text.as_bytes());
+
+    reader
+        .records()
+        .map(|record| {
+            record
+                .map(|r| r.iter().map(|cell| cell.trim().to_string()).collect())
+                .map_err(|e| ApiError::bad_request("invalid_file", format!("CSV nicht lesbar: {}", e)))
+        })
+        .collect()
+}
+
+fn read_xlsx(bytes: Vec<u8>) -> Result<Vec<Vec<String>>, ApiError> {
+    let unreadable = |e: calamine::Error| ApiError::bad_request("invalid_file", format!("Excel-Datei nicht lesbar: {}", e));
+
+    let mut workbook = calamine::open_workbook_auto_from_rs(Cursor::new(bytes)).map_err(unreadable)?;
+    let range = workbook
+        .worksheet_range_at(0)
+        .ok_or_else(|| ApiError::bad_request("invalid_file", "Excel-Datei enthält kein Tabellenblatt"))?
+        .map_err(unreadable)?;
+
+    Ok(range
+        .rows()
+        .map(|row| row.iter().map(cell_to_string).collect())
+        .collect())
+}
+
+fn cell_to_string(cell: &Data) -> String {
+    match cell {
+        Data::DateTime(dt) => dt
+            .as_datetime()
+            .map(|d| d.date().format("%Y-%m-%d").to_string())
+            .unwrap_or_default(),
+        Data::DateTimeIso(value) => value.chars().take(10).collect(),
+        Data::Empty | Data::Error(_) => String::new(),
+        other => other.to_string().trim().to_string(),
+    }
+}
+
+/// Ordnet Überschriften automatisch Feldern zu (Feldname, deutsche
+/// Bezeichnung oder Label eines Zusatzfelds).
+fn auto_mapping(columns: &[String], defs: &[CustomFieldDef]) -> BTreeMap<String, String> {
+    let mut mapping = BTreeMap::new();
+    for column in columns {
+        let key = column.trim().to_lowercase();
+        let field = match key.as_str() {
+            "id" | "nr" => Some("id".to_string()),
+            "name" | "mitarbeiter" | "person" => Some("name".to_string()),
+            "training" | "schulung" => Some("training".to_string()),
+            "duedate" | "fällig" | "fälligkeit" | "faellig" => Some("duedate".to_string()),
+            "status" => Some("status".to_string()),
+            "tags" => Some("tags".to_string()),
+            _ => defs
+                .iter()
+                .find(|d| d.name.to_lowercase() == key || d.label.to_lowercase() == key)
+                .map(|d| format!("custom_fields.{}", d.name)),
+        };
+        if let Some(field) = field {
+            mapping.entry(field).or_insert_with(|| column.clone());
+        }
+    }
+    mapping
+}
+
+/// Prüft die Zuordnung und liefert Feld -> Spaltenindex.
+fn resolve_mapping(
+    mapping: &BTreeMap<String, String>,
+    columns: &[String],
+    defs: &[CustomFieldDef],
+) -> Result<BTreeMap<String, usize>, ApiError> {
+    let mut errors = BTreeMap::new();
+    let mut resolved = BTreeMap::new();
+
+    for (field, column) in mapping {
+        let known = BASE_FIELDS.contains(&field.as_str())
+            || field
+                .strip_prefix("custom_fields.")
+                .is_some_and(|name| defs.iter().any(|d| d.name == name));
+        if !known {
+            errors.insert(format!("mapping.{}", field), "Unbekanntes Feld".to_string());
+            continue;
+        }
+        match columns.iter().position(|c| c == column) {
+            Some(index) => {
+                resolved.insert(field.clone(), index);
+            }
+            None => {
+                errors.insert(format!("mapping.{}", field), format!("Spalte \"{}\" fehlt", column));
+            }
+        }
+    }
+
+    let identifies = resolved.contains_key("id") || (resolved.contains_key("name") && resolved.contains_key("training"));
+    if errors.is_empty() && !identifies {
+        errors.insert(
+            "mapping".to_string(),
+            "Benötigt wird eine ID-Spalte oder Name und Training".to_string(),
+        );
+    }
+
+    if errors.is_empty() {
+        Ok(resolved)
+    } else {
+        Err(ApiError::Validation(errors))
+    }
+}
+
+fn normalize_date(value: &str) -> String {
+    ["%Y-%m-%d", "%d.%m.%Y"]
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(value, format).ok())
+        .map(|date| date.format("%Y-%m-%d").to_string())
+        .unwrap_or_else(|| value.to_string())
+}
+
+fn normalize_status(value: &str) -> String {
+    match value.trim().to_lowercase().as_str() {
+        "grün" => "gruen".to_string(),
+        other => other.to_string(),
+    }
+}
+
+fn single_error(field: &str, message: &str) -> ApiError {
+    let mut errors = BTreeMap::new();
+    errors.insert(field.to_string(), message.to_string());
+    ApiError::Validation(errors)
+}
+
+struct RowPlan {
+    action: ImportAction,
+    employee: Employee,
+    warnings: Vec<String>,
+}
+
+/// Baut aus einer Tabellenzeile den neuen bzw. geänderten Eintrag. Leere
+/// Zellen lassen bestehende Werte unverändert.
+fn plan_row(
+    data: &AppState,
+    session: &Session,
+    catalog: &[crate::CatalogEntry],
+    working: &[Employee],
+    next_id: u32,
+    mapping: &BTreeMap<String, usize>,
+    cells: &[String],
+) -> Result<RowPlan, ApiError> {
+    let cell = |field: &str| {
+        mapping
+            .get(field)
+            .and_then(|&i| cells.get(i))
+            .map(|v| v.trim())
+            .filter(|v| !v.is_empty())
+    };
+
+    let existing = match cell("id") {
+        Some(id) => {
+            let id: u32 = id.parse().map_err(|_| single_error("id", "Keine gültige ID"))?;
+            Some(
+                working
+                    .iter()
+                    .find(|e| e.id == id)
+                    .ok_or(ApiError::NotFound("Eintrag"))?,
+            )
+        }
+        None => match (cell("name"), cell("training")) {
+            (Some(name), Some(training)) => working
+                .iter()
+                .find(|e| same_person(&e.name, name) && e.training.eq_ignore_ascii_case(training)),
+            _ => None,
+        },
+    };
+
+    let mut input = match existing {
+        Some(e) => EmployeeInput {
+            name: e.name.clone(),
+            training: e.training.clone(),
+            duedate: e.duedate.clone(),
+            status: e.status.as_str().to_string(),
+            custom_fields: e.custom_fields.clone(),
+            tags: e.tags.clone(),
+            version: None,
+        },
+        None => EmployeeInput {
+            name: String::new(),
+            training: String::new(),
+            duedate: String::new(),
+            // Tabellen aus der Personalabteilung haben selten einen Status
+            status: "gruen".to_string(),
+            custom_fields: BTreeMap::new(),
+            tags: Vec::new(),
+            version: None,
+        },
+    };
+
+    if let Some(v) = cell("name") {
+        input.name = v.to_string();
+    }
+    if let Some(v) = cell("training") {
+        input.training = v.to_string();
+    }
+    if let Some(v) = cell("duedate") {
+        input.duedate = normalize_date(v);
+    }
+    if let Some(v) = cell("status") {
+        input.status = normalize_status(v);
+    }
+    if let Some(v) = cell("tags") {
+        input.tags = v
+            .split([';', ','])
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .map(String::from)
+            .collect();
+    }
+    for field in mapping.keys() {
+        if let (Some(name), Some(v)) = (field.strip_prefix("custom_fields."), cell(field)) {
+            input.custom_fields.insert(name.to_string(), v.to_string());
+        }
+    }
+
+    let status = validate_employee_input(data, &mut input)?;
+
+    let needs_check = existing.is_none_or(|e| e.name != input.name || e.training != input.training);
+    let mut warnings = Vec::new();
+    if needs_check {
+        let (unmet, strict) = unmet_prerequisites(catalog, working, &input.name, &input.training);
+        if strict && !unmet.is_empty() {
+            return Err(ApiError::PrerequisitesMissing(unmet));
+        }
+        warnings = unmet;
+    }
+
+    let mut employee = Employee {
+        id: existing.map_or(next_id, |e| e.id),
+        name: input.name,
+        training: input.training,
+        duedate: input.duedate,
+        status,
+        created_by: existing.map_or_else(|| session.username.clone(), |e| e.created_by.clone()),
+        custom_fields: input.custom_fields,
+        tags: input.tags,
+        version: existing.map_or(1, |e| e.version),
+    };
+
+    let action = match existing {
+        None => ImportAction::Create,
+        Some(e) if same_content(e, &employee) => ImportAction::Unchanged,
+        Some(_) => {
+            employee.version += 1;
+            ImportAction::Update
+        }
+    };
+
+    Ok(RowPlan { action, employee, warnings })
+}
+
+fn same_content(a: &Employee, b: &Employee) -> bool {
+    a.name == b.name
+        && a.training == b.training
+        && a.duedate == b.duedate
+        && a.status == b.status
+        && a.custom_fields == b.custom_fields
+        && a.tags == b.tags
+}
+
+/// Prüft alle Zeilen gegen eine Kopie des Bestands. Ohne `dry_run` wird das
+/// Ergebnis übernommen – bei Fehlern nur mit `skip_errors`.
+pub fn run(data: &AppState, session: &Session, request: ImportRequest) -> Result<ImportReport, ApiError> {
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(request.data.trim())
+        .map_err(|_| ApiError::bad_request("invalid_file", "Dateiinhalt ist kein gültiges Base64"))?;
+
+    let mut table = read_table(&request.format, bytes)?.into_iter();
+    let columns = table
+        .next()
+        .ok_or_else(|| ApiError::bad_request("invalid_file", "Datei ist leer"))?;
+    let rows: Vec<Vec<String>> = table.filter(|r| r.iter().any(|c| !c.is_empty())).collect();
+    if rows.len() > MAX_IMPORT_ROWS {
+        return Err(ApiError::bad_request(
+            "too_many_items",
+            format!("Höchstens {} Zeilen pro Import", MAX_IMPORT_ROWS),
+        ));
+    }
+
+    let defs = lock(&data.custom_fields).clone();
+    let mapping = if request.mapping.is_empty() {
+        auto_mapping(&columns, &defs)
+    } else {
+        request.mapping
+    };
+    let resolved = resolve_mapping(&mapping, &columns, &defs)?;
+    let catalog = lock(&data.catalog).clone();
+
+    let mut employees = lock(&data.employees);
+    let mut next_id = lock(&data.next_employee_id);
+    let mut working = employees.clone();
+    let mut working_next_id = *next_id;
+
+    let mut summary = ImportSummary::default();
+    let mut report_rows = Vec::with_capacity(rows.len());
+
+    for (index, cells) in rows.iter().enumerate() {
+        let row = index + 2;
+        match plan_row(data, session, &catalog, &working, working_next_id, &resolved, cells) {
+            Ok(plan) => {
+                match plan.action {
+                    ImportAction::Create => {
+                        summary.create += 1;
+                        working_next_id += 1;
+                        working.push(plan.employee.clone());
+                    }
+                    ImportAction::Update => {
+                        summary.update += 1;
+                        if let Some(e) = working.iter_mut().find(|e| e.id == plan.employee.id) {
+                            *e = plan.employee.clone();
+                        }
+                    }
+                    ImportAction::Unchanged => summary.unchanged += 1,
+                    ImportAction::Error => {}
+                }
+                report_rows.push(ImportRow {
+                    row,
+                    action: plan.action,
+                    employee: Some(plan.employee),
+                    error: None,
+                    fields: BTreeMap::new(),
+                    warnings: plan.warnings,
+                });
+            }
+            Err(err) => {
+                summary.error += 1;
+                let error = err.to_string();
+                let (fields, warnings) = match err {
+                    ApiError::Validation(fields) => (fields, Vec::new()),
+                    ApiError::PrerequisitesMissing(missing) => (BTreeMap::new(), missing),
+                    _ => (BTreeMap::new(), Vec::new()),
+                };
+                report_rows.push(ImportRow {
+                    row,
+                    action: ImportAction::Error,
+                    employee: None,
+                    error: Some(error),
+                    fields,
+                    warnings,
+                });
+            }
+        }
+    }
+
+    let commit = !request.dry_run;
+    if commit && summary.error > 0 && !request.skip_errors {
+        return Err(ApiError::bad_request(
+            "import_has_errors",
+            format!("{} Zeilen fehlerhaft – korrigieren oder mit skip_errors überspringen", summary.error),
+        ));
+    }
+
+    if commit {
+        let before = std::mem::replace(&mut *employees, working);
+        *next_id = working_next_id;
+        let after = employees.clone();
+        drop(employees);
+        drop(next_id);
+        data.save_employees()?;
+        live::employees_changed(data, &before, &after);
+        webhooks::employees_changed(data, &before, &after);
+    }
+
+    Ok(ImportReport {
+        columns,
+        mapping,
+        committed: commit,
+        summary,
+        rows: report_rows,
+    })
+}
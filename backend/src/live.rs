@@ -0,0 +1,106 @@
+use actix_web::web::{self, Bytes};
+use futures_util::stream::{self, Stream, StreamExt};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::MissedTickBehavior;
+
+use crate::{AppState, Employee, Role, Session, UserResponse};
+
+// Puffer je Verbindung; wer weiter zurückliegt, bekommt `resync`
+pub const CAPACITY: usize = 256;
+// Hält Proxys offen und beendet Streams abgemeldeter Sitzungen
+const HEARTBEAT_SECONDS: u64 = 15;
+
+/// Änderung, wie sie an verbundene Oberflächen geht. Der SSE-Ereignisname
+/// entspricht `type`.
+#[derive(Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Change {
+    Employee { employee: Employee },
+    EmployeeDeleted { id: u32 },
+    User { user: UserResponse },
+    UserDeleted { id: String },
+}
+
+impl Change {
+    fn name(&self) -> &'static str {
+        match self {
+            Change::Employee { .. } => "employee",
+            Change::EmployeeDeleted { .. } => "employee_deleted",
+            Change::User { .. } => "user",
+            Change::UserDeleted { .. } => "user_deleted",
+        }
+    }
+
+    // Die Benutzerliste sehen nur Manager und Admins
+    fn visible(&self, role: &Role) -> bool {
+        match self {
+            Change::User { .. } | Change::UserDeleted { .. } => *role != Role::User,
+            _ => true,
+        }
+    }
+}
+
+pub fn send(data: &AppState, change: Change) {
+    // Fehler heißt nur: gerade niemand verbunden
+    let _ = data.live.send(change);
+}
+
+/// Meldet alle Einträge, deren Version sich geändert hat, sowie gelöschte.
+pub fn employees_changed(data: &AppState, before: &[Employee], after: &[Employee]) {
+    let previous: BTreeMap<u32, u64> = before.iter().map(|e| (e.id, e.version)).collect();
+    for e in after {
+        if previous.get(&e.id) != Some(&e.version) {
+            send(data, Change::Employee { employee: e.clone() });
+        }
+    }
+    let current: BTreeMap<u32, u64> = after.iter().map(|e| (e.id, e.version)).collect();
+    for e in before.iter().filter(|e| !current.contains_key(&e.id)) {
+        send(data, Change::EmployeeDeleted { id: e.id });
+    }
+}
+
+fn frame(event: &str, data: &str) -> Bytes {
+    Bytes::from(format!("event: {}\ndata: {}\n\n", event, data))
+}
+
+/// Server-Sent-Events-Stream einer Sitzung. Endet, sobald die Sitzung
+/// abgemeldet oder abgelaufen ist oder der Server beendet wird.
+pub fn stream(data: web::Data<AppState>, session: Session) -> impl Stream<Item = Result<Bytes, actix_web::Error>> {
+    let receiver = data.live.subscribe();
+    let shutdown = data.shutdown.subscribe();
+    let mut heartbeat = tokio::time::interval(Duration::from_secs(HEARTBEAT_SECONDS));
+    heartbeat.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+    let hello = stream::iter([Ok(Bytes::from_static(b"retry: 3000\n\n"))]);
+    let changes = stream::unfold(
+        (receiver, heartbeat, shutdown, data, session),
+        |(mut receiver, mut heartbeat, mut shutdown, data, session)| async move {
+            let bytes = loop {
+                tokio::select! {
+                    change = receiver.recv() => match change {
+                        Ok(change) if change.visible(&session.role) => {
+                            break frame(change.name(), &serde_json::to_string(&change).ok()?);
+                        }
+                        Ok(_) => continue,
+                        Err(RecvError::Lagged(_)) => break frame("resync", "{}"),
+                        Err(RecvError::Closed) => return None,
+                    },
+                    _ = heartbeat.tick() => {
+                        // Zertifikats-Sitzungen haben kein Token und enden mit der Verbindung
+                        if !session.token.is_empty() {
+                            data.verify_token(&session.token)?;
+                        }
+                        break Bytes::from_static(b": ping\n\n");
+                    }
+                    // Sonst hielte jede offene Verbindung das Beenden bis zum Timeout auf
+                    _ = shutdown.changed() => return None,
+                }
+            };
+            Some((Ok(bytes), (receiver, heartbeat, shutdown, data, session)))
+        },
+    );
+    hello.chain(changes)
+}
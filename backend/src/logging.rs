@@ -0,0 +1,114 @@
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::{Error, HttpMessage};
+use std::io::IsTerminal;
+use std::time::Instant;
+use tracing::Span;
+use tracing_actix_web::{RequestId, RootSpanBuilder};
+use tracing_appender::non_blocking::{NonBlockingBuilder, WorkerGuard};
+use tracing_appender::rolling::{Builder, Rotation};
+use tracing_subscriber::EnvFilter;
+
+use crate::config::LogConfig;
+
+// Wird bei jeder Antwort mitgeschickt und vom Frontend in Fehlermeldungen angezeigt
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+const FILE_PREFIX: &str = "backend";
+
+/// Richtet die Ausgabe ein. Der Guard muss bis zum Programmende leben,
+/// sonst gehen beim Beenden die letzten Zeilen der Logdatei verloren.
+pub fn init(log: &LogConfig) -> Result<WorkerGuard, String> {
+    let filter = EnvFilter::try_new(&log.level).map_err(|e| e.to_string())?;
+    // Lieber kurz warten als Zeilen verwerfen, wenn der Puffer voll ist
+    let non_blocking = NonBlockingBuilder::default().lossy(false);
+    let (writer, guard) = if log.dir.as_os_str().is_empty() {
+        non_blocking.finish(std::io::stdout())
+    } else {
+        let rotation = match log.rotation.as_str() {
+            "hourly" => Rotation::HOURLY,
+            "never" => Rotation::NEVER,
+            _ => Rotation::DAILY,
+        };
+        let mut appender = Builder::new().rotation(rotation).filename_prefix(FILE_PREFIX).filename_suffix("log");
+        if log.max_files > 0 {
+            appender = appender.max_log_files(log.max_files);
+        }
+        let appender = std::fs::create_dir_all(&log.dir)
+            .map_err(|e| e.to_string())
+            .and_then(|()| appender.build(&log.dir).map_err(|e| e.to_string()))
+            .map_err(|e| format!("{}: {}", log.dir.display(), e))?;
+        non_blocking.finish(appender)
+    };
+
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(writer)
+        .with_ansi(log.dir.as_os_str().is_empty() && std::io::stdout().is_terminal());
+    let result = if log.format == "json" {
+        builder.json().flatten_event(true).with_current_span(true).with_span_list(false).try_init()
+    } else {
+        builder.try_init()
+    };
+    result.map_err(|e| e.to_string())?;
+    Ok(guard)
+}
+
+struct Started(Instant);
+
+/// Ein Span je Anfrage mit Anfrage-ID und – nach der Anmeldung – dem
+/// Benutzer (`record_user`); am Ende eine Zeile mit Status und Dauer.
+pub struct RequestSpan;
+
+impl RootSpanBuilder for RequestSpan {
+    fn on_request_start(request: &ServiceRequest) -> Span {
+        request.extensions_mut().insert(Started(Instant::now()));
+        let request_id = request.extensions().get::<RequestId>().map(|id| id.to_string()).unwrap_or_default();
+        tracing::info_span!(
+            "request",
+            request_id = %request_id,
+            method = %request.method(),
+            path = %request.path(),
+            user = tracing::field::Empty,
+        )
+    }
+
+    fn on_request_end<B: MessageBody>(span: Span, outcome: &Result<ServiceResponse<B>, Error>) {
+        let _entered = span.enter();
+        let response = match outcome {
+            Ok(response) => response,
+            Err(err) => {
+                tracing::error!(error = %err, "Anfrage abgebrochen");
+                return;
+            }
+        };
+        let status = response.status().as_u16();
+        let duration_ms = response
+            .request()
+            .extensions()
+            .get::<Started>()
+            .map_or(0, |started| started.0.elapsed().as_millis() as u64);
+        let error = response.response().error().map(|e| e.to_string());
+        // 503 ist eine bewusste Ablehnung (Wartungsmodus, Beenden), kein Fehler
+        if status >= 500 && status != 503 {
+            tracing::error!(status, duration_ms, error = error.as_deref(), "Anfrage fehlgeschlagen");
+        } else if let Some(error) = error {
+            tracing::info!(status, duration_ms, error = %error, "Anfrage abgelehnt");
+        } else {
+            tracing::info!(status, duration_ms, "Anfrage beantwortet");
+        }
+    }
+}
+
+/// Hängt den Benutzer an den Span der laufenden Anfrage.
+pub fn record_user(username: &str) {
+    Span::current().record("user", tracing::field::display(username));
+}
+
+/// Setzt `X-Request-Id` auf die Antwort.
+pub fn add_request_id<B>(response: &mut ServiceResponse<B>) {
+    let request_id = response.request().extensions().get::<RequestId>().map(|id| id.to_string());
+    if let Some(value) = request_id.and_then(|id| HeaderValue::from_str(&id).ok()) {
+        response.headers_mut().insert(HeaderName::from_static(REQUEST_ID_HEADER), value);
+    }
+}
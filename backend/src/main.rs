@@ -1,11 +1,39 @@
-use actix_web::{web, App, HttpResponse, HttpServer, Result, middleware, HttpRequest};
+mod bulk;
+mod calendar;
+mod chat;
+mod config;
+mod error;
+mod escalation;
+mod export;
+mod import;
+mod live;
+mod logging;
+mod maintenance;
+mod metrics;
+mod query;
+mod reminders;
+mod reports;
+mod scheduler;
+mod shutdown;
+mod tls;
+mod webhooks;
+
+use actix_web::dev::Service;
+use actix_web::{http::header, web, App, HttpResponse, HttpServer, HttpRequest};
 use actix_cors::Cors;
 use serde::{Deserialize, Serialize};
-use std::sync::Mutex;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
 use std::fs::File;
-use bcrypt::{hash, verify, DEFAULT_COST};
+use bcrypt::{hash, verify};
+use clap::Parser;
+use tracing_actix_web::TracingLogger;
 use uuid::Uuid;
 
+use error::ApiError;
+use query::EmployeeQuery;
+
 // Models
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
@@ -15,6 +43,42 @@ enum Role {
     User,
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum Status {
+    Gruen,
+    Gelb,
+    Rot,
+}
+
+impl Status {
+    fn parse(value: &str) -> Option<Status> {
+        match value.trim().to_lowercase().as_str() {
+            "gruen" => Some(Status::Gruen),
+            "gelb" => Some(Status::Gelb),
+            "rot" => Some(Status::Rot),
+            _ => None,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Status::Gruen => "gruen",
+            Status::Gelb => "gelb",
+            Status::Rot => "rot",
+        }
+    }
+
+    /// Anzeigename für Berichte und Kalender.
+    fn label(self) -> &'static str {
+        match self {
+            Status::Gruen => "Aktuell",
+            Status::Gelb => "Bald fällig",
+            Status::Rot => "Überfällig",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct User {
     id: String,
@@ -28,12 +92,146 @@ struct User {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct Employee {
+    id: u32,
+    name: String,
+    training: String,
+    duedate: String,
+    status: Status,
+    created_by: String,
+    #[serde(default)]
+    custom_fields: BTreeMap<String, String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    // Wird bei jeder Änderung erhöht und dient als ETag
+    #[serde(default)]
+    version: u64,
+}
+
+impl Employee {
+    fn etag(&self) -> String {
+        format!("\"{}\"", self.version)
+    }
+}
+
+// Zeile in employees.csv – Zusatzfelder werden als JSON-Spalte abgelegt,
+// da CSV keine verschachtelten Werte kennt
+#[derive(Serialize, Deserialize)]
+struct EmployeeRow {
     id: u32,
     name: String,
     training: String,
     duedate: String,
     status: String,
     created_by: String,
+    #[serde(default)]
+    custom_fields: String,
+    #[serde(default)]
+    tags: String,
+    #[serde(default)]
+    version: u64,
+}
+
+impl From<&Employee> for EmployeeRow {
+    fn from(e: &Employee) -> Self {
+        EmployeeRow {
+            id: e.id,
+            name: e.name.clone(),
+            training: e.training.clone(),
+            duedate: e.duedate.clone(),
+            status: e.status.as_str().to_string(),
+            created_by: e.created_by.clone(),
+            custom_fields: if e.custom_fields.is_empty() {
+                String::new()
+            } else {
+                serde_json::to_string(&e.custom_fields).unwrap_or_default()
+            },
+            tags: e.tags.join(";"),
+            version: e.version,
+        }
+    }
+}
+
+impl From<EmployeeRow> for Employee {
+    fn from(row: EmployeeRow) -> Self {
+        // Ein unbekannter Status würde das Frontend für alle Benutzer brechen,
+        // daher als überfällig markieren statt den Eintrag zu verwerfen
+        let status = Status::parse(&row.status).unwrap_or_else(|| {
+            tracing::warn!(id = row.id, status = %row.status, "Ungültiger Status, als rot übernommen");
+            Status::Rot
+        });
+
+        Employee {
+            id: row.id,
+            name: row.name,
+            training: row.training,
+            duedate: row.duedate,
+            status,
+            created_by: row.created_by,
+            custom_fields: serde_json::from_str(&row.custom_fields).unwrap_or_default(),
+            tags: row
+                .tags
+                .split(';')
+                .filter(|t| !t.is_empty())
+                .map(String::from)
+                .collect(),
+            // Ältere Dateien kennen noch keine Versionen
+            version: row.version.max(1),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum FieldKind {
+    Text,
+    Number,
+    Date,
+    Enum,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CustomFieldDef {
+    name: String,
+    label: String,
+    kind: FieldKind,
+    #[serde(default)]
+    options: Vec<String>,
+    #[serde(default)]
+    required: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CatalogEntry {
+    name: String,
+    #[serde(default)]
+    prerequisites: Vec<String>,
+    // Bei true wird eine Zuweisung ohne gültige Voraussetzungen abgelehnt,
+    // sonst nur mit Warnung angelegt
+    #[serde(default)]
+    strict: bool,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum PrerequisiteState {
+    Ok,
+    Missing,
+    Expired,
+}
+
+#[derive(Debug, Serialize)]
+struct PrerequisiteNode {
+    training: String,
+    state: PrerequisiteState,
+    requires: Vec<PrerequisiteNode>,
+}
+
+#[derive(Serialize)]
+struct CreateEmployeeResponse {
+    #[serde(flatten)]
+    employee: Employee,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    warnings: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -43,6 +241,8 @@ struct Session {
     username: String,
     role: Role,
     created_at: String,
+    // Letzte Anfrage, für die Abmeldung nach Inaktivität
+    last_seen: String,
 }
 
 #[derive(Deserialize)]
@@ -57,7 +257,7 @@ struct LoginResponse {
     user: UserResponse,
 }
 
-#[derive(Serialize)]
+#[derive(Clone, Serialize)]
 struct UserResponse {
     id: String,
     username: String,
@@ -79,376 +279,2216 @@ struct UpdateUserRequest {
 }
 
 #[derive(Deserialize)]
-struct CreateEmployee {
+struct EmployeeInput {
     name: String,
     training: String,
     duedate: String,
     status: String,
+    #[serde(default)]
+    custom_fields: BTreeMap<String, String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    // Erwartete Version für PUT, alternativ `If-Match`; beim Anlegen ignoriert
+    #[serde(default)]
+    version: Option<u64>,
+}
+
+/// Teilaktualisierung für `PATCH /api/employees/{id}` – nur gesetzte Felder
+/// werden geändert.
+#[derive(Deserialize)]
+struct EmployeePatch {
+    name: Option<String>,
+    training: Option<String>,
+    duedate: Option<String>,
+    status: Option<String>,
+    custom_fields: Option<BTreeMap<String, String>>,
+    tags: Option<Vec<String>>,
+    version: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct TagRequest {
+    name: String,
 }
 
 struct AppState {
     users: Mutex<Vec<User>>,
     employees: Mutex<Vec<Employee>>,
     sessions: Mutex<Vec<Session>>,
+    catalog: Mutex<Vec<CatalogEntry>>,
+    custom_fields: Mutex<Vec<CustomFieldDef>>,
+    tags: Mutex<Vec<String>>,
+    calendar_feeds: Mutex<Vec<calendar::CalendarFeed>>,
+    reminder_settings: Mutex<reminders::ReminderSettings>,
+    reminders_sent: Mutex<Vec<reminders::SentReminder>>,
+    // Verhindert, dass zwei Läufe gleichzeitig dieselben Erinnerungen senden
+    reminder_run: tokio::sync::Mutex<()>,
+    chat_settings: Mutex<chat::ChatSettings>,
+    chat_sent: Mutex<Vec<reminders::SentReminder>>,
+    // Wie `reminder_run`, damit kein Kanal dieselbe Meldung zweimal bekommt
+    chat_run: tokio::sync::Mutex<()>,
+    escalation_settings: Mutex<escalation::EscalationSettings>,
+    escalations: Mutex<Vec<escalation::Escalation>>,
+    scheduler_config: Mutex<scheduler::SchedulerConfig>,
+    scheduler_state: Mutex<scheduler::SchedulerState>,
+    running_jobs: Mutex<BTreeSet<scheduler::Job>>,
+    webhooks: Mutex<Vec<webhooks::Webhook>>,
+    // Geteilt mit den Zustell-Tasks, die im Hintergrund protokollieren
+    webhook_deliveries: Arc<Mutex<Vec<webhooks::Delivery>>>,
+    http: reqwest::Client,
+    // Änderungen für verbundene Oberflächen (`GET /api/events`)
+    live: tokio::sync::broadcast::Sender<live::Change>,
     next_employee_id: Mutex<u32>,
+    metrics: metrics::Metrics,
+    maintenance: Mutex<maintenance::Maintenance>,
+    // Während der eingebauten Sicherung, siehe `maintenance::check_writable`
+    backup_running: AtomicBool,
+    write_gate: maintenance::WriteGate,
+    // true, sobald der Server beendet wird; beendet auch die Live-Streams
+    shutdown: tokio::sync::watch::Sender<bool>,
+    config: config::Config,
+}
+
+// Angemeldete Sitzungen über einen Neustart hinweg (`[sessions] persist`)
+const SESSIONS_FILE: &str = "sessions.json";
+
+/// Sperrt einen Mutex auch dann, wenn ein anderer Handler währenddessen
+/// abgestürzt ist – die Daten bleiben nutzbar, statt den Server lahmzulegen.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Schreibt erst in eine temporäre Datei und ersetzt dann das Original.
+/// Wird der Server mitten im Schreiben beendet, bleibt die alte Datei
+/// vollständig erhalten statt einer halben neuen.
+fn write_atomic(path: &str, write: impl FnOnce(&mut File) -> Result<(), ApiError>) -> Result<(), ApiError> {
+    let temp = format!("{}.tmp", path);
+    let mut file = File::create(&temp)?;
+    write(&mut file)?;
+    file.sync_all()?;
+    std::fs::rename(&temp, path)?;
+    Ok(())
+}
+
+fn save_json<T: Serialize>(path: &str, value: &T) -> Result<(), ApiError> {
+    write_atomic(path, |file| Ok(serde_json::to_writer_pretty(file, value)?))
 }
 
 impl AppState {
-    fn new() -> Self {
+    fn new(config: config::Config) -> Result<Self, ApiError> {
         let state = AppState {
             users: Mutex::new(Vec::new()),
             employees: Mutex::new(Vec::new()),
             sessions: Mutex::new(Vec::new()),
+            catalog: Mutex::new(Vec::new()),
+            custom_fields: Mutex::new(Vec::new()),
+            tags: Mutex::new(Vec::new()),
+            calendar_feeds: Mutex::new(Vec::new()),
+            reminder_settings: Mutex::new(reminders::ReminderSettings::default()),
+            reminders_sent: Mutex::new(Vec::new()),
+            reminder_run: tokio::sync::Mutex::new(()),
+            chat_settings: Mutex::new(chat::ChatSettings::default()),
+            chat_sent: Mutex::new(Vec::new()),
+            chat_run: tokio::sync::Mutex::new(()),
+            escalation_settings: Mutex::new(escalation::EscalationSettings::default()),
+            escalations: Mutex::new(Vec::new()),
+            scheduler_config: Mutex::new(scheduler::SchedulerConfig::default()),
+            scheduler_state: Mutex::new(scheduler::SchedulerState::default()),
+            running_jobs: Mutex::new(BTreeSet::new()),
+            webhooks: Mutex::new(Vec::new()),
+            webhook_deliveries: Arc::new(Mutex::new(Vec::new())),
+            http: reqwest::Client::builder()
+                .timeout(std::time::Duration::from_secs(webhooks::TIMEOUT_SECONDS))
+                .build()
+                .expect("HTTP-Client"),
+            live: tokio::sync::broadcast::channel(live::CAPACITY).0,
             next_employee_id: Mutex::new(1),
+            metrics: metrics::Metrics::default(),
+            maintenance: Mutex::new(maintenance::Maintenance::default()),
+            backup_running: AtomicBool::new(false),
+            write_gate: maintenance::WriteGate::default(),
+            shutdown: tokio::sync::watch::channel(false).0,
+            config,
         };
+        if state.config.server.read_only {
+            let request = maintenance::MaintenanceRequest { read_only: true, message: String::new() };
+            *lock(&state.maintenance) = maintenance::Maintenance::new(request, None);
+        }
         
-        state.create_default_admin();
+        state.create_default_admin()?;
         state.load_employees();
-        state
+        state.load_catalog();
+        state.load_custom_fields();
+        state.load_tags();
+        state.load_calendar_feeds();
+        state.load_reminders();
+        state.load_chat();
+        state.load_escalations();
+        state.load_scheduler();
+        state.load_webhooks();
+        state.load_sessions();
+        Ok(state)
     }
 
-    fn create_default_admin(&self) {
-        let mut users = self.users.lock().unwrap();
+    fn create_default_admin(&self) -> Result<(), ApiError> {
         let admin = User {
             id: Uuid::new_v4().to_string(),
             username: "admin".to_string(),
-            password_hash: hash("admin123", DEFAULT_COST).unwrap(),
+            password_hash: hash("admin123", self.config.security.bcrypt_cost)?,
             role: Role::Admin,
             active: true,
             created_at: chrono::Utc::now().to_rfc3339(),
         };
-        users.push(admin);
-        println!("✓ Admin erstellt (Username: admin, Passwort: admin123)");
+        lock(&self.users).push(admin);
+        tracing::warn!("Admin erstellt (Username: admin, Passwort: admin123)");
+        Ok(())
     }
 
     fn load_employees(&self) {
         if let Ok(file) = File::open("employees.csv") {
             let mut reader = csv::Reader::from_reader(file);
-            let mut employees = self.employees.lock().unwrap();
+            let mut employees = lock(&self.employees);
             let mut max_id = 0;
 
-            for result in reader.deserialize() {
-                if let Ok(employee) = result {
-                    let emp: Employee = employee;
-                    if emp.id > max_id {
-                        max_id = emp.id;
-                    }
-                    employees.push(emp);
+            for emp in reader.deserialize::<EmployeeRow>().flatten().map(Employee::from) {
+                if emp.id > max_id {
+                    max_id = emp.id;
                 }
+                employees.push(emp);
             }
 
             if max_id > 0 {
-                *self.next_employee_id.lock().unwrap() = max_id + 1;
+                *lock(&self.next_employee_id) = max_id + 1;
             }
-            println!("✓ {} Schulungen geladen", employees.len());
+            tracing::info!(count = employees.len(), "Schulungen geladen");
         }
     }
 
-    fn save_employees(&self) {
-        let employees = self.employees.lock().unwrap();
-        if let Ok(file) = File::create("employees.csv") {
+    fn save_employees(&self) -> Result<(), ApiError> {
+        let employees = lock(&self.employees);
+        write_atomic("employees.csv", |file| {
             let mut writer = csv::Writer::from_writer(file);
             for employee in employees.iter() {
-                let _ = writer.serialize(employee);
+                writer.serialize(EmployeeRow::from(employee))?;
+            }
+            writer.flush()?;
+            Ok(())
+        })
+    }
+
+    fn load_catalog(&self) {
+        if let Ok(file) = File::open("catalog.json") {
+            if let Ok(entries) = serde_json::from_reader::<_, Vec<CatalogEntry>>(file) {
+                let mut catalog = lock(&self.catalog);
+                *catalog = entries;
+                tracing::info!(count = catalog.len(), "Katalogeinträge geladen");
+            }
+        }
+    }
+
+    fn save_catalog(&self) -> Result<(), ApiError> {
+        save_json("catalog.json", &*lock(&self.catalog))
+    }
+
+    fn load_custom_fields(&self) {
+        if let Ok(file) = File::open("custom_fields.json") {
+            if let Ok(defs) = serde_json::from_reader::<_, Vec<CustomFieldDef>>(file) {
+                let mut custom_fields = lock(&self.custom_fields);
+                *custom_fields = defs;
+                tracing::info!(count = custom_fields.len(), "Zusatzfelder geladen");
+            }
+        }
+    }
+
+    fn save_custom_fields(&self) -> Result<(), ApiError> {
+        save_json("custom_fields.json", &*lock(&self.custom_fields))
+    }
+
+    fn load_tags(&self) {
+        if let Ok(file) = File::open("tags.json") {
+            if let Ok(names) = serde_json::from_reader::<_, Vec<String>>(file) {
+                let mut tags = lock(&self.tags);
+                *tags = names;
+                tracing::info!(count = tags.len(), "Tags geladen");
+            }
+        }
+    }
+
+    fn save_tags(&self) -> Result<(), ApiError> {
+        save_json("tags.json", &*lock(&self.tags))
+    }
+
+    fn load_calendar_feeds(&self) {
+        if let Ok(file) = File::open(calendar::FEEDS_FILE) {
+            if let Ok(feeds) = serde_json::from_reader::<_, Vec<calendar::CalendarFeed>>(file) {
+                let mut calendar_feeds = lock(&self.calendar_feeds);
+                *calendar_feeds = feeds;
+                tracing::info!(count = calendar_feeds.len(), "Kalender-Abos geladen");
+            }
+        }
+    }
+
+    fn save_calendar_feeds(&self) -> Result<(), ApiError> {
+        save_json(calendar::FEEDS_FILE, &*lock(&self.calendar_feeds))
+    }
+
+    fn load_reminders(&self) {
+        if let Ok(file) = File::open(reminders::SETTINGS_FILE) {
+            if let Ok(settings) = serde_json::from_reader::<_, reminders::ReminderSettings>(file) {
+                tracing::info!(enabled = settings.enabled, "E-Mail-Erinnerungen geladen");
+                *lock(&self.reminder_settings) = settings;
+            }
+        }
+        if let Ok(file) = File::open(reminders::SENT_FILE) {
+            if let Ok(sent) = serde_json::from_reader::<_, Vec<reminders::SentReminder>>(file) {
+                *lock(&self.reminders_sent) = sent;
+            }
+        }
+    }
+
+    fn save_reminder_settings(&self) -> Result<(), ApiError> {
+        save_json(reminders::SETTINGS_FILE, &*lock(&self.reminder_settings))
+    }
+
+    fn save_reminders_sent(&self) -> Result<(), ApiError> {
+        save_json(reminders::SENT_FILE, &*lock(&self.reminders_sent))
+    }
+
+    fn load_chat(&self) {
+        if let Ok(file) = File::open(chat::SETTINGS_FILE) {
+            if let Ok(settings) = serde_json::from_reader::<_, chat::ChatSettings>(file) {
+                tracing::info!(count = settings.channels.len(), "Chat-Kanäle geladen");
+                *lock(&self.chat_settings) = settings;
+            }
+        }
+        if let Ok(file) = File::open(chat::SENT_FILE) {
+            if let Ok(sent) = serde_json::from_reader::<_, Vec<reminders::SentReminder>>(file) {
+                *lock(&self.chat_sent) = sent;
+            }
+        }
+    }
+
+    fn save_chat_settings(&self) -> Result<(), ApiError> {
+        save_json(chat::SETTINGS_FILE, &*lock(&self.chat_settings))
+    }
+
+    fn save_chat_sent(&self) -> Result<(), ApiError> {
+        save_json(chat::SENT_FILE, &*lock(&self.chat_sent))
+    }
+
+    fn load_escalations(&self) {
+        if let Ok(file) = File::open(escalation::SETTINGS_FILE) {
+            if let Ok(settings) = serde_json::from_reader::<_, escalation::EscalationSettings>(file) {
+                *lock(&self.escalation_settings) = settings;
+            }
+        }
+        if let Ok(file) = File::open(escalation::STATE_FILE) {
+            if let Ok(escalations) = serde_json::from_reader::<_, Vec<escalation::Escalation>>(file) {
+                tracing::info!(count = escalations.len(), "Eskalationen geladen");
+                *lock(&self.escalations) = escalations;
+            }
+        }
+    }
+
+    fn save_escalation_settings(&self) -> Result<(), ApiError> {
+        save_json(escalation::SETTINGS_FILE, &*lock(&self.escalation_settings))
+    }
+
+    fn save_escalations(&self) -> Result<(), ApiError> {
+        save_json(escalation::STATE_FILE, &*lock(&self.escalations))
+    }
+
+    fn load_webhooks(&self) {
+        if let Ok(file) = File::open(webhooks::WEBHOOKS_FILE) {
+            if let Ok(hooks) = serde_json::from_reader::<_, Vec<webhooks::Webhook>>(file) {
+                tracing::info!(count = hooks.len(), "Webhooks geladen");
+                *lock(&self.webhooks) = hooks;
+            }
+        }
+        if let Ok(file) = File::open(webhooks::DELIVERIES_FILE) {
+            if let Ok(deliveries) = serde_json::from_reader::<_, Vec<webhooks::Delivery>>(file) {
+                *lock(&self.webhook_deliveries) = deliveries;
+            }
+        }
+    }
+
+    fn save_webhooks(&self) -> Result<(), ApiError> {
+        save_json(webhooks::WEBHOOKS_FILE, &*lock(&self.webhooks))
+    }
+
+    fn load_sessions(&self) {
+        if !self.config.sessions.persist {
+            return;
+        }
+        if let Ok(file) = File::open(SESSIONS_FILE) {
+            if let Ok(sessions) = serde_json::from_reader::<_, Vec<Session>>(file) {
+                let now = chrono::Utc::now();
+                let users = lock(&self.users);
+                let mut current = lock(&self.sessions);
+                // Benutzer-IDs entstehen bei jedem Start neu, daher über den Namen zuordnen
+                *current = sessions
+                    .into_iter()
+                    .filter(|s| !self.config.sessions.expired(&s.created_at, &s.last_seen, now))
+                    .filter_map(|mut s| {
+                        let user = users.iter().find(|u| u.username == s.username && u.active)?;
+                        s.user_id = user.id.clone();
+                        s.role = user.role.clone();
+                        Some(s)
+                    })
+                    .collect();
+                tracing::info!(count = current.len(), "Sitzungen wiederhergestellt");
+            }
+            // Tokens sollen nicht länger als nötig auf der Platte liegen
+            let _ = std::fs::remove_file(SESSIONS_FILE);
+        }
+    }
+
+    /// Beim Beenden, siehe `[sessions] persist`. Nur für den Dienstbenutzer lesbar.
+    fn save_sessions(&self) -> Result<usize, ApiError> {
+        let sessions = lock(&self.sessions).clone();
+        save_json(SESSIONS_FILE, &sessions)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(SESSIONS_FILE, std::fs::Permissions::from_mode(0o600))?;
+        }
+        Ok(sessions.len())
+    }
+
+    fn load_scheduler(&self) {
+        if let Ok(file) = File::open(scheduler::CONFIG_FILE) {
+            if let Ok(config) = serde_json::from_reader::<_, scheduler::SchedulerConfig>(file) {
+                *lock(&self.scheduler_config) = config;
+            }
+        }
+        if let Ok(file) = File::open(scheduler::STATE_FILE) {
+            if let Ok(state) = serde_json::from_reader::<_, scheduler::SchedulerState>(file) {
+                tracing::info!(count = state.history.len(), "Job-Läufe im Verlauf");
+                *lock(&self.scheduler_state) = state;
             }
-            let _ = writer.flush();
         }
     }
 
+    fn save_scheduler_config(&self) -> Result<(), ApiError> {
+        save_json(scheduler::CONFIG_FILE, &*lock(&self.scheduler_config))
+    }
+
+    fn save_scheduler_state(&self) -> Result<(), ApiError> {
+        save_json(scheduler::STATE_FILE, &*lock(&self.scheduler_state))
+    }
+
     fn verify_token(&self, token: &str) -> Option<Session> {
-        let sessions = self.sessions.lock().unwrap();
-        sessions.iter().find(|s| s.token == token).cloned()
+        let now = chrono::Utc::now();
+        let sessions = lock(&self.sessions);
+        sessions
+            .iter()
+            .find(|s| s.token == token && !self.config.sessions.expired(&s.created_at, &s.last_seen, now))
+            .cloned()
+    }
+
+    /// Wie `verify_token`, zählt aber als Aktivität der Sitzung.
+    fn touch_token(&self, token: &str) -> Option<Session> {
+        let now = chrono::Utc::now();
+        let mut sessions = lock(&self.sessions);
+        let session = sessions
+            .iter_mut()
+            .find(|s| s.token == token && !self.config.sessions.expired(&s.created_at, &s.last_seen, now))?;
+        session.last_seen = now.to_rfc3339();
+        Some(session.clone())
+    }
+
+    /// Sitzung für ein geprüftes Client-Zertifikat, dessen Common Name ein
+    /// aktiver Benutzername ist. Sie gilt nur für die jeweilige Anfrage.
+    fn certificate_session(&self, username: &str) -> Option<Session> {
+        let users = lock(&self.users);
+        let user = users.iter().find(|u| u.username == username && u.active)?;
+        let now = chrono::Utc::now().to_rfc3339();
+        Some(Session {
+            token: String::new(),
+            user_id: user.id.clone(),
+            username: user.username.clone(),
+            role: user.role.clone(),
+            created_at: now.clone(),
+            last_seen: now,
+        })
+    }
+
+    /// Erinnerungseinstellungen für den Versand – mit dem SMTP-Server aus
+    /// der Startkonfiguration, sofern dort einer angegeben ist.
+    fn mail_settings(&self) -> reminders::ReminderSettings {
+        let mut settings = lock(&self.reminder_settings).clone();
+        if let Some(smtp) = &self.config.notifications.smtp {
+            settings.smtp = smtp.clone();
+        }
+        settings
+    }
+
+    fn require_notifications(&self) -> Result<(), ApiError> {
+        if self.config.notifications.enabled {
+            Ok(())
+        } else {
+            Err(ApiError::conflict(
+                "notifications_disabled",
+                "Benachrichtigungen sind in der Serverkonfiguration abgeschaltet",
+            ))
+        }
     }
 
     fn check_permission(&self, session: &Session, required_role: Role) -> bool {
-        match (&session.role, &required_role) {
-            (Role::Admin, _) => true,
-            (Role::Manager, Role::User) => true,
-            (Role::Manager, Role::Manager) => true,
-            (Role::User, Role::User) => true,
-            _ => false,
+        matches!(
+            (&session.role, &required_role),
+            (Role::Admin, _)
+                | (Role::Manager, Role::User)
+                | (Role::Manager, Role::Manager)
+                | (Role::User, Role::User)
+        )
+    }
+
+    fn require_role(&self, session: &Session, required_role: Role) -> Result<(), ApiError> {
+        if self.check_permission(session, required_role) {
+            Ok(())
+        } else {
+            Err(ApiError::Forbidden)
         }
     }
 }
 
-async fn login(login_req: web::Json<LoginRequest>, data: web::Data<AppState>) -> Result<HttpResponse> {
-    let users = data.users.lock().unwrap();
-    
-    if let Some(user) = users.iter().find(|u| u.username == login_req.username) {
-        if !user.active {
-            return Ok(HttpResponse::Unauthorized().json(serde_json::json!({"error": "Benutzer deaktiviert"})));
+// Validierung
+
+const MAX_NAME_LEN: usize = 100;
+const MAX_TRAINING_LEN: usize = 150;
+
+/// Prüft und bereinigt die Eingabe für einen Schulungseintrag. Alle Fehler
+/// werden gesammelt und feldweise zurückgegeben.
+fn validate_employee_input(data: &AppState, input: &mut EmployeeInput) -> Result<Status, ApiError> {
+    let mut errors = BTreeMap::new();
+
+    input.name = input.name.trim().to_string();
+    input.training = input.training.trim().to_string();
+    input.duedate = input.duedate.trim().to_string();
+
+    if input.name.is_empty() {
+        errors.insert("name".to_string(), "Name fehlt".to_string());
+    } else if input.name.chars().count() > MAX_NAME_LEN {
+        errors.insert("name".to_string(), format!("Höchstens {} Zeichen", MAX_NAME_LEN));
+    }
+
+    if input.training.is_empty() {
+        errors.insert("training".to_string(), "Training fehlt".to_string());
+    } else if input.training.chars().count() > MAX_TRAINING_LEN {
+        errors.insert("training".to_string(), format!("Höchstens {} Zeichen", MAX_TRAINING_LEN));
+    }
+
+    if chrono::NaiveDate::parse_from_str(&input.duedate, "%Y-%m-%d").is_err() {
+        errors.insert("duedate".to_string(), "Kein gültiges Datum (YYYY-MM-DD)".to_string());
+    }
+
+    let status = Status::parse(&input.status);
+    if status.is_none() {
+        errors.insert("status".to_string(), "Erlaubt: gruen, gelb, rot".to_string());
+    }
+
+    if let Err(fields) = validate_custom_fields(&lock(&data.custom_fields), &mut input.custom_fields) {
+        for (key, message) in fields {
+            errors.insert(format!("custom_fields.{}", key), message);
         }
+    }
 
-        if verify(&login_req.password, &user.password_hash).unwrap_or(false) {
-            let token = Uuid::new_v4().to_string();
-            let user_id = user.id.clone();
-            let username = user.username.clone();
-            let role = user.role.clone();
-            let active = user.active;
-            
-            drop(users);
-            
-            let session = Session {
-                token: token.clone(),
-                user_id: user_id.clone(),
-                username: username.clone(),
-                role: role.clone(),
-                created_at: chrono::Utc::now().to_rfc3339(),
-            };
+    let unknown = normalize_tags(&lock(&data.tags), &mut input.tags);
+    if !unknown.is_empty() {
+        errors.insert("tags".to_string(), format!("Unbekannt: {}", unknown.join(", ")));
+    }
+
+    match status {
+        Some(status) if errors.is_empty() => Ok(status),
+        _ => Err(ApiError::Validation(errors)),
+    }
+}
 
-            data.sessions.lock().unwrap().push(session);
+// Zusatzfelder
 
-            return Ok(HttpResponse::Ok().json(LoginResponse {
-                token: token.clone(),
-                user: UserResponse {
-                    id: user_id,
-                    username,
-                    role,
-                    active,
-                },
-            }));
+/// Prüft die Zusatzfelder eines Eintrags gegen die Felddefinitionen.
+/// Leere Werte gelten als nicht gesetzt und werden entfernt.
+fn validate_custom_fields(
+    defs: &[CustomFieldDef],
+    values: &mut BTreeMap<String, String>,
+) -> Result<(), BTreeMap<String, String>> {
+    let mut errors = BTreeMap::new();
+
+    values.retain(|_, v| !v.trim().is_empty());
+    for value in values.values_mut() {
+        *value = value.trim().to_string();
+    }
+
+    for key in values.keys() {
+        if !defs.iter().any(|d| &d.name == key) {
+            errors.insert(key.clone(), "Unbekanntes Feld".to_string());
         }
     }
 
-    Ok(HttpResponse::Unauthorized().json(serde_json::json!({"error": "Ungültige Anmeldedaten"})))
+    for def in defs {
+        let Some(value) = values.get_mut(&def.name) else {
+            if def.required {
+                errors.insert(def.name.clone(), "Pflichtfeld".to_string());
+            }
+            continue;
+        };
+
+        let error = match def.kind {
+            FieldKind::Text => None,
+            FieldKind::Number => {
+                let normalized = value.replace(',', ".");
+                if normalized.parse::<f64>().is_ok() {
+                    *value = normalized;
+                    None
+                } else {
+                    Some("Keine Zahl".to_string())
+                }
+            }
+            FieldKind::Date if chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d").is_err() => {
+                Some("Kein Datum (YYYY-MM-DD)".to_string())
+            }
+            FieldKind::Enum if !def.options.contains(value) => {
+                Some(format!("Erlaubt: {}", def.options.join(", ")))
+            }
+            _ => None,
+        };
+        if let Some(message) = error {
+            errors.insert(def.name.clone(), message);
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+// Tags
+
+/// Bereinigt die Tags eines Eintrags und liefert die nicht im Vokabular
+/// enthaltenen zurück.
+fn normalize_tags(vocabulary: &[String], tags: &mut Vec<String>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    tags.retain_mut(|t| {
+        *t = t.trim().to_string();
+        !t.is_empty() && seen.insert(t.clone())
+    });
+    tags.iter().filter(|t| !vocabulary.contains(t)).cloned().collect()
+}
+
+// Voraussetzungen
+
+fn same_person(a: &str, b: &str) -> bool {
+    a.trim().eq_ignore_ascii_case(b.trim())
 }
 
-async fn logout(req: HttpRequest, data: web::Data<AppState>) -> Result<HttpResponse> {
-    if let Some(session) = extract_session(&req, &data) {
-        let mut sessions = data.sessions.lock().unwrap();
-        sessions.retain(|s| s.token != session.token);
+fn is_expired(employee: &Employee, today: chrono::NaiveDate) -> bool {
+    match chrono::NaiveDate::parse_from_str(&employee.duedate, "%Y-%m-%d") {
+        Ok(due) => due < today,
+        Err(_) => employee.status == Status::Rot,
     }
-    Ok(HttpResponse::Ok().json(serde_json::json!({"message": "Abgemeldet"})))
 }
 
-async fn get_current_user(req: HttpRequest, data: web::Data<AppState>) -> Result<HttpResponse> {
-    if let Some(session) = extract_session(&req, &data) {
-        let users = data.users.lock().unwrap();
-        if let Some(user) = users.iter().find(|u| u.id == session.user_id) {
-            return Ok(HttpResponse::Ok().json(UserResponse {
-                id: user.id.clone(),
-                username: user.username.clone(),
-                role: user.role.clone(),
-                active: user.active,
-            }));
+fn prerequisite_state(employees: &[Employee], person: &str, training: &str) -> PrerequisiteState {
+    let today = chrono::Local::now().date_naive();
+    let mut records = employees
+        .iter()
+        .filter(|e| same_person(&e.name, person) && e.training == training)
+        .peekable();
+
+    if records.peek().is_none() {
+        return PrerequisiteState::Missing;
+    }
+    if records.any(|e| !is_expired(e, today)) {
+        PrerequisiteState::Ok
+    } else {
+        PrerequisiteState::Expired
+    }
+}
+
+/// Liefert die direkten Voraussetzungen einer Schulung, die der Person fehlen
+/// oder abgelaufen sind, sowie ob der Katalogeintrag diese strikt verlangt.
+fn unmet_prerequisites(
+    catalog: &[CatalogEntry],
+    employees: &[Employee],
+    person: &str,
+    training: &str,
+) -> (Vec<String>, bool) {
+    let Some(entry) = catalog.iter().find(|c| c.name == training) else {
+        return (Vec::new(), false);
+    };
+
+    let unmet = entry
+        .prerequisites
+        .iter()
+        .filter_map(|prereq| match prerequisite_state(employees, person, prereq) {
+            PrerequisiteState::Ok => None,
+            PrerequisiteState::Missing => Some(format!("{} (fehlt)", prereq)),
+            PrerequisiteState::Expired => Some(format!("{} (abgelaufen)", prereq)),
+        })
+        .collect();
+
+    (unmet, entry.strict)
+}
+
+fn prerequisite_tree(
+    catalog: &[CatalogEntry],
+    employees: &[Employee],
+    person: &str,
+    training: &str,
+    visited: &mut HashSet<String>,
+) -> PrerequisiteNode {
+    let mut requires = Vec::new();
+
+    // Zyklen werden beim Speichern abgewiesen, die Datei kann aber von Hand
+    // bearbeitet worden sein
+    if visited.insert(training.to_string()) {
+        if let Some(entry) = catalog.iter().find(|c| c.name == training) {
+            for prereq in &entry.prerequisites {
+                requires.push(prerequisite_tree(catalog, employees, person, prereq, visited));
+            }
         }
+        visited.remove(training);
+    }
+
+    PrerequisiteNode {
+        training: training.to_string(),
+        state: prerequisite_state(employees, person, training),
+        requires,
     }
-    Ok(HttpResponse::Unauthorized().finish())
 }
 
-async fn get_users(req: HttpRequest, data: web::Data<AppState>) -> Result<HttpResponse> {
-    if let Some(session) = extract_session(&req, &data) {
-        if !data.check_permission(&session, Role::Manager) {
-            return Ok(HttpResponse::Forbidden().finish());
+fn creates_cycle(catalog: &[CatalogEntry], entry: &CatalogEntry) -> bool {
+    let mut stack: Vec<&str> = entry.prerequisites.iter().map(String::as_str).collect();
+    let mut seen = HashSet::new();
+
+    while let Some(current) = stack.pop() {
+        if current == entry.name {
+            return true;
         }
+        if !seen.insert(current) {
+            continue;
+        }
+        if let Some(next) = catalog.iter().find(|c| c.name == current && c.name != entry.name) {
+            stack.extend(next.prerequisites.iter().map(String::as_str));
+        }
+    }
+    false
+}
 
-        let users = data.users.lock().unwrap();
-        let user_list: Vec<UserResponse> = users.iter().map(|u| UserResponse {
-            id: u.id.clone(),
-            username: u.username.clone(),
-            role: u.role.clone(),
-            active: u.active,
-        }).collect();
+async fn login(login_req: web::Json<LoginRequest>, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    let users = lock(&data.users);
 
-        return Ok(HttpResponse::Ok().json(user_list));
+    let user = users
+        .iter()
+        .find(|u| u.username == login_req.username)
+        .filter(|u| verify(&login_req.password, &u.password_hash).unwrap_or(false));
+    let Some(user) = user else {
+        data.metrics.login_failed();
+        return Err(ApiError::InvalidCredentials);
+    };
+    if !user.active {
+        data.metrics.login_failed();
+        return Err(ApiError::UserDisabled);
     }
-    Ok(HttpResponse::Unauthorized().finish())
+
+    let token = Uuid::new_v4().to_string();
+    let user_response = UserResponse {
+        id: user.id.clone(),
+        username: user.username.clone(),
+        role: user.role.clone(),
+        active: user.active,
+    };
+    drop(users);
+
+    let now = chrono::Utc::now().to_rfc3339();
+    let session = Session {
+        token: token.clone(),
+        user_id: user_response.id.clone(),
+        username: user_response.username.clone(),
+        role: user_response.role.clone(),
+        created_at: now.clone(),
+        last_seen: now,
+    };
+
+    lock(&data.sessions).push(session);
+    logging::record_user(&user_response.username);
+
+    Ok(HttpResponse::Ok().json(LoginResponse {
+        token,
+        user: user_response,
+    }))
 }
 
-async fn create_user(req: HttpRequest, user_req: web::Json<CreateUserRequest>, data: web::Data<AppState>) -> Result<HttpResponse> {
-    if let Some(session) = extract_session(&req, &data) {
-        if !data.check_permission(&session, Role::Admin) {
-            return Ok(HttpResponse::Forbidden().finish());
-        }
+async fn logout(req: HttpRequest, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    if let Ok(session) = extract_session(&req, &data) {
+        lock(&data.sessions).retain(|s| s.token != session.token);
+    }
+    Ok(HttpResponse::Ok().json(serde_json::json!({"message": "Abgemeldet"})))
+}
 
-        let mut users = data.users.lock().unwrap();
-        
-        if users.iter().any(|u| u.username == user_req.username) {
-            return Ok(HttpResponse::BadRequest().json(serde_json::json!({"error": "Username existiert"})));
-        }
+async fn get_current_user(req: HttpRequest, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    let session = extract_session(&req, &data)?;
+    let users = lock(&data.users);
+    let user = users
+        .iter()
+        .find(|u| u.id == session.user_id)
+        .ok_or(ApiError::Unauthorized)?;
 
-        let new_user = User {
-            id: Uuid::new_v4().to_string(),
-            username: user_req.username.clone(),
-            password_hash: hash(&user_req.password, DEFAULT_COST).unwrap(),
-            role: user_req.role.clone(),
-            active: true,
-            created_at: chrono::Utc::now().to_rfc3339(),
-        };
+    Ok(HttpResponse::Ok().json(UserResponse {
+        id: user.id.clone(),
+        username: user.username.clone(),
+        role: user.role.clone(),
+        active: user.active,
+    }))
+}
+
+async fn get_maintenance(req: HttpRequest, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    extract_session(&req, &data)?;
+    Ok(HttpResponse::Ok().json(&*lock(&data.maintenance)))
+}
 
-        users.push(new_user.clone());
+async fn set_maintenance(
+    req: HttpRequest,
+    request: web::Json<maintenance::MaintenanceRequest>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    let session = extract_session(&req, &data)?;
+    data.require_role(&session, Role::Admin)?;
 
-        return Ok(HttpResponse::Ok().json(UserResponse {
-            id: new_user.id,
-            username: new_user.username,
-            role: new_user.role,
-            active: new_user.active,
-        }));
+    let maintenance = maintenance::Maintenance::new(request.into_inner(), Some(session.username));
+    if maintenance.read_only {
+        tracing::warn!(message = %maintenance.message, "Wartungsmodus eingeschaltet");
+    } else {
+        tracing::info!("Wartungsmodus beendet");
     }
-    Ok(HttpResponse::Unauthorized().finish())
+    *lock(&data.maintenance) = maintenance.clone();
+    Ok(HttpResponse::Ok().json(maintenance))
 }
 
-async fn update_user(req: HttpRequest, path: web::Path<String>, update_req: web::Json<UpdateUserRequest>, data: web::Data<AppState>) -> Result<HttpResponse> {
-    if let Some(session) = extract_session(&req, &data) {
-        if !data.check_permission(&session, Role::Admin) {
-            return Ok(HttpResponse::Forbidden().finish());
-        }
+async fn get_users(req: HttpRequest, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    let session = extract_session(&req, &data)?;
+    data.require_role(&session, Role::Manager)?;
 
-        let mut users = data.users.lock().unwrap();
-        let user_id = path.into_inner();
+    let users = lock(&data.users);
+    let user_list: Vec<UserResponse> = users.iter().map(|u| UserResponse {
+        id: u.id.clone(),
+        username: u.username.clone(),
+        role: u.role.clone(),
+        active: u.active,
+    }).collect();
 
-        if let Some(user) = users.iter_mut().find(|u| u.id == user_id) {
-            if let Some(active) = update_req.active {
-                user.active = active;
-            }
-            if let Some(role) = &update_req.role {
-                user.role = role.clone();
-            }
+    Ok(HttpResponse::Ok().json(user_list))
+}
+
+async fn create_user(req: HttpRequest, user_req: web::Json<CreateUserRequest>, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    let session = extract_session(&req, &data)?;
+    data.require_role(&session, Role::Admin)?;
+
+    let username = user_req.username.trim().to_string();
+    let mut errors = BTreeMap::new();
+    if username.is_empty() {
+        errors.insert("username".to_string(), "Username fehlt".to_string());
+    }
+    if user_req.password.is_empty() {
+        errors.insert("password".to_string(), "Passwort fehlt".to_string());
+    }
+    if !errors.is_empty() {
+        return Err(ApiError::Validation(errors));
+    }
+
+    if lock(&data.users).iter().any(|u| u.username == username) {
+        return Err(ApiError::conflict("username_taken", "Username existiert"));
+    }
+
+    // bcrypt ist bewusst langsam – nicht unter der Sperre hashen
+    let password_hash = hash(&user_req.password, data.config.security.bcrypt_cost)?;
+
+    let new_user = User {
+        id: Uuid::new_v4().to_string(),
+        username,
+        password_hash,
+        role: user_req.role.clone(),
+        active: true,
+        created_at: chrono::Utc::now().to_rfc3339(),
+    };
+
+    let mut users = lock(&data.users);
+    if users.iter().any(|u| u.username == new_user.username) {
+        return Err(ApiError::conflict("username_taken", "Username existiert"));
+    }
+    users.push(new_user.clone());
+    drop(users);
+
+    let response = UserResponse {
+        id: new_user.id,
+        username: new_user.username,
+        role: new_user.role,
+        active: new_user.active,
+    };
+    webhooks::user_event(&data, webhooks::Event::UserCreated, &response);
+    live::send(&data, live::Change::User { user: response.clone() });
+    Ok(HttpResponse::Ok().json(response))
+}
+
+async fn update_user(req: HttpRequest, path: web::Path<String>, update_req: web::Json<UpdateUserRequest>, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    let session = extract_session(&req, &data)?;
+    data.require_role(&session, Role::Admin)?;
 
-            let response = UserResponse {
-                id: user.id.clone(),
-                username: user.username.clone(),
-                role: user.role.clone(),
-                active: user.active,
-            };
+    let mut users = lock(&data.users);
+    let user_id = path.into_inner();
+    let user = users
+        .iter_mut()
+        .find(|u| u.id == user_id)
+        .ok_or(ApiError::NotFound("Benutzer"))?;
 
-            return Ok(HttpResponse::Ok().json(response));
+    let was_active = user.active;
+    if let Some(active) = update_req.active {
+        user.active = active;
+    }
+    if let Some(role) = &update_req.role {
+        user.role = role.clone();
+    }
+
+    let response = UserResponse {
+        id: user.id.clone(),
+        username: user.username.clone(),
+        role: user.role.clone(),
+        active: user.active,
+    };
+    drop(users);
+
+    if was_active && !response.active {
+        webhooks::user_event(&data, webhooks::Event::UserDeactivated, &response);
+    }
+    live::send(&data, live::Change::User { user: response.clone() });
+    Ok(HttpResponse::Ok().json(response))
+}
+
+async fn delete_user(req: HttpRequest, path: web::Path<String>, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    let session = extract_session(&req, &data)?;
+    data.require_role(&session, Role::Admin)?;
+
+    let user_id = path.into_inner();
+    if user_id == session.user_id {
+        return Err(ApiError::conflict("cannot_delete_self", "Kann eigenen Account nicht löschen"));
+    }
+
+    let mut users = lock(&data.users);
+    let before = users.len();
+    users.retain(|u| u.id != user_id);
+    if users.len() == before {
+        return Err(ApiError::NotFound("Benutzer"));
+    }
+    drop(users);
+
+    live::send(&data, live::Change::UserDeleted { id: user_id });
+    Ok(HttpResponse::Ok().finish())
+}
+
+async fn live_events(req: HttpRequest, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    let session = extract_session(&req, &data)?;
+    Ok(HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header((header::CACHE_CONTROL, "no-cache"))
+        .streaming(live::stream(data, session)))
+}
+
+async fn get_employees(req: HttpRequest, query: web::Query<EmployeeQuery>, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    extract_session(&req, &data)?;
+    let employees = lock(&data.employees);
+    let (total, page) = query.apply(&employees)?;
+
+    Ok(HttpResponse::Ok()
+        .insert_header(("X-Total-Count", total.to_string()))
+        .json(page))
+}
+
+async fn get_employee_stats(req: HttpRequest, query: web::Query<EmployeeQuery>, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    extract_session(&req, &data)?;
+    let employees = lock(&data.employees);
+    Ok(HttpResponse::Ok().json(query.stats(&employees)?))
+}
+
+async fn export_employees(
+    req: HttpRequest,
+    query: web::Query<EmployeeQuery>,
+    format: web::Query<export::ExportFormat>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    extract_session(&req, &data)?;
+    let defs = lock(&data.custom_fields).clone();
+    let employees = lock(&data.employees);
+    let (_, selected) = query.apply(&employees)?;
+
+    let file = export::export(format.format.as_deref().unwrap_or("csv"), &selected, &defs)?;
+    let file_name = format!("schulungen-{}.{}", chrono::Local::now().format("%Y-%m-%d"), file.extension);
+    Ok(HttpResponse::Ok()
+        .content_type(file.content_type)
+        .insert_header((header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", file_name)))
+        .body(file.body))
+}
+
+fn pdf_response(file_name: String, body: Vec<u8>) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("application/pdf")
+        .insert_header((header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", file_name)))
+        .body(body)
+}
+
+/// ASCII-Dateiname für Content-Disposition, Umlaute werden umschrieben.
+fn file_slug(text: &str) -> String {
+    let mut slug = String::new();
+    for c in text.trim().chars() {
+        match c {
+            'ä' => slug.push_str("ae"),
+            'ö' => slug.push_str("oe"),
+            'ü' => slug.push_str("ue"),
+            'Ä' => slug.push_str("Ae"),
+            'Ö' => slug.push_str("Oe"),
+            'Ü' => slug.push_str("Ue"),
+            'ß' => slug.push_str("ss"),
+            c if c.is_ascii_alphanumeric() => slug.push(c),
+            _ => slug.push('-'),
         }
     }
-    Ok(HttpResponse::Unauthorized().finish())
+    slug.split('-').filter(|p| !p.is_empty()).collect::<Vec<_>>().join("-")
+}
+
+async fn report_person(req: HttpRequest, path: web::Path<String>, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    extract_session(&req, &data)?;
+    let person = path.into_inner();
+    let defs = lock(&data.custom_fields).clone();
+    let employees = lock(&data.employees);
+    let body = reports::person_record(&person, &employees, &defs)?;
+    Ok(pdf_response(format!("schulungsnachweis-{}.pdf", file_slug(&person)), body))
+}
+
+async fn report_compliance(
+    req: HttpRequest,
+    query: web::Query<EmployeeQuery>,
+    params: web::Query<reports::ComplianceParams>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    extract_session(&req, &data)?;
+    let defs = lock(&data.custom_fields).clone();
+    let employees = lock(&data.employees);
+    let body = reports::compliance_summary(&query, &params, &employees, &defs)?;
+    Ok(pdf_response(
+        format!("compliance-{}.pdf", chrono::Local::now().format("%Y-%m-%d")),
+        body,
+    ))
+}
+
+async fn report_certificate(req: HttpRequest, path: web::Path<u32>, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    extract_session(&req, &data)?;
+    let id = path.into_inner();
+    let employee = lock(&data.employees)
+        .iter()
+        .find(|e| e.id == id)
+        .cloned()
+        .ok_or(ApiError::NotFound("Eintrag"))?;
+    let body = reports::certificate(&employee)?;
+    Ok(pdf_response(
+        format!("bescheinigung-{}-{}.pdf", file_slug(&employee.name), file_slug(&employee.training)),
+        body,
+    ))
+}
+
+fn feed_info(req: &HttpRequest, feed: &calendar::CalendarFeed) -> calendar::FeedInfo {
+    let connection = req.connection_info();
+    calendar::FeedInfo {
+        url: format!("{}://{}/api/calendar/{}.ics", connection.scheme(), connection.host(), feed.token),
+        created_at: feed.created_at.clone(),
+    }
+}
+
+/// Liefert die Abo-Adresse des angemeldeten Benutzers und legt sie beim
+/// ersten Aufruf an.
+async fn get_calendar_feed(req: HttpRequest, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    let session = extract_session(&req, &data)?;
+    let mut feeds = lock(&data.calendar_feeds);
+    if let Some(feed) = feeds.iter().find(|f| f.username == session.username) {
+        return Ok(HttpResponse::Ok().json(feed_info(&req, feed)));
+    }
+
+    let feed = calendar::CalendarFeed {
+        token: Uuid::new_v4().simple().to_string(),
+        username: session.username,
+        created_at: chrono::Utc::now().to_rfc3339(),
+    };
+    let info = feed_info(&req, &feed);
+    feeds.push(feed);
+    drop(feeds);
+    data.save_calendar_feeds()?;
+    Ok(HttpResponse::Ok().json(info))
+}
+
+/// Erzeugt eine neue Abo-Adresse, die alte wird ungültig.
+async fn reset_calendar_feed(req: HttpRequest, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    let session = extract_session(&req, &data)?;
+    let feed = calendar::CalendarFeed {
+        token: Uuid::new_v4().simple().to_string(),
+        username: session.username.clone(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+    };
+    let info = feed_info(&req, &feed);
+
+    let mut feeds = lock(&data.calendar_feeds);
+    feeds.retain(|f| f.username != session.username);
+    feeds.push(feed);
+    drop(feeds);
+    data.save_calendar_feeds()?;
+    Ok(HttpResponse::Ok().json(info))
+}
+
+/// Kalender-Abo ohne Anmeldung – das Token in der Adresse ersetzt den
+/// Bearer-Header, den Kalender-Apps nicht senden können.
+async fn calendar_ics(
+    path: web::Path<String>,
+    query: web::Query<EmployeeQuery>,
+    params: web::Query<calendar::FeedParams>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    let token = path.into_inner();
+    let username = lock(&data.calendar_feeds)
+        .iter()
+        .find(|f| f.token == token)
+        .map(|f| f.username.clone())
+        .ok_or(ApiError::NotFound("Kalender"))?;
+    let role = lock(&data.users)
+        .iter()
+        .find(|u| u.username == username && u.active)
+        .map(|u| u.role.clone())
+        .ok_or(ApiError::NotFound("Kalender"))?;
+    let alarms = params.alarm_days()?;
+
+    let defs = lock(&data.custom_fields).clone();
+    let employees = lock(&data.employees);
+    let visible: Vec<Employee> = employees
+        .iter()
+        .filter(|e| calendar::visible(&role, &username, e))
+        .cloned()
+        .collect();
+    drop(employees);
+    let (_, selected) = query.apply(&visible)?;
+
+    let body = calendar::render(&username, &selected, &defs, &alarms);
+    Ok(HttpResponse::Ok()
+        .content_type("text/calendar; charset=utf-8")
+        .insert_header((header::CONTENT_DISPOSITION, "inline; filename=\"schulungen.ics\""))
+        .body(body))
+}
+
+async fn get_reminder_settings(req: HttpRequest, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    let session = extract_session(&req, &data)?;
+    data.require_role(&session, Role::Admin)?;
+    let view = reminders::SettingsView::from(&*lock(&data.reminder_settings));
+    Ok(HttpResponse::Ok().json(view))
+}
+
+async fn save_reminder_settings(
+    req: HttpRequest,
+    settings: web::Json<reminders::ReminderSettings>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    let session = extract_session(&req, &data)?;
+    data.require_role(&session, Role::Admin)?;
+
+    let mut settings = settings.into_inner();
+    settings.validate(&lock(&data.custom_fields))?;
+
+    let mut current = lock(&data.reminder_settings);
+    // Leeres Passwort heißt: bisheriges behalten (GET liefert es nicht aus)
+    if settings.smtp.password.is_empty() {
+        settings.smtp.password = current.smtp.password.clone();
+    }
+    *current = settings;
+    let view = reminders::SettingsView::from(&*current);
+    drop(current);
+
+    data.save_reminder_settings()?;
+    Ok(HttpResponse::Ok().json(view))
+}
+
+async fn run_reminders(
+    req: HttpRequest,
+    params: web::Query<reminders::RunParams>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    let session = extract_session(&req, &data)?;
+    data.require_role(&session, Role::Admin)?;
+    let report = reminders::run(&data, params.dry_run).await?;
+    Ok(HttpResponse::Ok().json(report))
 }
 
-async fn delete_user(req: HttpRequest, path: web::Path<String>, data: web::Data<AppState>) -> Result<HttpResponse> {
-    if let Some(session) = extract_session(&req, &data) {
-        if !data.check_permission(&session, Role::Admin) {
-            return Ok(HttpResponse::Forbidden().finish());
+async fn test_reminder_mail(
+    req: HttpRequest,
+    request: web::Json<reminders::TestRequest>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    let session = extract_session(&req, &data)?;
+    data.require_role(&session, Role::Admin)?;
+    reminders::send_test(&data, &request.to).await?;
+    Ok(HttpResponse::Ok().json(serde_json::json!({"message": "Testnachricht verschickt"})))
+}
+
+async fn get_reminder_log(req: HttpRequest, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    let session = extract_session(&req, &data)?;
+    data.require_role(&session, Role::Admin)?;
+    let mut log = lock(&data.reminders_sent).clone();
+    log.reverse();
+    Ok(HttpResponse::Ok().json(log))
+}
+
+async fn get_chat_settings(req: HttpRequest, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    let session = extract_session(&req, &data)?;
+    data.require_role(&session, Role::Admin)?;
+    Ok(HttpResponse::Ok().json(chat::SettingsView::from(&*lock(&data.chat_settings))))
+}
+
+async fn save_chat_settings(
+    req: HttpRequest,
+    settings: web::Json<chat::ChatSettings>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    let session = extract_session(&req, &data)?;
+    data.require_role(&session, Role::Admin)?;
+
+    let mut settings = settings.into_inner();
+    settings.validate(&lock(&data.custom_fields))?;
+
+    let mut current = lock(&data.chat_settings);
+    // Leeres Token heißt: bisheriges des gleichnamigen Kanals behalten
+    for channel in settings.channels.iter_mut().filter(|c| c.access_token.is_empty()) {
+        if let Some(old) = current.channels.iter().find(|c| c.name == channel.name) {
+            channel.access_token = old.access_token.clone();
         }
+    }
+    *current = settings;
+    let view = chat::SettingsView::from(&*current);
+    drop(current);
+
+    data.save_chat_settings()?;
+    Ok(HttpResponse::Ok().json(view))
+}
+
+async fn run_chat(
+    req: HttpRequest,
+    params: web::Query<reminders::RunParams>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    let session = extract_session(&req, &data)?;
+    data.require_role(&session, Role::Admin)?;
+    Ok(HttpResponse::Ok().json(chat::run(&data, params.dry_run).await?))
+}
+
+async fn test_chat(
+    req: HttpRequest,
+    request: web::Json<chat::TestRequest>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    let session = extract_session(&req, &data)?;
+    data.require_role(&session, Role::Admin)?;
+    chat::send_test(&data, &request.channel).await?;
+    Ok(HttpResponse::Ok().json(serde_json::json!({"message": "Testnachricht verschickt"})))
+}
+
+async fn get_escalations(req: HttpRequest, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    let session = extract_session(&req, &data)?;
+    let employees = lock(&data.employees);
+    let visible: HashSet<u32> = employees
+        .iter()
+        .filter(|e| calendar::visible(&session.role, &session.username, e))
+        .map(|e| e.id)
+        .collect();
+    drop(employees);
+    let escalations: Vec<escalation::Escalation> = lock(&data.escalations)
+        .iter()
+        .filter(|s| s.level > 0 && visible.contains(&s.employee_id))
+        .cloned()
+        .collect();
+    Ok(HttpResponse::Ok().json(escalations))
+}
+
+async fn acknowledge_escalation(
+    req: HttpRequest,
+    path: web::Path<u32>,
+    request: web::Json<escalation::CommentRequest>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    let session = extract_session(&req, &data)?;
+    data.require_role(&session, Role::Manager)?;
+    let escalation = escalation::annotate(&data, *path, &session.username, &request.comment, false)?;
+    Ok(HttpResponse::Ok().json(escalation))
+}
+
+async fn clear_escalation(
+    req: HttpRequest,
+    path: web::Path<u32>,
+    request: web::Json<escalation::CommentRequest>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    let session = extract_session(&req, &data)?;
+    data.require_role(&session, Role::Manager)?;
+    let escalation = escalation::annotate(&data, *path, &session.username, &request.comment, true)?;
+    Ok(HttpResponse::Ok().json(escalation))
+}
+
+async fn get_escalation_settings(req: HttpRequest, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    let session = extract_session(&req, &data)?;
+    data.require_role(&session, Role::Admin)?;
+    Ok(HttpResponse::Ok().json(&*lock(&data.escalation_settings)))
+}
+
+async fn save_escalation_settings(
+    req: HttpRequest,
+    settings: web::Json<escalation::EscalationSettings>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    let session = extract_session(&req, &data)?;
+    data.require_role(&session, Role::Admin)?;
 
-        let mut users = data.users.lock().unwrap();
-        let user_id = path.into_inner();
+    let settings = settings.into_inner();
+    settings.validate(&lock(&data.custom_fields))?;
+    *lock(&data.escalation_settings) = settings.clone();
+    data.save_escalation_settings()?;
+    if escalation::clamp_levels(&mut lock(&data.escalations), settings.rules.len()) {
+        data.save_escalations()?;
+    }
+    Ok(HttpResponse::Ok().json(settings))
+}
+
+async fn get_webhooks(req: HttpRequest, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    let session = extract_session(&req, &data)?;
+    data.require_role(&session, Role::Admin)?;
+    Ok(HttpResponse::Ok().json(&*lock(&data.webhooks)))
+}
+
+async fn create_webhook(
+    req: HttpRequest,
+    input: web::Json<webhooks::WebhookInput>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    let session = extract_session(&req, &data)?;
+    data.require_role(&session, Role::Admin)?;
+    Ok(HttpResponse::Ok().json(webhooks::create(&data, input.into_inner())?))
+}
+
+async fn update_webhook(
+    req: HttpRequest,
+    path: web::Path<String>,
+    input: web::Json<webhooks::WebhookInput>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    let session = extract_session(&req, &data)?;
+    data.require_role(&session, Role::Admin)?;
+    Ok(HttpResponse::Ok().json(webhooks::update(&data, &path, input.into_inner())?))
+}
+
+async fn delete_webhook(req: HttpRequest, path: web::Path<String>, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    let session = extract_session(&req, &data)?;
+    data.require_role(&session, Role::Admin)?;
+    webhooks::delete(&data, &path)?;
+    Ok(HttpResponse::Ok().finish())
+}
+
+async fn test_webhook(req: HttpRequest, path: web::Path<String>, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    let session = extract_session(&req, &data)?;
+    data.require_role(&session, Role::Admin)?;
+    Ok(HttpResponse::Ok().json(webhooks::send_test(&data, &path).await?))
+}
+
+async fn get_webhook_deliveries(
+    req: HttpRequest,
+    params: web::Query<webhooks::DeliveryParams>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    let session = extract_session(&req, &data)?;
+    data.require_role(&session, Role::Admin)?;
+    Ok(HttpResponse::Ok().json(webhooks::deliveries(&data, &params)))
+}
+
+fn parse_job(name: &str) -> Result<scheduler::Job, ApiError> {
+    scheduler::Job::parse(name).ok_or(ApiError::NotFound("Job"))
+}
 
-        if user_id == session.user_id {
-            return Ok(HttpResponse::BadRequest().json(serde_json::json!({"error": "Kann eigenen Account nicht löschen"})));
+async fn get_jobs(req: HttpRequest, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    let session = extract_session(&req, &data)?;
+    data.require_role(&session, Role::Admin)?;
+    Ok(HttpResponse::Ok().json(scheduler::list(&data)))
+}
+
+async fn update_job(
+    req: HttpRequest,
+    path: web::Path<String>,
+    update: web::Json<scheduler::JobUpdate>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    let session = extract_session(&req, &data)?;
+    data.require_role(&session, Role::Admin)?;
+    let job = parse_job(&path)?;
+    scheduler::update(&data, job, update.into_inner())?;
+    Ok(HttpResponse::Ok().json(scheduler::list(&data)))
+}
+
+async fn run_job(req: HttpRequest, path: web::Path<String>, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    let session = extract_session(&req, &data)?;
+    data.require_role(&session, Role::Admin)?;
+    let job = parse_job(&path)?;
+    let run = scheduler::execute(&data, job, scheduler::Trigger::Manual).await?;
+    Ok(HttpResponse::Ok().json(run))
+}
+
+async fn get_job_history(
+    req: HttpRequest,
+    params: web::Query<scheduler::HistoryParams>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    let session = extract_session(&req, &data)?;
+    data.require_role(&session, Role::Admin)?;
+    Ok(HttpResponse::Ok().json(scheduler::history(&data, &params)?))
+}
+
+async fn create_employee(req: HttpRequest, employee: web::Json<EmployeeInput>, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    let session = extract_session(&req, &data)?;
+    let mut employee = employee.into_inner();
+    let status = validate_employee_input(&data, &mut employee)?;
+
+    let mut employees = lock(&data.employees);
+
+    let (unmet, strict) = unmet_prerequisites(
+        &lock(&data.catalog),
+        &employees,
+        &employee.name,
+        &employee.training,
+    );
+    if strict && !unmet.is_empty() {
+        return Err(ApiError::PrerequisitesMissing(unmet));
+    }
+
+    let mut next_id = lock(&data.next_employee_id);
+
+    let new_employee = Employee {
+        id: *next_id,
+        name: employee.name,
+        training: employee.training,
+        duedate: employee.duedate,
+        status,
+        created_by: session.username.clone(),
+        custom_fields: employee.custom_fields,
+        tags: employee.tags,
+        version: 1,
+    };
+
+    *next_id += 1;
+    employees.push(new_employee.clone());
+    drop(employees);
+    drop(next_id);
+
+    data.save_employees()?;
+    webhooks::employees_changed(&data, &[], std::slice::from_ref(&new_employee));
+    live::send(&data, live::Change::Employee { employee: new_employee.clone() });
+    Ok(HttpResponse::Ok()
+        .insert_header((header::ETAG, new_employee.etag()))
+        .json(CreateEmployeeResponse {
+            employee: new_employee,
+            warnings: unmet,
+        }))
+}
+
+/// Erwartete Version aus `If-Match` (`"3"`, `W/"3"`) oder dem Body. Ohne
+/// Angabe kommt 428, nur `If-Match: *` schaltet die Prüfung bewusst ab.
+fn expected_version(req: &HttpRequest, body_version: Option<u64>) -> Result<Option<u64>, ApiError> {
+    let Some(value) = req.headers().get(header::IF_MATCH) else {
+        return body_version.map(Some).ok_or(ApiError::PreconditionRequired);
+    };
+
+    let value = value.to_str().unwrap_or("").trim();
+    if value == "*" {
+        return Ok(None);
+    }
+    value
+        .trim_start_matches("W/")
+        .trim_matches('"')
+        .parse::<u64>()
+        .map(Some)
+        .map_err(|_| ApiError::bad_request("invalid_if_match", "If-Match muss eine Version enthalten, z.B. \"3\""))
+}
+
+/// Gemeinsamer Teil von PUT und PATCH: prüft Version und Voraussetzungen
+/// und übernimmt die validierte Eingabe.
+fn apply_employee_update(
+    data: &AppState,
+    id: u32,
+    mut input: EmployeeInput,
+    expected: Option<u64>,
+) -> Result<Employee, ApiError> {
+    let status = validate_employee_input(data, &mut input)?;
+
+    let mut employees = lock(&data.employees);
+    let pos = employees
+        .iter()
+        .position(|e| e.id == id)
+        .ok_or(ApiError::NotFound("Eintrag"))?;
+
+    if expected.is_some_and(|v| v != employees[pos].version) {
+        return Err(ApiError::VersionConflict(Box::new(employees[pos].clone())));
+    }
+
+    let changed = employees[pos].name != input.name || employees[pos].training != input.training;
+    if changed {
+        let (unmet, strict) = unmet_prerequisites(
+            &lock(&data.catalog),
+            &employees,
+            &input.name,
+            &input.training,
+        );
+        if strict && !unmet.is_empty() {
+            return Err(ApiError::PrerequisitesMissing(unmet));
         }
+    }
+
+    // ID und Ersteller bleiben erhalten, auch wenn der Body sie mitschickt
+    let previous = employees[pos].clone();
+    let existing = &mut employees[pos];
+    existing.name = input.name;
+    existing.training = input.training;
+    existing.duedate = input.duedate;
+    existing.status = status;
+    existing.custom_fields = input.custom_fields;
+    existing.tags = input.tags;
+    existing.version += 1;
+    let result = existing.clone();
+    drop(employees);
+
+    data.save_employees()?;
+    webhooks::employees_changed(data, &[previous], std::slice::from_ref(&result));
+    live::send(data, live::Change::Employee { employee: result.clone() });
+    Ok(result)
+}
+
+async fn get_employee(req: HttpRequest, path: web::Path<u32>, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    extract_session(&req, &data)?;
+    let id = path.into_inner();
+    let employees = lock(&data.employees);
+    let employee = employees
+        .iter()
+        .find(|e| e.id == id)
+        .ok_or(ApiError::NotFound("Eintrag"))?;
 
-        users.retain(|u| u.id != user_id);
-        return Ok(HttpResponse::Ok().finish());
+    Ok(HttpResponse::Ok()
+        .insert_header((header::ETAG, employee.etag()))
+        .json(employee))
+}
+
+async fn update_employee(req: HttpRequest, path: web::Path<u32>, employee: web::Json<EmployeeInput>, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    let session = extract_session(&req, &data)?;
+    data.require_role(&session, Role::User)?;
+
+    let employee = employee.into_inner();
+    let expected = expected_version(&req, employee.version)?;
+    let result = apply_employee_update(&data, path.into_inner(), employee, expected)?;
+
+    Ok(HttpResponse::Ok()
+        .insert_header((header::ETAG, result.etag()))
+        .json(result))
+}
+
+async fn patch_employee(req: HttpRequest, path: web::Path<u32>, patch: web::Json<EmployeePatch>, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    let session = extract_session(&req, &data)?;
+    data.require_role(&session, Role::User)?;
+
+    let id = path.into_inner();
+    let patch = patch.into_inner();
+    let expected = expected_version(&req, patch.version)?;
+
+    let current = lock(&data.employees)
+        .iter()
+        .find(|e| e.id == id)
+        .cloned()
+        .ok_or(ApiError::NotFound("Eintrag"))?;
+
+    let input = EmployeeInput {
+        name: patch.name.unwrap_or(current.name),
+        training: patch.training.unwrap_or(current.training),
+        duedate: patch.duedate.unwrap_or(current.duedate),
+        status: patch.status.unwrap_or_else(|| current.status.as_str().to_string()),
+        custom_fields: patch.custom_fields.unwrap_or(current.custom_fields),
+        tags: patch.tags.unwrap_or(current.tags),
+        version: None,
+    };
+    // Ohne Vorbedingung gegen den eben gelesenen Stand prüfen, damit ein
+    // paralleler Schreibzugriff zwischen Lesen und Schreiben nicht verloren geht
+    let result = apply_employee_update(&data, id, input, expected.or(Some(current.version)))?;
+
+    Ok(HttpResponse::Ok()
+        .insert_header((header::ETAG, result.etag()))
+        .json(result))
+}
+
+async fn bulk_create_employees(req: HttpRequest, request: web::Json<bulk::BulkCreateRequest>, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    let session = extract_session(&req, &data)?;
+    Ok(HttpResponse::Ok().json(bulk::create(&data, &session, request.into_inner())?))
+}
+
+async fn bulk_update_employees(req: HttpRequest, request: web::Json<bulk::BulkUpdateRequest>, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    let session = extract_session(&req, &data)?;
+    data.require_role(&session, Role::User)?;
+    Ok(HttpResponse::Ok().json(bulk::update(&data, request.into_inner())?))
+}
+
+async fn bulk_delete_employees(req: HttpRequest, request: web::Json<bulk::BulkDeleteRequest>, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    let session = extract_session(&req, &data)?;
+    data.require_role(&session, Role::Manager)?;
+    Ok(HttpResponse::Ok().json(bulk::delete(&data, request.into_inner())?))
+}
+
+async fn import_employees(req: HttpRequest, request: web::Json<import::ImportRequest>, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    let session = extract_session(&req, &data)?;
+    data.require_role(&session, Role::User)?;
+    Ok(HttpResponse::Ok().json(import::run(&data, &session, request.into_inner())?))
+}
+
+async fn delete_employee(req: HttpRequest, path: web::Path<u32>, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    let session = extract_session(&req, &data)?;
+    data.require_role(&session, Role::Manager)?;
+
+    let id = path.into_inner();
+    // Beim Löschen ist die Version freiwillig
+    let expected = if req.headers().contains_key(header::IF_MATCH) {
+        expected_version(&req, None)?
+    } else {
+        None
+    };
+    let mut employees = lock(&data.employees);
+    let pos = employees
+        .iter()
+        .position(|e| e.id == id)
+        .ok_or(ApiError::NotFound("Eintrag"))?;
+    if expected.is_some_and(|v| v != employees[pos].version) {
+        return Err(ApiError::VersionConflict(Box::new(employees[pos].clone())));
     }
-    Ok(HttpResponse::Unauthorized().finish())
+    let removed = employees.remove(pos);
+    drop(employees);
+
+    data.save_employees()?;
+    live::send(&data, live::Change::EmployeeDeleted { id: removed.id });
+    webhooks::employees_changed(&data, &[removed], &[]);
+    Ok(HttpResponse::Ok().finish())
+}
+
+async fn get_catalog(req: HttpRequest, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    extract_session(&req, &data)?;
+    let catalog = lock(&data.catalog);
+    Ok(HttpResponse::Ok().json(&*catalog))
 }
 
-async fn get_employees(req: HttpRequest, data: web::Data<AppState>) -> Result<HttpResponse> {
-    if extract_session(&req, &data).is_some() {
-        let employees = data.employees.lock().unwrap();
-        return Ok(HttpResponse::Ok().json(&*employees));
+async fn save_catalog_entry(req: HttpRequest, entry: web::Json<CatalogEntry>, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    let session = extract_session(&req, &data)?;
+    data.require_role(&session, Role::Manager)?;
+
+    let mut entry = entry.into_inner();
+    entry.name = entry.name.trim().to_string();
+    // Doppelte entfernen, Reihenfolge der Eingabe beibehalten
+    let mut seen = HashSet::new();
+    entry.prerequisites = entry
+        .prerequisites
+        .iter()
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty() && seen.insert(p.clone()))
+        .collect();
+
+    if entry.name.is_empty() {
+        let mut errors = BTreeMap::new();
+        errors.insert("name".to_string(), "Name fehlt".to_string());
+        return Err(ApiError::Validation(errors));
+    }
+
+    let mut catalog = lock(&data.catalog);
+    if creates_cycle(&catalog, &entry) {
+        return Err(ApiError::conflict("prerequisite_cycle", "Zirkuläre Voraussetzung"));
     }
-    Ok(HttpResponse::Unauthorized().finish())
+
+    match catalog.iter_mut().find(|c| c.name == entry.name) {
+        Some(existing) => *existing = entry.clone(),
+        None => catalog.push(entry.clone()),
+    }
+    drop(catalog);
+
+    data.save_catalog()?;
+    Ok(HttpResponse::Ok().json(entry))
 }
 
-async fn create_employee(req: HttpRequest, employee: web::Json<CreateEmployee>, data: web::Data<AppState>) -> Result<HttpResponse> {
-    if let Some(session) = extract_session(&req, &data) {
-        let mut employees = data.employees.lock().unwrap();
-        let mut next_id = data.next_employee_id.lock().unwrap();
+async fn delete_catalog_entry(req: HttpRequest, path: web::Path<String>, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    let session = extract_session(&req, &data)?;
+    data.require_role(&session, Role::Manager)?;
 
-        let new_employee = Employee {
-            id: *next_id,
-            name: employee.name.clone(),
-            training: employee.training.clone(),
-            duedate: employee.duedate.clone(),
-            status: employee.status.clone(),
-            created_by: session.username.clone(),
-        };
+    let name = path.into_inner();
+    let mut catalog = lock(&data.catalog);
+    let pos = catalog
+        .iter()
+        .position(|c| c.name == name)
+        .ok_or(ApiError::NotFound("Katalogeintrag"))?;
+    let dependents: Vec<&str> = catalog
+        .iter()
+        .filter(|c| c.prerequisites.contains(&name))
+        .map(|c| c.name.as_str())
+        .collect();
+    if !dependents.is_empty() {
+        return Err(ApiError::conflict(
+            "catalog_entry_in_use",
+            format!("Voraussetzung für: {}", dependents.join(", ")),
+        ));
+    }
+    catalog.remove(pos);
+    drop(catalog);
+
+    data.save_catalog()?;
+    Ok(HttpResponse::Ok().finish())
+}
+
+async fn get_custom_fields(req: HttpRequest, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    extract_session(&req, &data)?;
+    let custom_fields = lock(&data.custom_fields);
+    Ok(HttpResponse::Ok().json(&*custom_fields))
+}
+
+async fn save_custom_field(req: HttpRequest, def: web::Json<CustomFieldDef>, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    let session = extract_session(&req, &data)?;
+    data.require_role(&session, Role::Admin)?;
 
-        *next_id += 1;
-        employees.push(new_employee.clone());
-        drop(employees);
-        drop(next_id);
+    let mut def = def.into_inner();
+    def.name = def.name.trim().to_string();
+    def.label = def.label.trim().to_string();
+    def.options.retain(|o| !o.trim().is_empty());
+
+    let mut errors = BTreeMap::new();
+    if def.name.is_empty() || !def.name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        errors.insert("name".to_string(), "Feldname ungültig (a-z, 0-9, _)".to_string());
+    }
+    if def.kind == FieldKind::Enum && def.options.is_empty() {
+        errors.insert("options".to_string(), "Auswahlfeld ohne Optionen".to_string());
+    }
+    if !errors.is_empty() {
+        return Err(ApiError::Validation(errors));
+    }
+    if def.label.is_empty() {
+        def.label = def.name.clone();
+    }
 
-        data.save_employees();
-        return Ok(HttpResponse::Ok().json(new_employee));
+    let mut custom_fields = lock(&data.custom_fields);
+    match custom_fields.iter_mut().find(|d| d.name == def.name) {
+        Some(existing) => *existing = def.clone(),
+        None => custom_fields.push(def.clone()),
     }
-    Ok(HttpResponse::Unauthorized().finish())
+    drop(custom_fields);
+
+    data.save_custom_fields()?;
+    Ok(HttpResponse::Ok().json(def))
 }
 
-async fn update_employee(req: HttpRequest, path: web::Path<u32>, employee: web::Json<Employee>, data: web::Data<AppState>) -> Result<HttpResponse> {
-    if let Some(session) = extract_session(&req, &data) {
-        if !data.check_permission(&session, Role::User) {
-            return Ok(HttpResponse::Forbidden().finish());
+async fn delete_custom_field(req: HttpRequest, path: web::Path<String>, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    let session = extract_session(&req, &data)?;
+    data.require_role(&session, Role::Admin)?;
+
+    let name = path.into_inner();
+    let mut custom_fields = lock(&data.custom_fields);
+    let before = custom_fields.len();
+    custom_fields.retain(|d| d.name != name);
+    if custom_fields.len() == before {
+        return Err(ApiError::NotFound("Zusatzfeld"));
+    }
+    drop(custom_fields);
+
+    // Verwaiste Werte entfernen, sonst scheitert die Validierung beim nächsten Bearbeiten
+    let mut employees = lock(&data.employees);
+    let before = employees.clone();
+    for employee in employees.iter_mut() {
+        if employee.custom_fields.remove(&name).is_some() {
+            employee.version += 1;
         }
+    }
+    let after = employees.clone();
+    drop(employees);
 
-        let mut employees = data.employees.lock().unwrap();
-        let id = path.into_inner();
+    data.save_custom_fields()?;
+    data.save_employees()?;
+    live::employees_changed(&data, &before, &after);
+    webhooks::employees_changed(&data, &before, &after);
+    Ok(HttpResponse::Ok().finish())
+}
 
-        if let Some(pos) = employees.iter().position(|e| e.id == id) {
-            employees[pos] = employee.into_inner();
-            let result = employees[pos].clone();
-            drop(employees);
+fn validate_tag_name(name: &str) -> Result<(), ApiError> {
+    if name.is_empty() || name.contains([';', ',']) {
+        let mut errors = BTreeMap::new();
+        errors.insert("name".to_string(), "Tag leer oder enthält ; bzw. ,".to_string());
+        return Err(ApiError::Validation(errors));
+    }
+    Ok(())
+}
 
-            data.save_employees();
-            return Ok(HttpResponse::Ok().json(result));
-        }
+async fn get_tags(req: HttpRequest, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    extract_session(&req, &data)?;
+    let tags = lock(&data.tags);
+    Ok(HttpResponse::Ok().json(&*tags))
+}
+
+async fn create_tag(req: HttpRequest, tag: web::Json<TagRequest>, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    let session = extract_session(&req, &data)?;
+    data.require_role(&session, Role::Manager)?;
+
+    let name = tag.name.trim().to_string();
+    validate_tag_name(&name)?;
+
+    let mut tags = lock(&data.tags);
+    if tags.contains(&name) {
+        return Err(ApiError::conflict("tag_exists", "Tag existiert"));
     }
-    Ok(HttpResponse::Unauthorized().finish())
+    tags.push(name.clone());
+    tags.sort();
+    drop(tags);
+
+    data.save_tags()?;
+    Ok(HttpResponse::Ok().json(name))
 }
 
-async fn delete_employee(req: HttpRequest, path: web::Path<u32>, data: web::Data<AppState>) -> Result<HttpResponse> {
-    if let Some(session) = extract_session(&req, &data) {
-        if !data.check_permission(&session, Role::Manager) {
-            return Ok(HttpResponse::Forbidden().finish());
+async fn rename_tag(req: HttpRequest, path: web::Path<String>, tag: web::Json<TagRequest>, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    let session = extract_session(&req, &data)?;
+    data.require_role(&session, Role::Manager)?;
+
+    let old_name = path.into_inner();
+    let new_name = tag.name.trim().to_string();
+    validate_tag_name(&new_name)?;
+
+    let mut tags = lock(&data.tags);
+    if !tags.contains(&old_name) {
+        return Err(ApiError::NotFound("Tag"));
+    }
+    if old_name != new_name && tags.contains(&new_name) {
+        return Err(ApiError::conflict("tag_exists", "Tag existiert"));
+    }
+    for t in tags.iter_mut().filter(|t| **t == old_name) {
+        *t = new_name.clone();
+    }
+    tags.sort();
+    drop(tags);
+
+    let mut employees = lock(&data.employees);
+    let before = employees.clone();
+    for employee in employees.iter_mut() {
+        if let Some(t) = employee.tags.iter_mut().find(|t| **t == old_name) {
+            *t = new_name.clone();
+            employee.version += 1;
         }
+    }
+    let after = employees.clone();
+    drop(employees);
 
-        let id = path.into_inner();
-        let mut employees = data.employees.lock().unwrap();
-        employees.retain(|e| e.id != id);
-        drop(employees);
+    data.save_tags()?;
+    data.save_employees()?;
+    live::employees_changed(&data, &before, &after);
+    webhooks::employees_changed(&data, &before, &after);
+    Ok(HttpResponse::Ok().json(new_name))
+}
+
+async fn delete_tag(req: HttpRequest, path: web::Path<String>, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    let session = extract_session(&req, &data)?;
+    data.require_role(&session, Role::Manager)?;
+
+    let name = path.into_inner();
+    let mut tags = lock(&data.tags);
+    let before = tags.len();
+    tags.retain(|t| *t != name);
+    if tags.len() == before {
+        return Err(ApiError::NotFound("Tag"));
+    }
+    drop(tags);
 
-        data.save_employees();
-        return Ok(HttpResponse::Ok().finish());
+    let mut employees = lock(&data.employees);
+    let before = employees.clone();
+    for employee in employees.iter_mut() {
+        let count = employee.tags.len();
+        employee.tags.retain(|t| *t != name);
+        if employee.tags.len() != count {
+            employee.version += 1;
+        }
     }
-    Ok(HttpResponse::Unauthorized().finish())
+    let after = employees.clone();
+    drop(employees);
+
+    data.save_tags()?;
+    data.save_employees()?;
+    live::employees_changed(&data, &before, &after);
+    webhooks::employees_changed(&data, &before, &after);
+    Ok(HttpResponse::Ok().finish())
+}
+
+async fn get_person_prerequisites(req: HttpRequest, path: web::Path<String>, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    extract_session(&req, &data)?;
+    let person = path.into_inner();
+    let employees = lock(&data.employees);
+    let catalog = lock(&data.catalog);
+
+    let mut trainings: Vec<&str> = employees
+        .iter()
+        .filter(|e| same_person(&e.name, &person))
+        .map(|e| e.training.as_str())
+        .collect();
+    trainings.sort_unstable();
+    trainings.dedup();
+
+    let chain: Vec<PrerequisiteNode> = trainings
+        .into_iter()
+        .map(|t| prerequisite_tree(&catalog, &employees, &person, t, &mut HashSet::new()))
+        .collect();
+
+    Ok(HttpResponse::Ok().json(chain))
 }
 
-fn extract_session(req: &HttpRequest, data: &web::Data<AppState>) -> Option<Session> {
-    req.headers()
+fn extract_session(req: &HttpRequest, data: &web::Data<AppState>) -> Result<Session, ApiError> {
+    let token = req
+        .headers()
         .get("Authorization")
         .and_then(|h| h.to_str().ok())
-        .and_then(|s| s.strip_prefix("Bearer "))
-        .and_then(|token| data.verify_token(token))
+        .and_then(|s| s.strip_prefix("Bearer "));
+    let session = match token {
+        Some(token) => data.touch_token(token),
+        // Maschinen-Clients melden sich mit einem Client-Zertifikat an (mTLS)
+        None => req
+            .conn_data::<tls::ClientIdentity>()
+            .and_then(|identity| data.certificate_session(&identity.0)),
+    }
+    .ok_or(ApiError::Unauthorized)?;
+    logging::record_user(&session.username);
+    Ok(session)
+}
+
+/// Lebenszeichen für den Orchestrator, ohne Anmeldung.
+async fn health() -> HttpResponse {
+    HttpResponse::Ok().json(serde_json::json!({ "status": "ok" }))
+}
+
+/// Bereit, sobald das Datenverzeichnis beschreibbar ist; sonst 503, damit
+/// der Load-Balancer keine Anfragen schickt, die beim Speichern scheitern.
+/// Beim Beenden ebenfalls 503, damit er keine neuen mehr schickt.
+async fn ready(data: web::Data<AppState>) -> HttpResponse {
+    if *data.shutdown.borrow() {
+        return HttpResponse::ServiceUnavailable().json(serde_json::json!({ "status": "shutting_down" }));
+    }
+    let read_only = lock(&data.maintenance).read_only;
+    let probe = ".ready-probe";
+    let storage = std::fs::write(probe, b"ok").and_then(|()| std::fs::remove_file(probe));
+    match storage {
+        Ok(()) => HttpResponse::Ok().json(serde_json::json!({
+            "status": "ready",
+            "read_only": read_only,
+            "checks": { "storage": "ok" },
+        })),
+        Err(err) => HttpResponse::ServiceUnavailable().json(serde_json::json!({
+            "status": "not_ready",
+            "checks": { "storage": err.to_string() },
+        })),
+    }
+}
+
+async fn get_metrics(req: HttpRequest, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    if !metrics::token_valid(&req, &data.config.metrics.token) {
+        let session = extract_session(&req, &data)?;
+        data.require_role(&session, Role::Admin)?;
+    }
+    Ok(metrics_response(&data))
+}
+
+/// `/metrics` auf dem eigenen Monitoring-Port: ohne Token frei abrufbar.
+async fn scrape_metrics(req: HttpRequest, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    if !data.config.metrics.token.is_empty() && !metrics::token_valid(&req, &data.config.metrics.token) {
+        return Err(ApiError::Unauthorized);
+    }
+    Ok(metrics_response(&data))
+}
+
+fn metrics_response(data: &AppState) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4; charset=utf-8")
+        .body(metrics::render(data))
 }
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
-    println!("╔════════════════════════════════════════╗");
-    println!("║   Training Dashboard Backend v2.0     ║");
-    println!("╚════════════════════════════════════════╝");
-    println!("🚀 Server: http://127.0.0.1:8080");
-    println!();
+    let cli = config::Cli::parse();
+    let config = match config::Config::load(&cli) {
+        Ok(config) => config,
+        Err(errors) => {
+            eprintln!("✗ Ungültige Konfiguration:");
+            for (key, message) in errors {
+                eprintln!("  {}: {}", key, message);
+            }
+            std::process::exit(2);
+        }
+    };
+    if cli.print_config {
+        print!("{}", config.to_toml());
+        return Ok(());
+    }
+
+    // Bis zum Ende von main halten, damit gepufferte Zeilen geschrieben werden
+    let _log_guard = logging::init(&config.log).map_err(|e| std::io::Error::other(format!("Logging: {}", e)))?;
 
-    let app_state = web::Data::new(AppState::new());
+    // Alle Dateien (employees.csv, *.json, backups/) liegen im Datenverzeichnis
+    let data_dir = &config.storage.data_dir;
+    std::fs::create_dir_all(data_dir)
+        .and_then(|()| std::env::set_current_dir(data_dir))
+        .map_err(|e| std::io::Error::other(format!("Datenverzeichnis {}: {}", data_dir.display(), e)))?;
 
-    HttpServer::new(move || {
-        let cors = Cors::default()
-            .allow_any_origin()
+    let rustls = if config.tls.enabled() {
+        let (rustls, resolver) =
+            tls::server_config(&config.tls).map_err(|e| std::io::Error::other(format!("TLS: {}", e)))?;
+        actix_web::rt::spawn(tls::watch(config.tls.clone(), resolver));
+        Some(rustls)
+    } else {
+        None
+    };
+
+    let scheme = if rustls.is_some() { "https" } else { "http" };
+    tracing::info!(
+        version = env!("CARGO_PKG_VERSION"),
+        address = %format!("{}://{}:{}", scheme, config.server.bind, config.server.port),
+        data_dir = %std::env::current_dir()?.display(),
+        "Training Dashboard Backend startet"
+    );
+    if config.tls.redirect_port != 0 {
+        tracing::info!(port = config.tls.redirect_port, "Weiterleitung von HTTP auf HTTPS");
+    }
+    if !config.tls.client_ca.as_os_str().is_empty() {
+        tracing::info!(client_auth = %config.tls.client_auth, "Client-Zertifikate aktiv");
+    }
+    if config.metrics.port != 0 {
+        tracing::info!(address = %format!("http://{}:{}/metrics", config.metrics.bind, config.metrics.port), "Monitoring");
+    }
+    if !config.notifications.enabled {
+        tracing::warn!("Benachrichtigungen abgeschaltet");
+    }
+    if config.server.read_only {
+        tracing::warn!("Wartungsmodus: Änderungen werden abgelehnt");
+    }
+
+    let address = (config.server.bind.clone(), config.server.port);
+    let redirect_address = (config.server.bind.clone(), config.tls.redirect_port);
+    let metrics_address = (config.metrics.bind.clone(), config.metrics.port);
+    let shutdown_timeout = config.server.shutdown_timeout_seconds;
+    let cors_origins = config.server.cors_origins.clone();
+    let app_state = web::Data::new(
+        AppState::new(config).map_err(|e| std::io::Error::other(format!("Start fehlgeschlagen: {:?}", e)))?,
+    );
+
+    // Zeitgesteuerte Jobs laufen unabhängig von Anfragen im Hintergrund
+    actix_web::rt::spawn(scheduler::run_loop(app_state.clone()));
+
+    let metrics_state = app_state.clone();
+    let shutdown_state = app_state.clone();
+    let server = HttpServer::new(move || {
+        let mut cors = Cors::default()
             .allow_any_method()
-            .allow_any_header();
+            .allow_any_header()
+            .expose_headers(["X-Total-Count", "ETag", "Content-Disposition", "X-Request-Id"]);
+        for origin in &cors_origins {
+            cors = if origin == "*" { cors.allow_any_origin() } else { cors.allowed_origin(origin) };
+        }
+
+        let json_config = web::JsonConfig::default().error_handler(|err, _req| {
+            ApiError::bad_request("invalid_body", format!("Ungültiger JSON-Body: {}", err)).into()
+        });
+        let path_config = web::PathConfig::default().error_handler(|err, _req| {
+            ApiError::bad_request("invalid_path", format!("Ungültiger Pfad: {}", err)).into()
+        });
+        let query_config = web::QueryConfig::default().error_handler(|err, _req| {
+            ApiError::bad_request("invalid_query", format!("Ungültige Parameter: {}", err)).into()
+        });
 
         App::new()
+            .wrap_fn(|req, srv| {
+                let response = match maintenance::check_request(&req) {
+                    Ok(gate) => Ok((gate, srv.call(req))),
+                    Err(err) => Err(req.error_response(err)),
+                };
+                async move {
+                    match response {
+                        Ok((_gate, response)) => Ok(response.await?.map_into_left_body()),
+                        Err(rejected) => Ok(rejected.map_into_right_body()),
+                    }
+                }
+            })
             .wrap(cors)
-            .wrap(middleware::Logger::default())
+            .wrap_fn(|req, srv| {
+                let started = std::time::Instant::now();
+                let response = srv.call(req);
+                async move {
+                    let mut response = response.await?;
+                    if let Some(data) = response.request().app_data::<web::Data<AppState>>() {
+                        data.metrics.observe(response.request(), response.status(), started.elapsed());
+                    }
+                    logging::add_request_id(&mut response);
+                    Ok(response)
+                }
+            })
+            .wrap(TracingLogger::<logging::RequestSpan>::new())
             .app_data(app_state.clone())
+            .app_data(json_config.clone())
+            .app_data(path_config)
+            .app_data(query_config)
+            .route("/health", web::get().to(health))
+            .route("/ready", web::get().to(ready))
+            .route("/metrics", web::get().to(get_metrics))
             .route("/api/auth/login", web::post().to(login))
             .route("/api/auth/logout", web::post().to(logout))
             .route("/api/auth/me", web::get().to(get_current_user))
+            .route("/api/maintenance", web::get().to(get_maintenance))
+            .route("/api/maintenance", web::put().to(set_maintenance))
             .route("/api/users", web::get().to(get_users))
             .route("/api/users", web::post().to(create_user))
             .route("/api/users/{id}", web::put().to(update_user))
             .route("/api/users/{id}", web::delete().to(delete_user))
+            .route("/api/events", web::get().to(live_events))
             .route("/api/employees", web::get().to(get_employees))
             .route("/api/employees", web::post().to(create_employee))
+            .route("/api/employees/stats", web::get().to(get_employee_stats))
+            .route("/api/employees/export", web::get().to(export_employees))
+            .route("/api/employees/bulk", web::post().to(bulk_create_employees))
+            .route("/api/employees/bulk", web::patch().to(bulk_update_employees))
+            .route("/api/employees/bulk", web::delete().to(bulk_delete_employees))
+            .service(
+                // Dateien kommen Base64-kodiert im Body und sind größer als üblich
+                web::resource("/api/employees/import")
+                    .app_data(json_config.clone().limit(import::MAX_IMPORT_BYTES))
+                    .route(web::post().to(import_employees)),
+            )
+            .route("/api/employees/{id}", web::get().to(get_employee))
             .route("/api/employees/{id}", web::put().to(update_employee))
+            .route("/api/employees/{id}", web::patch().to(patch_employee))
             .route("/api/employees/{id}", web::delete().to(delete_employee))
+            .route("/api/reports/compliance", web::get().to(report_compliance))
+            .route("/api/reports/persons/{name}", web::get().to(report_person))
+            .route("/api/reports/certificates/{id}", web::get().to(report_certificate))
+            .route("/api/calendar/feed", web::get().to(get_calendar_feed))
+            .route("/api/calendar/feed", web::post().to(reset_calendar_feed))
+            .route("/api/calendar/{token}.ics", web::get().to(calendar_ics))
+            .route("/api/reminders/settings", web::get().to(get_reminder_settings))
+            .route("/api/reminders/settings", web::put().to(save_reminder_settings))
+            .route("/api/reminders/run", web::post().to(run_reminders))
+            .route("/api/reminders/test", web::post().to(test_reminder_mail))
+            .route("/api/reminders/log", web::get().to(get_reminder_log))
+            .route("/api/chat/settings", web::get().to(get_chat_settings))
+            .route("/api/chat/settings", web::put().to(save_chat_settings))
+            .route("/api/chat/run", web::post().to(run_chat))
+            .route("/api/chat/test", web::post().to(test_chat))
+            .route("/api/escalations", web::get().to(get_escalations))
+            .route("/api/escalations/settings", web::get().to(get_escalation_settings))
+            .route("/api/escalations/settings", web::put().to(save_escalation_settings))
+            .route("/api/escalations/{id}/acknowledge", web::post().to(acknowledge_escalation))
+            .route("/api/escalations/{id}/clear", web::post().to(clear_escalation))
+            .route("/api/webhooks", web::get().to(get_webhooks))
+            .route("/api/webhooks", web::post().to(create_webhook))
+            .route("/api/webhooks/deliveries", web::get().to(get_webhook_deliveries))
+            .route("/api/webhooks/{id}", web::put().to(update_webhook))
+            .route("/api/webhooks/{id}", web::delete().to(delete_webhook))
+            .route("/api/webhooks/{id}/test", web::post().to(test_webhook))
+            .route("/api/jobs", web::get().to(get_jobs))
+            .route("/api/jobs/history", web::get().to(get_job_history))
+            .route("/api/jobs/{name}", web::put().to(update_job))
+            .route("/api/jobs/{name}/run", web::post().to(run_job))
+            .route("/api/catalog", web::get().to(get_catalog))
+            .route("/api/catalog", web::post().to(save_catalog_entry))
+            .route("/api/catalog/{name}", web::delete().to(delete_catalog_entry))
+            .route("/api/persons/{name}/prerequisites", web::get().to(get_person_prerequisites))
+            .route("/api/custom-fields", web::get().to(get_custom_fields))
+            .route("/api/custom-fields", web::post().to(save_custom_field))
+            .route("/api/custom-fields/{name}", web::delete().to(delete_custom_field))
+            .route("/api/tags", web::get().to(get_tags))
+            .route("/api/tags", web::post().to(create_tag))
+            .route("/api/tags/{name}", web::put().to(rename_tag))
+            .route("/api/tags/{name}", web::delete().to(delete_tag))
     })
-    .bind(("127.0.0.1", 8080))?
-    .run()
-    .await
-}
\ No newline at end of file
+    .on_connect(tls::on_connect)
+    // Signale behandelt `shutdown::on_signal` für alle Server gemeinsam
+    .disable_signals()
+    .shutdown_timeout(shutdown_timeout);
+
+    let https_port = address.1;
+    let mut servers = vec![match rustls {
+        Some(rustls) => server.bind_rustls_0_23(address, rustls)?.run(),
+        None => server.bind(address)?.run(),
+    }];
+    if redirect_address.1 != 0 {
+        let redirect = HttpServer::new(move || {
+            App::new().default_service(web::to(move |req: HttpRequest| tls::redirect(req, https_port)))
+        })
+        .workers(1)
+        .disable_signals()
+        .bind(redirect_address)?;
+        servers.push(redirect.run());
+    }
+    if metrics_address.1 != 0 {
+        // Nur für das Monitoring, üblicherweise nicht von außen erreichbar
+        let monitoring = HttpServer::new(move || {
+            App::new()
+                .app_data(metrics_state.clone())
+                .route("/health", web::get().to(health))
+                .route("/ready", web::get().to(ready))
+                .route("/metrics", web::get().to(scrape_metrics))
+        })
+        .workers(1)
+        .disable_signals()
+        .bind(metrics_address)?;
+        servers.push(monitoring.run());
+    }
+    let handles = servers.iter().map(|server| server.handle()).collect();
+    actix_web::rt::spawn(shutdown::on_signal(shutdown_state.clone(), handles));
+    futures_util::future::try_join_all(servers).await?;
+
+    shutdown::flush(&shutdown_state).await;
+    tracing::info!("Backend beendet");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+    use actix_web::ResponseError;
+
+    fn request(if_match: Option<&str>) -> HttpRequest {
+        let mut req = TestRequest::put();
+        if let Some(value) = if_match {
+            req = req.insert_header((header::IF_MATCH, value));
+        }
+        req.to_http_request()
+    }
+
+    #[test]
+    fn expected_version_from_header_or_body() {
+        assert_eq!(expected_version(&request(Some("\"3\"")), None).unwrap(), Some(3));
+        assert_eq!(expected_version(&request(Some("W/\"4\"")), Some(9)).unwrap(), Some(4));
+        assert_eq!(expected_version(&request(None), Some(5)).unwrap(), Some(5));
+    }
+
+    #[test]
+    fn expected_version_star_skips_check() {
+        assert_eq!(expected_version(&request(Some("*")), Some(5)).unwrap(), None);
+    }
+
+    #[test]
+    fn expected_version_missing_is_precondition_required() {
+        let err = expected_version(&request(None), None).unwrap_err();
+        assert_eq!(err.code(), "precondition_required");
+        assert_eq!(err.status_code(), actix_web::http::StatusCode::PRECONDITION_REQUIRED);
+    }
+
+    #[test]
+    fn expected_version_rejects_garbage() {
+        let err = expected_version(&request(Some("abc")), None).unwrap_err();
+        assert_eq!(err.code(), "invalid_if_match");
+    }
+}
@@ -0,0 +1,93 @@
+use actix_web::dev::ServiceRequest;
+use actix_web::http::Method;
+use actix_web::web;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use tokio::sync::{OwnedRwLockReadGuard, RwLock};
+
+use crate::error::ApiError;
+use crate::{lock, AppState};
+
+// Bleiben im Wartungsmodus möglich: Sitzungen liegen nur im Speicher
+const ALWAYS_ALLOWED: [&str; 3] = ["/api/auth/login", "/api/auth/logout", "/api/maintenance"];
+
+/// Schreibende Anfragen und Jobs halten die Sperre geteilt, die Sicherung
+/// exklusiv – so kopiert sie keine halb gespeicherten Änderungen.
+pub type WriteGate = Arc<RwLock<()>>;
+
+/// Wartungsmodus: Lesen geht weiter, Änderungen werden mit 503 abgelehnt –
+/// etwa während einer Migration oder einer Sicherung von außen.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Maintenance {
+    pub read_only: bool,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub since: Option<String>,
+    // Leer, wenn per Konfiguration eingeschaltet
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub by: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct MaintenanceRequest {
+    pub read_only: bool,
+    #[serde(default)]
+    pub message: String,
+}
+
+impl Maintenance {
+    pub fn new(request: MaintenanceRequest, by: Option<String>) -> Self {
+        Maintenance {
+            since: request.read_only.then(|| chrono::Utc::now().to_rfc3339()),
+            read_only: request.read_only,
+            message: request.message.trim().to_string(),
+            by: by.filter(|_| request.read_only),
+        }
+    }
+}
+
+/// Fehler, solange nichts gespeichert werden darf: beim Beenden, während
+/// der eingebauten Sicherung und im Wartungsmodus.
+pub fn check_writable(data: &AppState) -> Result<(), ApiError> {
+    if *data.shutdown.borrow() {
+        return Err(ApiError::unavailable("shutting_down", "Server wird beendet"));
+    }
+    if data.backup_running.load(Ordering::SeqCst) {
+        return Err(ApiError::unavailable("maintenance", "Sicherung läuft, bitte gleich erneut versuchen"));
+    }
+    let maintenance = lock(&data.maintenance);
+    if !maintenance.read_only {
+        return Ok(());
+    }
+    let message = if maintenance.message.is_empty() {
+        "Wartungsmodus – Änderungen sind vorübergehend nicht möglich".to_string()
+    } else {
+        format!("Wartungsmodus: {}", maintenance.message)
+    };
+    Err(ApiError::unavailable("maintenance", message))
+}
+
+// Jobs prüfen den Wartungsmodus und nehmen die Sperre selbst, siehe `scheduler::execute`
+fn runs_job(path: &str) -> bool {
+    path.starts_with("/api/jobs/") && path.ends_with("/run")
+}
+
+/// Für `wrap_fn`: schreibende Anfragen werden abgelehnt, bevor ein Handler
+/// läuft. Zugelassene halten bis zu ihrem Ende die `WriteGate` geteilt.
+pub fn check_request(req: &ServiceRequest) -> Result<Option<OwnedRwLockReadGuard<()>>, ApiError> {
+    let reading = matches!(*req.method(), Method::GET | Method::HEAD | Method::OPTIONS);
+    if reading || ALWAYS_ALLOWED.contains(&req.path()) || runs_job(req.path()) {
+        return Ok(None);
+    }
+    let Some(data) = req.app_data::<web::Data<AppState>>() else {
+        return Ok(None);
+    };
+    check_writable(data)?;
+    // Wartet die Sicherung bereits auf die Sperre, lieber ablehnen als hängen
+    data.write_gate
+        .clone()
+        .try_read_owned()
+        .map(Some)
+        .map_err(|_| ApiError::unavailable("maintenance", "Sicherung läuft, bitte gleich erneut versuchen"))
+}
@@ -0,0 +1,128 @@
+use actix_web::http::StatusCode;
+use actix_web::HttpRequest;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::{escalation, lock, AppState, Status};
+
+// Obergrenzen der Latenz-Buckets in Sekunden
+const BUCKETS: [f64; 11] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+// Anfragen ohne passende Route, damit Scanner keine neuen Zeitreihen erzeugen
+const UNMATCHED: &str = "unmatched";
+
+#[derive(Default)]
+struct Latency {
+    buckets: [u64; BUCKETS.len()],
+    count: u64,
+    sum: f64,
+}
+
+/// Zähler für `GET /metrics`. Anfragen werden je Routen-Muster (z.B.
+/// `/api/employees/{id}`) erfasst, nicht je konkretem Pfad.
+#[derive(Default)]
+pub struct Metrics {
+    // (Methode, Route, Status)
+    requests: Mutex<BTreeMap<(String, String, u16), u64>>,
+    // (Methode, Route)
+    latencies: Mutex<BTreeMap<(String, String), Latency>>,
+    login_failures: AtomicU64,
+}
+
+impl Metrics {
+    pub fn observe(&self, req: &HttpRequest, status: StatusCode, elapsed: Duration) {
+        let method = req.method().as_str().to_string();
+        let route = req.match_pattern().unwrap_or_else(|| UNMATCHED.to_string());
+        *lock(&self.requests).entry((method.clone(), route.clone(), status.as_u16())).or_default() += 1;
+
+        let seconds = elapsed.as_secs_f64();
+        let mut latencies = lock(&self.latencies);
+        let latency = latencies.entry((method, route)).or_default();
+        for (bucket, bound) in latency.buckets.iter_mut().zip(BUCKETS) {
+            if seconds <= bound {
+                *bucket += 1;
+            }
+        }
+        latency.count += 1;
+        latency.sum += seconds;
+    }
+
+    pub fn login_failed(&self) {
+        self.login_failures.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+fn label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+/// Prometheus-Textformat (Version 0.0.4).
+pub fn render(data: &AppState) -> String {
+    let mut out = String::new();
+    let metrics = &data.metrics;
+
+    header(&mut out, "training_http_requests_total", "counter", "Anfragen je Route und Status");
+    for ((method, route, status), count) in lock(&metrics.requests).iter() {
+        let _ = writeln!(
+            out,
+            "training_http_requests_total{{method=\"{}\",route=\"{}\",status=\"{}\"}} {}",
+            method,
+            label(route),
+            status,
+            count
+        );
+    }
+
+    header(&mut out, "training_http_request_duration_seconds", "histogram", "Antwortzeit je Route");
+    for ((method, route), latency) in lock(&metrics.latencies).iter() {
+        let labels = format!("method=\"{}\",route=\"{}\"", method, label(route));
+        for (count, bound) in latency.buckets.iter().zip(BUCKETS) {
+            let _ = writeln!(out, "training_http_request_duration_seconds_bucket{{{},le=\"{}\"}} {}", labels, bound, count);
+        }
+        let _ = writeln!(out, "training_http_request_duration_seconds_bucket{{{},le=\"+Inf\"}} {}", labels, latency.count);
+        let _ = writeln!(out, "training_http_request_duration_seconds_sum{{{}}} {}", labels, latency.sum);
+        let _ = writeln!(out, "training_http_request_duration_seconds_count{{{}}} {}", labels, latency.count);
+    }
+
+    header(&mut out, "training_login_failures_total", "counter", "Fehlgeschlagene Anmeldungen");
+    let _ = writeln!(out, "training_login_failures_total {}", metrics.login_failures.load(Ordering::Relaxed));
+
+    let now = chrono::Utc::now();
+    let sessions = lock(&data.sessions)
+        .iter()
+        .filter(|s| !data.config.sessions.expired(&s.created_at, &s.last_seen, now))
+        .count();
+    header(&mut out, "training_sessions_active", "gauge", "Gültige Sitzungen");
+    let _ = writeln!(out, "training_sessions_active {}", sessions);
+
+    let today = chrono::Local::now().date_naive();
+    let employees = lock(&data.employees);
+    header(&mut out, "training_trainings", "gauge", "Schulungseinträge je Status");
+    for status in [Status::Gruen, Status::Gelb, Status::Rot] {
+        let count = employees.iter().filter(|e| e.status == status).count();
+        let _ = writeln!(out, "training_trainings{{status=\"{}\"}} {}", status.as_str(), count);
+    }
+    let overdue = employees.iter().filter(|e| escalation::days_overdue(e, today).is_some()).count();
+    header(&mut out, "training_trainings_overdue", "gauge", "Überfällige Schulungen");
+    let _ = writeln!(out, "training_trainings_overdue {}", overdue);
+
+    out
+}
+
+/// Prüft das Bearer-Token für Prometheus; ohne konfiguriertes Token nie.
+pub fn token_valid(req: &HttpRequest, token: &str) -> bool {
+    !token.is_empty()
+        && req
+            .headers()
+            .get("Authorization")
+            .and_then(|h| h.to_str().ok())
+            .and_then(|s| s.strip_prefix("Bearer "))
+            .is_some_and(|given| given == token)
+}
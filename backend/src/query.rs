@@ -0,0 +1,398 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+use crate::error::ApiError;
+use crate::{Employee, Status};
+
+/// Query-Parameter für `GET /api/employees` und alle Endpunkte, die
+/// dieselbe Auswahl liefern (Statistik, Export, ...).
+#[derive(Debug, Default, Deserialize)]
+pub struct EmployeeQuery {
+    // Kommagetrennte Tags, z.B. ?tags=Pflicht,Arbeitsschutz
+    pub tags: Option<String>,
+    // "all" (Standard) oder "any"
+    pub tag_match: Option<String>,
+    // Kommagetrennte Status, z.B. ?status=gelb,rot
+    pub status: Option<String>,
+    pub due_from: Option<String>,
+    pub due_to: Option<String>,
+    pub training: Option<String>,
+    pub created_by: Option<String>,
+    // Volltextsuche über ID, Name, Training, Tags und Zusatzfelder
+    pub q: Option<String>,
+    // id, name, training, duedate, status, created_by oder custom_fields.<name>
+    pub sort: Option<String>,
+    // "asc" (Standard) oder "desc"
+    pub order: Option<String>,
+    // 1-basiert
+    pub page: Option<usize>,
+    // Ohne limit wird die gesamte Auswahl geliefert
+    pub limit: Option<usize>,
+}
+
+pub const MAX_LIMIT: usize = 1000;
+
+pub struct EmployeeFilter {
+    statuses: Vec<Status>,
+    due_from: Option<NaiveDate>,
+    due_to: Option<NaiveDate>,
+    training: Option<String>,
+    created_by: Option<String>,
+    terms: Vec<String>,
+    tags: Vec<String>,
+    match_any_tag: bool,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum SortField<'a> {
+    Id,
+    Name,
+    Training,
+    Duedate,
+    Status,
+    CreatedBy,
+    Custom(&'a str),
+}
+
+#[derive(Serialize, Default)]
+pub struct EmployeeStats {
+    pub total: usize,
+    pub gruen: usize,
+    pub gelb: usize,
+    pub rot: usize,
+    pub tags: BTreeMap<String, usize>,
+}
+
+fn split_list(value: Option<&str>) -> impl Iterator<Item = &str> {
+    value
+        .unwrap_or("")
+        .split(',')
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+}
+
+fn parse_date(value: Option<&str>, field: &str, errors: &mut BTreeMap<String, String>) -> Option<NaiveDate> {
+    let value = value.map(str::trim).filter(|v| !v.is_empty())?;
+    match NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        Ok(date) => Some(date),
+        Err(_) => {
+            errors.insert(field.to_string(), "Kein gültiges Datum (YYYY-MM-DD)".to_string());
+            None
+        }
+    }
+}
+
+impl EmployeeQuery {
+    pub fn filter(&self) -> Result<EmployeeFilter, ApiError> {
+        let mut errors = BTreeMap::new();
+
+        let mut statuses = Vec::new();
+        for value in split_list(self.status.as_deref()) {
+            match Status::parse(value) {
+                Some(status) => statuses.push(status),
+                None => {
+                    errors.insert("status".to_string(), "Erlaubt: gruen, gelb, rot".to_string());
+                }
+            }
+        }
+
+        let due_from = parse_date(self.due_from.as_deref(), "due_from", &mut errors);
+        let due_to = parse_date(self.due_to.as_deref(), "due_to", &mut errors);
+
+        match self.tag_match.as_deref() {
+            None | Some("all") | Some("any") => {}
+            Some(_) => {
+                errors.insert("tag_match".to_string(), "Erlaubt: all, any".to_string());
+            }
+        }
+
+        if let Err(message) = self.sort_field() {
+            errors.insert("sort".to_string(), message);
+        }
+        match self.order.as_deref() {
+            None | Some("asc") | Some("desc") => {}
+            Some(_) => {
+                errors.insert("order".to_string(), "Erlaubt: asc, desc".to_string());
+            }
+        }
+        if self.page == Some(0) {
+            errors.insert("page".to_string(), "Seiten beginnen bei 1".to_string());
+        }
+        if matches!(self.limit, Some(limit) if limit == 0 || limit > MAX_LIMIT) {
+            errors.insert("limit".to_string(), format!("Erlaubt: 1 bis {}", MAX_LIMIT));
+        }
+
+        if !errors.is_empty() {
+            return Err(ApiError::Validation(errors));
+        }
+
+        let lower = |v: &Option<String>| {
+            v.as_deref()
+                .map(|v| v.trim().to_lowercase())
+                .filter(|v| !v.is_empty())
+        };
+
+        Ok(EmployeeFilter {
+            statuses,
+            due_from,
+            due_to,
+            training: lower(&self.training),
+            created_by: lower(&self.created_by),
+            terms: self
+                .q
+                .as_deref()
+                .unwrap_or("")
+                .split_whitespace()
+                .map(str::to_lowercase)
+                .collect(),
+            tags: split_list(self.tags.as_deref()).map(String::from).collect(),
+            match_any_tag: self.tag_match.as_deref() == Some("any"),
+        })
+    }
+
+    fn sort_field(&self) -> Result<SortField<'_>, String> {
+        match self.sort.as_deref().unwrap_or("id") {
+            "id" => Ok(SortField::Id),
+            "name" => Ok(SortField::Name),
+            "training" => Ok(SortField::Training),
+            "duedate" => Ok(SortField::Duedate),
+            "status" => Ok(SortField::Status),
+            "created_by" => Ok(SortField::CreatedBy),
+            other => match other.strip_prefix("custom_fields.") {
+                Some(key) if !key.is_empty() => Ok(SortField::Custom(key)),
+                _ => Err("Erlaubt: id, name, training, duedate, status, created_by, custom_fields.<name>".to_string()),
+            },
+        }
+    }
+
+    /// Filtert und sortiert die Einträge. Liefert die Gesamtzahl der Treffer
+    /// und die angeforderte Seite.
+    pub fn apply<'a>(&self, employees: &'a [Employee]) -> Result<(usize, Vec<&'a Employee>), ApiError> {
+        let filter = self.filter()?;
+        let mut selected: Vec<&Employee> = employees.iter().filter(|e| filter.matches(e)).collect();
+
+        let field = self.sort_field().unwrap_or(SortField::Id);
+        selected.sort_by(|a, b| compare(a, b, field));
+        if self.order.as_deref() == Some("desc") {
+            selected.reverse();
+        }
+
+        let total = selected.len();
+        if let Some(limit) = self.limit {
+            let page = self.page.unwrap_or(1);
+            selected = selected
+                .into_iter()
+                .skip((page - 1).saturating_mul(limit))
+                .take(limit)
+                .collect();
+        }
+
+        Ok((total, selected))
+    }
+
+    pub fn stats(&self, employees: &[Employee]) -> Result<EmployeeStats, ApiError> {
+        let filter = self.filter()?;
+        let mut stats = EmployeeStats {
+            total: 0,
+            gruen: 0,
+            gelb: 0,
+            rot: 0,
+            tags: BTreeMap::new(),
+        };
+
+        for employee in employees.iter().filter(|e| filter.matches(e)) {
+            stats.total += 1;
+            match employee.status {
+                Status::Gruen => stats.gruen += 1,
+                Status::Gelb => stats.gelb += 1,
+                Status::Rot => stats.rot += 1,
+            }
+            for tag in &employee.tags {
+                *stats.tags.entry(tag.clone()).or_default() += 1;
+            }
+        }
+
+        Ok(stats)
+    }
+}
+
+impl EmployeeFilter {
+    pub fn matches(&self, e: &Employee) -> bool {
+        if !self.statuses.is_empty() && !self.statuses.contains(&e.status) {
+            return false;
+        }
+
+        if self.due_from.is_some() || self.due_to.is_some() {
+            let Ok(due) = NaiveDate::parse_from_str(&e.duedate, "%Y-%m-%d") else {
+                return false;
+            };
+            if self.due_from.is_some_and(|from| due < from) || self.due_to.is_some_and(|to| due > to) {
+                return false;
+            }
+        }
+
+        if self.training.as_ref().is_some_and(|t| e.training.to_lowercase() != *t) {
+            return false;
+        }
+        if self.created_by.as_ref().is_some_and(|c| e.created_by.to_lowercase() != *c) {
+            return false;
+        }
+
+        if !self.tags.is_empty() {
+            let has = |t: &String| e.tags.contains(t);
+            let matched = if self.match_any_tag {
+                self.tags.iter().any(has)
+            } else {
+                self.tags.iter().all(has)
+            };
+            if !matched {
+                return false;
+            }
+        }
+
+        self.terms.iter().all(|term| {
+            e.id.to_string().contains(term.as_str())
+                || e.name.to_lowercase().contains(term.as_str())
+                || e.training.to_lowercase().contains(term.as_str())
+                || e.tags.iter().any(|t| t.to_lowercase().contains(term.as_str()))
+                || e.custom_fields.values().any(|v| v.to_lowercase().contains(term.as_str()))
+        })
+    }
+}
+
+fn status_rank(status: Status) -> u8 {
+    match status {
+        Status::Gruen => 0,
+        Status::Gelb => 1,
+        Status::Rot => 2,
+    }
+}
+
+fn compare(a: &Employee, b: &Employee, field: SortField) -> Ordering {
+    let ordering = match field {
+        SortField::Id => a.id.cmp(&b.id),
+        SortField::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+        SortField::Training => a.training.to_lowercase().cmp(&b.training.to_lowercase()),
+        // ISO-Daten lassen sich lexikographisch vergleichen
+        SortField::Duedate => a.duedate.cmp(&b.duedate),
+        SortField::Status => status_rank(a.status).cmp(&status_rank(b.status)),
+        SortField::CreatedBy => a.created_by.to_lowercase().cmp(&b.created_by.to_lowercase()),
+        SortField::Custom(key) => compare_custom(a.custom_fields.get(key), b.custom_fields.get(key)),
+    };
+    // Stabile Reihenfolge bei gleichen Werten
+    ordering.then(a.id.cmp(&b.id))
+}
+
+/// Zahlen vor Text, Zahlen nach Wert, Text ohne Groß-/Kleinschreibung.
+/// Muss eine Totalordnung sein, sonst bricht `sort_by` ab.
+fn compare_custom(a: Option<&String>, b: Option<&String>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => match (a.trim().parse::<f64>(), b.trim().parse::<f64>()) {
+            (Ok(x), Ok(y)) => x.total_cmp(&y),
+            (Ok(_), Err(_)) => Ordering::Less,
+            (Err(_), Ok(_)) => Ordering::Greater,
+            (Err(_), Err(_)) => a.to_lowercase().cmp(&b.to_lowercase()),
+        },
+        // Leere Werte bei aufsteigender Sortierung ans Ende
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn employee(id: u32, value: Option<&str>) -> Employee {
+        Employee {
+            id,
+            name: format!("Mitarbeiter {}", id),
+            training: "Erste Hilfe".to_string(),
+            duedate: "2026-06-30".to_string(),
+            status: Status::Gruen,
+            created_by: "admin".to_string(),
+            custom_fields: value.map(|v| ("Raum".to_string(), v.to_string())).into_iter().collect(),
+            tags: Vec::new(),
+            version: 1,
+        }
+    }
+
+    fn sorted_ids(employees: &[Employee], order: Option<&str>) -> Vec<u32> {
+        let query = EmployeeQuery {
+            sort: Some("custom_fields.Raum".to_string()),
+            order: order.map(String::from),
+            ..Default::default()
+        };
+        let (_, selected) = query.apply(employees).unwrap();
+        selected.iter().map(|e| e.id).collect()
+    }
+
+    #[test]
+    fn custom_sort_puts_numbers_before_text_and_empty_last() {
+        let employees = [
+            employee(1, Some("b")),
+            employee(2, Some("10")),
+            employee(3, None),
+            employee(4, Some("A")),
+            employee(5, Some("9")),
+            employee(6, Some("-1.5")),
+        ];
+        assert_eq!(sorted_ids(&employees, None), vec![6, 5, 2, 4, 1, 3]);
+        assert_eq!(sorted_ids(&employees, Some("desc")), vec![3, 1, 4, 2, 5, 6]);
+    }
+
+    #[test]
+    fn compare_custom_is_total_for_mixed_values() {
+        let values: Vec<Option<String>> = ["NaN", "inf", "-inf", "3", "x", "X", "", " 2 "]
+            .into_iter()
+            .map(|v| Some(v.to_string()))
+            .chain([None])
+            .collect();
+        for a in &values {
+            assert_eq!(compare_custom(a.as_ref(), a.as_ref()), Ordering::Equal);
+            for b in &values {
+                let ab = compare_custom(a.as_ref(), b.as_ref());
+                assert_eq!(ab, compare_custom(b.as_ref(), a.as_ref()).reverse());
+                for c in &values {
+                    if ab != Ordering::Greater && compare_custom(b.as_ref(), c.as_ref()) != Ordering::Greater {
+                        assert_ne!(compare_custom(a.as_ref(), c.as_ref()), Ordering::Greater, "{:?} {:?} {:?}", a, b, c);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn apply_filters_and_pages() {
+        let employees: Vec<Employee> = (1..=5).map(|id| employee(id, Some(&id.to_string()))).collect();
+        let query = EmployeeQuery {
+            q: Some("mitarbeiter".to_string()),
+            order: Some("desc".to_string()),
+            page: Some(2),
+            limit: Some(2),
+            ..Default::default()
+        };
+        let (total, selected) = query.apply(&employees).unwrap();
+        assert_eq!(total, 5);
+        assert_eq!(selected.iter().map(|e| e.id).collect::<Vec<_>>(), vec![3, 2]);
+    }
+
+    #[test]
+    fn apply_rejects_invalid_parameters() {
+        let query = EmployeeQuery {
+            sort: Some("custom_fields.".to_string()),
+            limit: Some(MAX_LIMIT + 1),
+            ..Default::default()
+        };
+        match query.apply(&[]) {
+            Err(ApiError::Validation(errors)) => {
+                assert!(errors.contains_key("sort"));
+                assert!(errors.contains_key("limit"));
+            }
+            _ => panic!("Validierungsfehler erwartet"),
+        }
+    }
+}
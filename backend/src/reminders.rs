@@ -0,0 +1,466 @@
+use chrono::{Local, NaiveDate, Utc};
+use lettre::message::{header::ContentType, Mailbox};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::transport::smtp::client::{Tls, TlsParameters};
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+use crate::error::ApiError;
+use crate::{lock, AppState, CustomFieldDef, Employee};
+
+pub const SETTINGS_FILE: &str = "reminders.json";
+pub const SENT_FILE: &str = "reminders_sent.json";
+// Protokolleinträge älter als das entfernt der Aufräum-Job
+pub const SENT_RETENTION_DAYS: i64 = 400;
+const MAX_LEAD_DAYS: u32 = 365;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SmtpSettings {
+    pub host: String,
+    pub port: u16,
+    // "none", "starttls" oder "tls"
+    pub security: String,
+    pub username: String,
+    pub password: String,
+    pub from: String,
+}
+
+impl Default for SmtpSettings {
+    fn default() -> Self {
+        SmtpSettings {
+            host: "localhost".to_string(),
+            port: 25,
+            security: "none".to_string(),
+            username: String::new(),
+            password: String::new(),
+            from: "Training Dashboard <noreply@localhost>".to_string(),
+        }
+    }
+}
+
+impl SmtpSettings {
+    /// Fehler je Feld; der Host ist nur Pflicht, wenn auch verschickt wird.
+    pub fn validate(&self, require_host: bool) -> BTreeMap<String, String> {
+        let mut errors = BTreeMap::new();
+        if require_host && self.host.trim().is_empty() {
+            errors.insert("host".to_string(), "Host fehlt".to_string());
+        }
+        if !["none", "starttls", "tls"].contains(&self.security.as_str()) {
+            errors.insert("security".to_string(), "Erlaubt: none, starttls, tls".to_string());
+        }
+        if self.from.parse::<Mailbox>().is_err() {
+            errors.insert("from".to_string(), "Keine gültige Absenderadresse".to_string());
+        }
+        errors
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Template {
+    pub subject: String,
+    pub body: String,
+}
+
+/// Einstellungen der E-Mail-Erinnerungen (`reminders.json`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReminderSettings {
+    pub enabled: bool,
+    pub smtp: SmtpSettings,
+    // Tage vor Fälligkeit, z.B. [60, 30, 7]
+    pub lead_days: Vec<u32>,
+    pub overdue: bool,
+    // Standardsprache "de" oder "en"
+    pub language: String,
+    // Zusatzfelder mit der Adresse der Person bzw. der Führungskraft
+    pub person_email_field: String,
+    pub manager_email_field: String,
+    // Optionales Zusatzfeld mit "de"/"en" je Eintrag
+    pub language_field: String,
+    // Überschreibt die eingebauten Vorlagen, Schlüssel z.B. "de.upcoming"
+    pub templates: BTreeMap<String, Template>,
+}
+
+impl Default for ReminderSettings {
+    fn default() -> Self {
+        ReminderSettings {
+            enabled: false,
+            smtp: SmtpSettings::default(),
+            lead_days: vec![60, 30, 7],
+            overdue: true,
+            language: "de".to_string(),
+            person_email_field: "email".to_string(),
+            manager_email_field: "manager_email".to_string(),
+            language_field: String::new(),
+            templates: BTreeMap::new(),
+        }
+    }
+}
+
+/// Antwort von `GET /api/reminders/settings` – ohne Passwort.
+#[derive(Serialize)]
+pub struct SettingsView {
+    #[serde(flatten)]
+    pub settings: ReminderSettings,
+    pub password_set: bool,
+}
+
+impl From<&ReminderSettings> for SettingsView {
+    fn from(settings: &ReminderSettings) -> Self {
+        let mut settings = settings.clone();
+        let password_set = !settings.smtp.password.is_empty();
+        settings.smtp.password.clear();
+        SettingsView { settings, password_set }
+    }
+}
+
+/// Versandprotokoll; `key` verhindert doppelte Erinnerungen.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SentReminder {
+    pub key: String,
+    pub employee_id: u32,
+    pub stage: String,
+    pub to: Vec<String>,
+    pub sent_at: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReminderStatus {
+    // Nur bei dry_run: würde verschickt
+    Pending,
+    Sent,
+    Skipped,
+    Failed,
+}
+
+#[derive(Serialize)]
+pub struct ReminderItem {
+    pub employee_id: u32,
+    pub name: String,
+    pub training: String,
+    pub duedate: String,
+    pub stage: String,
+    pub to: Vec<String>,
+    pub status: ReminderStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Default)]
+pub struct RunReport {
+    pub dry_run: bool,
+    pub sent: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    pub already_sent: usize,
+    pub items: Vec<ReminderItem>,
+}
+
+#[derive(Deserialize)]
+pub struct RunParams {
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Deserialize)]
+pub struct TestRequest {
+    pub to: String,
+}
+
+impl ReminderSettings {
+    pub fn validate(&self, defs: &[CustomFieldDef]) -> Result<(), ApiError> {
+        let mut errors: BTreeMap<String, String> = self
+            .smtp
+            .validate(self.enabled)
+            .into_iter()
+            .map(|(field, message)| (format!("smtp.{}", field), message))
+            .collect();
+        if self.lead_days.iter().any(|d| *d == 0 || *d > MAX_LEAD_DAYS) {
+            errors.insert(
+                "lead_days".to_string(),
+                format!("Tage zwischen 1 und {} erwartet", MAX_LEAD_DAYS),
+            );
+        }
+        if !["de", "en"].contains(&self.language.as_str()) {
+            errors.insert("language".to_string(), "Erlaubt: de, en".to_string());
+        }
+        for (key, field) in [
+            ("person_email_field", &self.person_email_field),
+            ("manager_email_field", &self.manager_email_field),
+            ("language_field", &self.language_field),
+        ] {
+            if !field.is_empty() && !defs.iter().any(|d| &d.name == field) {
+                errors.insert(key.to_string(), format!("Unbekanntes Zusatzfeld: {}", field));
+            }
+        }
+        for key in self.templates.keys() {
+            if default_template(key).is_none() {
+                errors.insert(
+                    format!("templates.{}", key),
+                    "Erlaubt: de.upcoming, de.overdue, en.upcoming, en.overdue".to_string(),
+                );
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ApiError::Validation(errors))
+        }
+    }
+
+    fn template(&self, language: &str, kind: &str) -> Template {
+        let key = format!("{}.{}", language, kind);
+        self.templates
+            .get(&key)
+            .cloned()
+            .or_else(|| default_template(&key))
+            .unwrap_or_else(|| Template { subject: String::new(), body: String::new() })
+    }
+}
+
+// Platzhalter: {name}, {training}, {duedate}, {days}, {status}
+fn default_template(key: &str) -> Option<Template> {
+    let (subject, body) = match key {
+        "de.upcoming" => (
+            "Schulung „{training}“ fällig am {duedate}",
+            "Hallo {name},\n\ndie Schulung „{training}“ ist am {duedate} fällig (in {days} Tagen).\nBitte rechtzeitig einen Termin vereinbaren.\n\nViele Grüße\nTraining Dashboard",
+        ),
+        "de.overdue" => (
+            "Schulung „{training}“ ist überfällig",
+            "Hallo {name},\n\ndie Schulung „{training}“ war am {duedate} fällig und ist seit {days} Tagen überfällig.\nBitte so bald wie möglich nachholen.\n\nViele Grüße\nTraining Dashboard",
+        ),
+        "en.upcoming" => (
+            "Training \"{training}\" due on {duedate}",
+            "Hello {name},\n\nthe training \"{training}\" is due on {duedate} (in {days} days).\nPlease schedule it in time.\n\nBest regards\nTraining Dashboard",
+        ),
+        "en.overdue" => (
+            "Training \"{training}\" is overdue",
+            "Hello {name},\n\nthe training \"{training}\" was due on {duedate} and is {days} days overdue.\nPlease complete it as soon as possible.\n\nBest regards\nTraining Dashboard",
+        ),
+        _ => return None,
+    };
+    Some(Template { subject: subject.to_string(), body: body.to_string() })
+}
+
+/// Aktuelle Stufe eines Eintrags: die kleinste Vorlaufzeit, die schon
+/// erreicht ist, oder "overdue". Frühere Stufen werden nicht nachgeholt.
+pub fn stage(settings: &ReminderSettings, days_left: i64) -> Option<String> {
+    if days_left < 0 {
+        return settings.overdue.then(|| "overdue".to_string());
+    }
+    settings
+        .lead_days
+        .iter()
+        .filter(|d| days_left <= i64::from(**d))
+        .min()
+        .map(|d| format!("{}d", d))
+}
+
+fn reminder_key(e: &Employee, stage: &str) -> String {
+    // Mit Fälligkeit, damit ein verschobener Termin neu erinnert wird
+    format!("{}:{}:{}", e.id, e.duedate, stage)
+}
+
+pub fn render(template: &str, e: &Employee, language: &str, due: NaiveDate, days: i64) -> String {
+    let duedate = if language == "de" {
+        due.format("%d.%m.%Y").to_string()
+    } else {
+        due.format("%Y-%m-%d").to_string()
+    };
+    let status = if language == "de" { e.status.label() } else { e.status.as_str() };
+    template
+        .replace("{name}", &e.name)
+        .replace("{training}", &e.training)
+        .replace("{duedate}", &duedate)
+        .replace("{days}", &days.abs().to_string())
+        .replace("{status}", status)
+}
+
+pub fn transport(smtp: &SmtpSettings) -> Result<AsyncSmtpTransport<Tokio1Executor>, ApiError> {
+    let tls_error = |e: lettre::transport::smtp::Error| ApiError::bad_gateway("smtp_failed", format!("TLS: {}", e));
+    let tls = match smtp.security.as_str() {
+        "starttls" => Tls::Required(TlsParameters::new(smtp.host.clone()).map_err(tls_error)?),
+        "tls" => Tls::Wrapper(TlsParameters::new(smtp.host.clone()).map_err(tls_error)?),
+        _ => Tls::None,
+    };
+    let mut builder = AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(smtp.host.clone())
+        .port(smtp.port)
+        .tls(tls)
+        .timeout(Some(std::time::Duration::from_secs(30)));
+    if !smtp.username.is_empty() {
+        builder = builder.credentials(Credentials::new(smtp.username.clone(), smtp.password.clone()));
+    }
+    Ok(builder.build())
+}
+
+struct Planned {
+    item: ReminderItem,
+    key: String,
+    message: Option<Message>,
+}
+
+fn plan(
+    settings: &ReminderSettings,
+    e: &Employee,
+    today: NaiveDate,
+    sent: &HashSet<String>,
+    already_sent: &mut usize,
+) -> Option<Planned> {
+    let due = NaiveDate::parse_from_str(&e.duedate, "%Y-%m-%d").ok()?;
+    let days_left = (due - today).num_days();
+    let stage = stage(settings, days_left)?;
+    let key = reminder_key(e, &stage);
+    if sent.contains(&key) {
+        *already_sent += 1;
+        return None;
+    }
+
+    let field = |name: &str| {
+        (!name.is_empty())
+            .then(|| e.custom_fields.get(name))
+            .flatten()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+    let person = field(&settings.person_email_field);
+    let manager = field(&settings.manager_email_field);
+    let language = field(&settings.language_field)
+        .map(|l| l.to_lowercase())
+        .filter(|l| l == "de" || l == "en")
+        .unwrap_or_else(|| settings.language.clone());
+
+    let mut item = ReminderItem {
+        employee_id: e.id,
+        name: e.name.clone(),
+        training: e.training.clone(),
+        duedate: e.duedate.clone(),
+        stage: stage.clone(),
+        to: person.iter().chain(manager.iter()).cloned().collect(),
+        status: ReminderStatus::Pending,
+        error: None,
+    };
+
+    let message = (|| {
+        // Person als Empfänger, Führungskraft in Kopie – fehlt die Person,
+        // geht die Erinnerung direkt an die Führungskraft
+        let (to, cc) = match (&person, &manager) {
+            (Some(p), m) => (p, m.as_ref()),
+            (None, Some(m)) => (m, None),
+            (None, None) => return Err("Keine E-Mail-Adresse hinterlegt".to_string()),
+        };
+        let kind = if stage == "overdue" { "overdue" } else { "upcoming" };
+        let template = settings.template(&language, kind);
+        let invalid = |a: &str| format!("Ungültige Adresse: {}", a);
+
+        let mut builder = Message::builder()
+            .from(settings.smtp.from.parse().map_err(|_| invalid(&settings.smtp.from))?)
+            .to(to.parse().map_err(|_| invalid(to))?)
+            .subject(render(&template.subject, e, &language, due, days_left))
+            .header(ContentType::TEXT_PLAIN);
+        if let Some(cc) = cc {
+            builder = builder.cc(cc.parse().map_err(|_| invalid(cc))?);
+        }
+        builder
+            .body(render(&template.body, e, &language, due, days_left))
+            .map_err(|err| err.to_string())
+    })();
+
+    let message = match message {
+        Ok(message) => Some(message),
+        Err(error) => {
+            item.status = ReminderStatus::Skipped;
+            item.error = Some(error);
+            None
+        }
+    };
+    Some(Planned { item, key, message })
+}
+
+/// Prüft alle Einträge und verschickt fällige Erinnerungen. Mit `dry_run`
+/// wird nur gemeldet, was verschickt würde.
+pub async fn run(data: &AppState, dry_run: bool) -> Result<RunReport, ApiError> {
+    let Ok(_running) = data.reminder_run.try_lock() else {
+        return Err(ApiError::conflict("reminders_running", "Erinnerungen werden gerade verschickt"));
+    };
+
+    let settings = data.mail_settings();
+    // Abgeschaltete Benachrichtigungen: nur melden, was verschickt würde
+    let dry_run = dry_run || !data.config.notifications.enabled;
+    let sent: HashSet<String> = lock(&data.reminders_sent).iter().map(|s| s.key.clone()).collect();
+    let employees = lock(&data.employees).clone();
+    let today = Local::now().date_naive();
+
+    let mut report = RunReport { dry_run, ..RunReport::default() };
+    let planned: Vec<Planned> = employees
+        .iter()
+        .filter_map(|e| plan(&settings, e, today, &sent, &mut report.already_sent))
+        .collect();
+
+    let mailer = if dry_run || planned.iter().all(|p| p.message.is_none()) {
+        None
+    } else {
+        Some(transport(&settings.smtp)?)
+    };
+
+    let mut records = Vec::new();
+    for Planned { mut item, key, message } in planned {
+        if let (Some(mailer), Some(message)) = (&mailer, message) {
+            match mailer.send(message).await {
+                Ok(_) => {
+                    item.status = ReminderStatus::Sent;
+                    records.push(SentReminder {
+                        key,
+                        employee_id: item.employee_id,
+                        stage: item.stage.clone(),
+                        to: item.to.clone(),
+                        sent_at: Utc::now().to_rfc3339(),
+                    });
+                }
+                Err(err) => {
+                    item.status = ReminderStatus::Failed;
+                    item.error = Some(err.to_string());
+                }
+            }
+        }
+        match item.status {
+            ReminderStatus::Sent => report.sent += 1,
+            ReminderStatus::Skipped => report.skipped += 1,
+            ReminderStatus::Failed => report.failed += 1,
+            ReminderStatus::Pending => {}
+        }
+        report.items.push(item);
+    }
+
+    if !records.is_empty() {
+        lock(&data.reminders_sent).extend(records);
+        data.save_reminders_sent()?;
+    }
+    Ok(report)
+}
+
+/// Schickt eine Testnachricht mit den aktuellen SMTP-Einstellungen.
+pub async fn send_test(data: &AppState, to: &str) -> Result<(), ApiError> {
+    data.require_notifications()?;
+    let smtp = data.mail_settings().smtp;
+    let invalid = |field: &str| {
+        let mut errors = BTreeMap::new();
+        errors.insert(field.to_string(), "Keine gültige Adresse".to_string());
+        ApiError::Validation(errors)
+    };
+    let message = Message::builder()
+        .from(smtp.from.parse().map_err(|_| invalid("smtp.from"))?)
+        .to(to.trim().parse().map_err(|_| invalid("to"))?)
+        .subject("Training Dashboard – Testnachricht")
+        .header(ContentType::TEXT_PLAIN)
+        .body("Die SMTP-Einstellungen funktionieren.".to_string())
+        .map_err(|e| ApiError::Internal(format!("E-Mail-Fehler: {}", e)))?;
+
+    transport(&smtp)?
+        .send(message)
+        .await
+        .map_err(|e| ApiError::bad_gateway("smtp_failed", format!("Versand fehlgeschlagen: {}", e)))?;
+    Ok(())
+}
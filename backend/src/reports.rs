@@ -0,0 +1,416 @@
+use chrono::{Local, NaiveDate};
+use printpdf::path::PaintMode;
+use printpdf::{
+    BuiltinFont, Color, IndirectFontRef, Line, Mm, PdfDocument, PdfDocumentReference, PdfLayerReference,
+    Point, Rect, Rgb,
+};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+use crate::error::ApiError;
+use crate::query::{EmployeeQuery, EmployeeStats};
+use crate::{is_expired, same_person, CustomFieldDef, Employee, Status};
+
+const A4_SHORT: f32 = 210.0;
+const A4_LONG: f32 = 297.0;
+const MARGIN: f32 = 20.0;
+// Durchschnittliche Zeichenbreite von Helvetica relativ zur Schriftgröße
+const CHAR_WIDTH: f32 = 0.5;
+const PT_TO_MM: f32 = 0.3528;
+
+#[derive(Deserialize)]
+pub struct ComplianceParams {
+    // training, tags oder custom_fields.<name> (z.B. die Abteilung)
+    pub group_by: Option<String>,
+}
+
+/// Einfacher Seitenfluss über printpdf: Text von oben nach unten,
+/// Seitenumbruch mit Fußzeile, sobald der Rand erreicht ist.
+struct Pdf {
+    doc: PdfDocumentReference,
+    layer: PdfLayerReference,
+    regular: IndirectFontRef,
+    bold: IndirectFontRef,
+    width: f32,
+    height: f32,
+    y: f32,
+    footer: String,
+}
+
+fn rgb(r: f32, g: f32, b: f32) -> Color {
+    Color::Rgb(Rgb::new(r, g, b, None))
+}
+
+fn status_color(status: Status) -> Color {
+    match status {
+        Status::Gruen => rgb(0.2, 0.65, 0.3),
+        Status::Gelb => rgb(0.95, 0.7, 0.1),
+        Status::Rot => rgb(0.85, 0.2, 0.2),
+    }
+}
+
+/// Kürzt Text für feste Spaltenbreiten.
+fn fit(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        text.to_string()
+    } else {
+        let mut short: String = text.chars().take(max_chars.saturating_sub(1)).collect();
+        short.push('…');
+        short
+    }
+}
+
+fn format_date(iso: &str) -> String {
+    NaiveDate::parse_from_str(iso, "%Y-%m-%d")
+        .map(|d| d.format("%d.%m.%Y").to_string())
+        .unwrap_or_else(|_| iso.to_string())
+}
+
+impl Pdf {
+    fn new(title: &str, landscape: bool) -> Result<Self, ApiError> {
+        let (width, height) = if landscape { (A4_LONG, A4_SHORT) } else { (A4_SHORT, A4_LONG) };
+        let (doc, page, layer) = PdfDocument::new(title, Mm(width), Mm(height), "Inhalt");
+        let regular = doc.add_builtin_font(BuiltinFont::Helvetica)?;
+        let bold = doc.add_builtin_font(BuiltinFont::HelveticaBold)?;
+        let layer = doc.get_page(page).get_layer(layer);
+
+        let pdf = Pdf {
+            doc,
+            layer,
+            regular,
+            bold,
+            width,
+            height,
+            y: height - MARGIN,
+            footer: format!("Training Dashboard · erstellt am {}", Local::now().format("%d.%m.%Y %H:%M")),
+        };
+        pdf.draw_footer();
+        Ok(pdf)
+    }
+
+    fn draw_footer(&self) {
+        self.text(&self.footer, 8.0, MARGIN, 10.0, false);
+    }
+
+    fn text(&self, text: &str, size: f32, x: f32, y: f32, bold: bool) {
+        let font = if bold { &self.bold } else { &self.regular };
+        self.layer.use_text(text, size, Mm(x), Mm(y), font);
+    }
+
+    fn text_width(text: &str, size: f32) -> f32 {
+        text.chars().count() as f32 * size * CHAR_WIDTH * PT_TO_MM
+    }
+
+    fn centered(&mut self, text: &str, size: f32, bold: bool) {
+        let x = ((self.width - Self::text_width(text, size)) / 2.0).max(MARGIN);
+        self.text(text, size, x, self.y, bold);
+        self.advance(size * PT_TO_MM * 1.6);
+    }
+
+    /// Geht um `step` mm nach unten und beginnt bei Bedarf eine neue Seite.
+    fn advance(&mut self, step: f32) {
+        self.y -= step;
+        if self.y < MARGIN {
+            let (page, layer) = self.doc.add_page(Mm(self.width), Mm(self.height), "Inhalt");
+            self.layer = self.doc.get_page(page).get_layer(layer);
+            self.y = self.height - MARGIN;
+            self.draw_footer();
+        }
+    }
+
+    fn heading(&mut self, text: &str) {
+        self.text(text, 18.0, MARGIN, self.y, true);
+        self.advance(10.0);
+    }
+
+    fn subheading(&mut self, text: &str) {
+        self.advance(3.0);
+        self.text(text, 12.0, MARGIN, self.y, true);
+        self.advance(7.0);
+    }
+
+    fn line(&mut self, text: &str) {
+        self.text(text, 10.0, MARGIN, self.y, false);
+        self.advance(5.5);
+    }
+
+    fn row(&mut self, cells: &[(f32, String)], bold: bool) {
+        for (x, value) in cells {
+            self.text(value, 10.0, *x, self.y, bold);
+        }
+        self.advance(5.5);
+    }
+
+    fn rule(&mut self) {
+        self.layer.set_outline_color(rgb(0.6, 0.6, 0.6));
+        self.layer.set_outline_thickness(0.5);
+        self.layer.add_line(Line {
+            points: vec![
+                (Point::new(Mm(MARGIN), Mm(self.y + 3.5)), false),
+                (Point::new(Mm(self.width - MARGIN), Mm(self.y + 3.5)), false),
+            ],
+            is_closed: false,
+        });
+        self.advance(1.5);
+    }
+
+    fn status_dot(&self, x: f32, status: Status) {
+        self.layer.set_fill_color(status_color(status));
+        self.layer.add_rect(
+            Rect::new(Mm(x), Mm(self.y - 0.5), Mm(x + 3.0), Mm(self.y + 2.5)).with_mode(PaintMode::Fill),
+        );
+        self.layer.set_fill_color(rgb(0.0, 0.0, 0.0));
+    }
+
+    /// Gestapelter Balken mit den Anteilen grün/gelb/rot.
+    fn status_bar(&mut self, stats: &EmployeeStats) {
+        if stats.total == 0 {
+            return;
+        }
+        let width = self.width - 2.0 * MARGIN;
+        let mut x = MARGIN;
+        for (status, count) in [(Status::Gruen, stats.gruen), (Status::Gelb, stats.gelb), (Status::Rot, stats.rot)] {
+            let w = width * count as f32 / stats.total as f32;
+            if w > 0.0 {
+                self.layer.set_fill_color(status_color(status));
+                self.layer.add_rect(Rect::new(Mm(x), Mm(self.y), Mm(x + w), Mm(self.y + 6.0)).with_mode(PaintMode::Fill));
+            }
+            x += w;
+        }
+        self.layer.set_fill_color(rgb(0.0, 0.0, 0.0));
+        self.advance(10.0);
+    }
+
+    fn finish(self) -> Result<Vec<u8>, ApiError> {
+        Ok(self.doc.save_to_bytes()?)
+    }
+}
+
+fn quota(green: usize, total: usize) -> String {
+    if total == 0 {
+        "–".to_string()
+    } else {
+        format!("{:.0} %", green as f32 * 100.0 / total as f32)
+    }
+}
+
+/// (a) Schulungsnachweis einer Person mit allen Einträgen.
+pub fn person_record(person: &str, employees: &[Employee], defs: &[CustomFieldDef]) -> Result<Vec<u8>, ApiError> {
+    let mut records: Vec<&Employee> = employees.iter().filter(|e| same_person(&e.name, person)).collect();
+    if records.is_empty() {
+        return Err(ApiError::NotFound("Person"));
+    }
+    records.sort_by(|a, b| a.duedate.cmp(&b.duedate));
+
+    let mut pdf = Pdf::new(&format!("Schulungsnachweis {}", person), false)?;
+    pdf.heading("Schulungsnachweis");
+    pdf.line(&format!("Person: {}", records[0].name));
+    pdf.line(&format!("Stand: {}", Local::now().format("%d.%m.%Y")));
+    pdf.line(&format!("Einträge: {}", records.len()));
+
+    pdf.subheading("Schulungen");
+    let columns = [MARGIN, 95.0, 125.0, 155.0];
+    pdf.row(
+        &[
+            (columns[0], "Training".to_string()),
+            (columns[1], "Fällig".to_string()),
+            (columns[2], "Status".to_string()),
+            (columns[3], "Tags".to_string()),
+        ],
+        true,
+    );
+    pdf.rule();
+
+    for e in records {
+        pdf.status_dot(columns[2], e.status);
+        pdf.row(
+            &[
+                (columns[0], fit(&e.training, 40)),
+                (columns[1], format_date(&e.duedate)),
+                (columns[2] + 5.0, e.status.label().to_string()),
+                (columns[3], fit(&e.tags.join(", "), 20)),
+            ],
+            false,
+        );
+        let extras: Vec<String> = defs
+            .iter()
+            .filter_map(|d| e.custom_fields.get(&d.name).map(|v| format!("{}: {}", d.label, v)))
+            .collect();
+        if !extras.is_empty() {
+            pdf.text(&fit(&extras.join(" · "), 110), 8.0, columns[0] + 4.0, pdf.y + 1.0, false);
+            pdf.advance(4.0);
+        }
+    }
+
+    pdf.finish()
+}
+
+fn group_keys(e: &Employee, group_by: &str) -> Vec<String> {
+    match group_by {
+        "training" => vec![e.training.clone()],
+        "tags" if e.tags.is_empty() => vec!["(ohne Tag)".to_string()],
+        "tags" => e.tags.clone(),
+        other => {
+            let name = other.strip_prefix("custom_fields.").unwrap_or(other);
+            vec![e
+                .custom_fields
+                .get(name)
+                .cloned()
+                .unwrap_or_else(|| "(ohne Angabe)".to_string())]
+        }
+    }
+}
+
+/// (b) Compliance-Übersicht: Ampelzahlen der Auswahl, optional je Gruppe
+/// (z.B. Abteilung als Zusatzfeld).
+pub fn compliance_summary(
+    query: &EmployeeQuery,
+    params: &ComplianceParams,
+    employees: &[Employee],
+    defs: &[CustomFieldDef],
+) -> Result<Vec<u8>, ApiError> {
+    let group_by = params.group_by.as_deref().map(str::trim).filter(|g| !g.is_empty());
+    let group_label = match group_by {
+        None => None,
+        Some("training") => Some("Training".to_string()),
+        Some("tags") => Some("Tag".to_string()),
+        Some(other) => match other.strip_prefix("custom_fields.").and_then(|n| defs.iter().find(|d| d.name == n)) {
+            Some(def) => Some(def.label.clone()),
+            None => {
+                let mut errors = BTreeMap::new();
+                errors.insert(
+                    "group_by".to_string(),
+                    "Erlaubt: training, tags, custom_fields.<name>".to_string(),
+                );
+                return Err(ApiError::Validation(errors));
+            }
+        },
+    };
+
+    let stats = query.stats(employees)?;
+    let (_, selected) = query.apply(employees)?;
+
+    let mut pdf = Pdf::new("Compliance-Bericht", false)?;
+    pdf.heading("Compliance-Bericht");
+    pdf.line(&format!("Stand: {}", Local::now().format("%d.%m.%Y")));
+    if let Some(filter) = describe_filter(query) {
+        pdf.line(&fit(&format!("Auswahl: {}", filter), 95));
+    }
+
+    pdf.subheading("Gesamt");
+    pdf.status_bar(&stats);
+    let columns = [MARGIN, 85.0, 105.0, 125.0, 150.0, 175.0];
+    let header = |first: &str| {
+        [
+            (columns[0], first.to_string()),
+            (columns[1], "Gesamt".to_string()),
+            (columns[2], "Aktuell".to_string()),
+            (columns[3], "Bald fällig".to_string()),
+            (columns[4], "Überfällig".to_string()),
+            (columns[5], "Quote".to_string()),
+        ]
+    };
+    let counts = |label: String, s: &EmployeeStats| {
+        [
+            (columns[0], label),
+            (columns[1], s.total.to_string()),
+            (columns[2], s.gruen.to_string()),
+            (columns[3], s.gelb.to_string()),
+            (columns[4], s.rot.to_string()),
+            (columns[5], quota(s.gruen, s.total)),
+        ]
+    };
+    pdf.row(&header(""), true);
+    pdf.rule();
+    pdf.row(&counts("Alle Einträge".to_string(), &stats), false);
+
+    if let (Some(group_by), Some(label)) = (group_by, group_label) {
+        let mut groups: BTreeMap<String, EmployeeStats> = BTreeMap::new();
+        for e in &selected {
+            for key in group_keys(e, group_by) {
+                let g = groups.entry(key).or_default();
+                g.total += 1;
+                match e.status {
+                    Status::Gruen => g.gruen += 1,
+                    Status::Gelb => g.gelb += 1,
+                    Status::Rot => g.rot += 1,
+                }
+            }
+        }
+
+        pdf.subheading(&format!("Nach {}", label));
+        pdf.row(&header(&label), true);
+        pdf.rule();
+        for (key, s) in &groups {
+            pdf.row(&counts(fit(key, 30), s), false);
+        }
+    }
+
+    pdf.finish()
+}
+
+fn describe_filter(query: &EmployeeQuery) -> Option<String> {
+    let parts: Vec<String> = [
+        ("Status", &query.status),
+        ("Tags", &query.tags),
+        ("Training", &query.training),
+        ("Suche", &query.q),
+        ("fällig ab", &query.due_from),
+        ("fällig bis", &query.due_to),
+        ("Ersteller", &query.created_by),
+    ]
+    .iter()
+    .filter_map(|(label, value)| value.as_deref().filter(|v| !v.is_empty()).map(|v| format!("{} {}", label, v)))
+    .collect();
+    (!parts.is_empty()).then(|| parts.join(", "))
+}
+
+/// (c) Teilnahmebescheinigung für einen gültigen Eintrag.
+pub fn certificate(e: &Employee) -> Result<Vec<u8>, ApiError> {
+    if e.status == Status::Rot || is_expired(e, Local::now().date_naive()) {
+        return Err(ApiError::conflict(
+            "not_completed",
+            "Für überfällige Schulungen wird keine Bescheinigung ausgestellt",
+        ));
+    }
+
+    let mut pdf = Pdf::new(&format!("Teilnahmebescheinigung {}", e.name), true)?;
+
+    // Rahmen
+    pdf.layer.set_outline_color(rgb(0.2, 0.35, 0.6));
+    pdf.layer.set_outline_thickness(2.0);
+    pdf.layer.add_rect(
+        Rect::new(Mm(12.0), Mm(15.0), Mm(pdf.width - 12.0), Mm(pdf.height - 12.0)).with_mode(PaintMode::Stroke),
+    );
+
+    pdf.y = pdf.height - 45.0;
+    pdf.centered("Teilnahmebescheinigung", 32.0, true);
+    pdf.advance(8.0);
+    pdf.centered("Hiermit wird bestätigt, dass", 14.0, false);
+    pdf.advance(2.0);
+    pdf.centered(&e.name, 24.0, true);
+    pdf.advance(2.0);
+    pdf.centered("die Schulung", 14.0, false);
+    pdf.advance(2.0);
+    pdf.centered(&e.training, 20.0, true);
+    pdf.advance(2.0);
+    pdf.centered("erfolgreich absolviert hat.", 14.0, false);
+    pdf.advance(6.0);
+    pdf.centered(&format!("Gültig bis: {}", format_date(&e.duedate)), 12.0, false);
+
+    let bottom = 35.0;
+    pdf.text(&format!("Ausgestellt am {}", Local::now().format("%d.%m.%Y")), 10.0, 30.0, bottom, false);
+    pdf.text(&format!("Nachweis-Nr. {}-{}", e.id, e.version), 10.0, 30.0, bottom - 6.0, false);
+    pdf.layer.set_outline_thickness(0.5);
+    pdf.layer.set_outline_color(rgb(0.0, 0.0, 0.0));
+    pdf.layer.add_line(Line {
+        points: vec![
+            (Point::new(Mm(pdf.width - 110.0), Mm(bottom + 2.0)), false),
+            (Point::new(Mm(pdf.width - 30.0), Mm(bottom + 2.0)), false),
+        ],
+        is_closed: false,
+    });
+    pdf.text("Unterschrift / Schulungsverantwortliche(r)", 9.0, pdf.width - 110.0, bottom - 3.0, false);
+
+    pdf.finish()
+}
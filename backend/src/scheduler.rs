@@ -0,0 +1,591 @@
+use actix_web::web;
+use chrono::{DateTime, Duration, Local, NaiveDate, Utc};
+use cron::Schedule;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::atomic::Ordering;
+
+use crate::error::ApiError;
+use crate::{calendar, chat, escalation, live, lock, maintenance, reminders, webhooks, AppState, Status};
+
+pub const CONFIG_FILE: &str = "jobs.json";
+pub const STATE_FILE: &str = "jobs_state.json";
+const BACKUP_DIR: &str = "backups";
+const HISTORY_LIMIT: usize = 200;
+const TICK_SECONDS: u64 = 30;
+
+// Dateien, die der Backup-Job sichert
+const DATA_FILES: [&str; 14] = [
+    "employees.csv",
+    "catalog.json",
+    "custom_fields.json",
+    "tags.json",
+    calendar::FEEDS_FILE,
+    reminders::SETTINGS_FILE,
+    reminders::SENT_FILE,
+    chat::SETTINGS_FILE,
+    chat::SENT_FILE,
+    escalation::SETTINGS_FILE,
+    escalation::STATE_FILE,
+    webhooks::WEBHOOKS_FILE,
+    CONFIG_FILE,
+    STATE_FILE,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Job {
+    StatusUpdate,
+    Reminders,
+    Chat,
+    Escalations,
+    SessionCleanup,
+    Backup,
+    RetentionPurge,
+}
+
+impl Job {
+    pub const ALL: [Job; 7] = [
+        Job::StatusUpdate,
+        Job::Reminders,
+        Job::Chat,
+        Job::Escalations,
+        Job::SessionCleanup,
+        Job::Backup,
+        Job::RetentionPurge,
+    ];
+
+    pub fn parse(value: &str) -> Option<Job> {
+        Job::ALL.into_iter().find(|job| job.name() == value)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Job::StatusUpdate => "status_update",
+            Job::Reminders => "reminders",
+            Job::Chat => "chat",
+            Job::Escalations => "escalations",
+            Job::SessionCleanup => "session_cleanup",
+            Job::Backup => "backup",
+            Job::RetentionPurge => "retention_purge",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Job::StatusUpdate => "Status nach Fälligkeit aktualisieren",
+            Job::Reminders => "E-Mail-Erinnerungen",
+            Job::Chat => "Chat-Benachrichtigungen",
+            Job::Escalations => "Eskalation überfälliger Schulungen",
+            Job::SessionCleanup => "Abgelaufene Sitzungen entfernen",
+            Job::Backup => "Datensicherung",
+            Job::RetentionPurge => "Alte Sicherungen und Protokolle löschen",
+        }
+    }
+
+    // Sekunde Minute Stunde Tag Monat Wochentag
+    fn default_schedule(self) -> &'static str {
+        match self {
+            Job::StatusUpdate => "0 5 0 * * *",
+            Job::Reminders => "0 0 * * * *",
+            Job::Chat => "0 0 7 * * *",
+            Job::Escalations => "0 30 * * * *",
+            Job::SessionCleanup => "0 */15 * * * *",
+            Job::Backup => "0 0 2 * * *",
+            Job::RetentionPurge => "0 30 3 * * *",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobConfig {
+    pub schedule: String,
+    pub enabled: bool,
+}
+
+/// Zeitpläne und Parameter der Hintergrundjobs (`jobs.json`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SchedulerConfig {
+    pub jobs: BTreeMap<Job, JobConfig>,
+    // Ab so vielen Tagen vor Fälligkeit wird "gruen" zu "gelb"
+    pub yellow_days: i64,
+    pub backup_keep: usize,
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        SchedulerConfig {
+            jobs: BTreeMap::new(),
+            yellow_days: 30,
+            backup_keep: 14,
+        }
+    }
+}
+
+impl SchedulerConfig {
+    pub fn job(&self, job: Job) -> JobConfig {
+        self.jobs.get(&job).cloned().unwrap_or_else(|| JobConfig {
+            schedule: job.default_schedule().to_string(),
+            enabled: true,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Trigger {
+    Schedule,
+    Manual,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobRun {
+    pub job: Job,
+    pub trigger: Trigger,
+    pub started_at: String,
+    pub finished_at: String,
+    pub ok: bool,
+    pub message: String,
+}
+
+/// Letzte Läufe und Verlauf (`jobs_state.json`), überlebt Neustarts.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SchedulerState {
+    pub last_runs: BTreeMap<Job, String>,
+    pub history: Vec<JobRun>,
+}
+
+impl SchedulerState {
+    /// Noch nie gelaufene Jobs zählen ab `now`; ohne Eintrag läge der nächste
+    /// Termin immer in der Zukunft und der Job würde nie fällig.
+    pub fn seed_last_runs(&mut self, now: DateTime<Local>) -> bool {
+        let mut seeded = false;
+        for job in Job::ALL {
+            self.last_runs.entry(job).or_insert_with(|| {
+                seeded = true;
+                now.to_rfc3339()
+            });
+        }
+        seeded
+    }
+}
+
+#[derive(Serialize)]
+pub struct JobInfo {
+    pub job: Job,
+    pub label: &'static str,
+    pub schedule: String,
+    pub enabled: bool,
+    pub running: bool,
+    pub next_run: Option<String>,
+    pub last_run: Option<JobRun>,
+}
+
+#[derive(Deserialize)]
+pub struct JobUpdate {
+    pub schedule: Option<String>,
+    pub enabled: Option<bool>,
+}
+
+#[derive(Deserialize)]
+pub struct HistoryParams {
+    pub job: Option<String>,
+    pub limit: Option<usize>,
+}
+
+/// Akzeptiert Cron-Ausdrücke mit 6 Feldern (mit Sekunde) oder klassisch mit 5.
+pub fn parse_schedule(expression: &str) -> Result<Schedule, ApiError> {
+    let expression = expression.trim();
+    let full = if expression.split_whitespace().count() == 5 {
+        format!("0 {}", expression)
+    } else {
+        expression.to_string()
+    };
+    Schedule::from_str(&full).map_err(|e| {
+        let mut errors = BTreeMap::new();
+        errors.insert("schedule".to_string(), format!("Ungültiger Cron-Ausdruck: {}", e));
+        ApiError::Validation(errors)
+    })
+}
+
+/// Nächster Termin nach dem letzten Lauf. Liegt er in der Vergangenheit
+/// (Server war aus), ist der Job sofort einmal fällig. Ohne letzten Lauf
+/// zählt `now` – `run_loop` trägt deshalb beim Start einen Zeitpunkt ein.
+fn next_run(config: &JobConfig, last_run: Option<&String>, now: DateTime<Local>) -> Option<DateTime<Local>> {
+    if !config.enabled {
+        return None;
+    }
+    let schedule = parse_schedule(&config.schedule).ok()?;
+    let base = last_run
+        .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+        .map(|t| t.with_timezone(&Local))
+        .unwrap_or(now);
+    schedule.after(&base).next()
+}
+
+pub fn list(data: &AppState) -> Vec<JobInfo> {
+    let config = lock(&data.scheduler_config).clone();
+    let state = lock(&data.scheduler_state).clone();
+    let running = lock(&data.running_jobs).clone();
+    let now = Local::now();
+
+    Job::ALL
+        .into_iter()
+        .map(|job| {
+            let job_config = config.job(job);
+            JobInfo {
+                job,
+                label: job.label(),
+                next_run: next_run(&job_config, state.last_runs.get(&job), now).map(|t| t.to_rfc3339()),
+                schedule: job_config.schedule,
+                enabled: job_config.enabled,
+                running: running.contains(&job),
+                last_run: state.history.iter().rev().find(|r| r.job == job).cloned(),
+            }
+        })
+        .collect()
+}
+
+pub fn update(data: &AppState, job: Job, update: JobUpdate) -> Result<(), ApiError> {
+    if let Some(schedule) = &update.schedule {
+        parse_schedule(schedule)?;
+    }
+    let mut config = lock(&data.scheduler_config);
+    let mut job_config = config.job(job);
+    if let Some(schedule) = update.schedule {
+        job_config.schedule = schedule.trim().to_string();
+    }
+    if let Some(enabled) = update.enabled {
+        job_config.enabled = enabled;
+    }
+    config.jobs.insert(job, job_config);
+    drop(config);
+    data.save_scheduler_config()
+}
+
+pub fn history(data: &AppState, params: &HistoryParams) -> Result<Vec<JobRun>, ApiError> {
+    let job = match params.job.as_deref() {
+        Some(name) => Some(Job::parse(name).ok_or(ApiError::NotFound("Job"))?),
+        None => None,
+    };
+    let limit = params.limit.unwrap_or(50).min(HISTORY_LIMIT);
+    Ok(lock(&data.scheduler_state)
+        .history
+        .iter()
+        .rev()
+        .filter(|r| job.is_none_or(|j| r.job == j))
+        .take(limit)
+        .cloned()
+        .collect())
+}
+
+/// Gibt den Job beim Verlassen wieder frei – auch wenn er abbricht.
+struct RunningGuard<'a> {
+    data: &'a AppState,
+    job: Job,
+}
+
+impl Drop for RunningGuard<'_> {
+    fn drop(&mut self) {
+        lock(&self.data.running_jobs).remove(&self.job);
+    }
+}
+
+/// Führt einen Job aus, sofern er nicht schon läuft, und protokolliert das
+/// Ergebnis.
+pub async fn execute(data: &AppState, job: Job, trigger: Trigger) -> Result<JobRun, ApiError> {
+    // Auch manuelle Läufe schreiben Dateien – im Wartungsmodus und beim Beenden nicht
+    maintenance::check_writable(data)?;
+    if !lock(&data.running_jobs).insert(job) {
+        return Err(ApiError::conflict("job_running", format!("{} läuft bereits", job.label())));
+    }
+    let _guard = RunningGuard { data, job };
+
+    // Jobs ändern Daten wie schreibende Anfragen; die Sicherung sperrt selbst exklusiv
+    let _writing = match job {
+        Job::Backup => None,
+        _ => Some(data.write_gate.read().await),
+    };
+
+    let started_at = Local::now().to_rfc3339();
+    let result = match job {
+        Job::StatusUpdate => update_statuses(data),
+        Job::Reminders => send_reminders(data).await,
+        Job::Chat => send_chat(data).await,
+        Job::Escalations => escalation::run(data).await,
+        Job::SessionCleanup => cleanup_sessions(data),
+        Job::Backup => backup(data).await,
+        Job::RetentionPurge => purge(data),
+    };
+
+    let (ok, message) = match result {
+        Ok(message) => (true, message),
+        Err(err) => (false, err.to_string()),
+    };
+    let run = JobRun {
+        job,
+        trigger,
+        started_at: started_at.clone(),
+        finished_at: Local::now().to_rfc3339(),
+        ok,
+        message,
+    };
+
+    let mut state = lock(&data.scheduler_state);
+    state.last_runs.insert(job, started_at);
+    state.history.push(run.clone());
+    let excess = state.history.len().saturating_sub(HISTORY_LIMIT);
+    state.history.drain(..excess);
+    drop(state);
+    data.save_scheduler_state()?;
+    Ok(run)
+}
+
+/// Hintergrundschleife: prüft regelmäßig, welche Jobs fällig sind.
+pub async fn run_loop(data: web::Data<AppState>) {
+    let seeded = lock(&data.scheduler_state).seed_last_runs(Local::now());
+    if seeded {
+        if let Err(err) = data.save_scheduler_state() {
+            tracing::warn!(error = %err, "Jobzustand nicht gespeichert");
+        }
+    }
+    loop {
+        // Im Wartungsmodus nichts schreiben; verpasste Läufe werden danach nachgeholt
+        if maintenance::check_writable(&data).is_err() {
+            actix_web::rt::time::sleep(std::time::Duration::from_secs(TICK_SECONDS)).await;
+            continue;
+        }
+        let now = Local::now();
+        let due: Vec<Job> = {
+            let config = lock(&data.scheduler_config);
+            let state = lock(&data.scheduler_state);
+            let running = lock(&data.running_jobs);
+            Job::ALL
+                .into_iter()
+                .filter(|job| !running.contains(job))
+                .filter(|job| next_run(&config.job(*job), state.last_runs.get(job), now).is_some_and(|t| t <= now))
+                .collect()
+        };
+
+        for job in due {
+            let data = data.clone();
+            actix_web::rt::spawn(async move {
+                match execute(&data, job, Trigger::Schedule).await {
+                    Ok(run) if !run.ok => tracing::warn!(job = job.name(), message = %run.message, "Job fehlgeschlagen"),
+                    Ok(_) => {}
+                    Err(err) => tracing::error!(job = job.name(), error = %err, "Job fehlgeschlagen"),
+                }
+            });
+        }
+        actix_web::rt::time::sleep(std::time::Duration::from_secs(TICK_SECONDS)).await;
+    }
+}
+
+// Jobs
+
+/// Setzt überfällige Einträge auf "rot" und bald fällige von "gruen" auf
+/// "gelb". Zurückgestuft wird nie – das bleibt eine bewusste Entscheidung.
+fn update_statuses(data: &AppState) -> Result<String, ApiError> {
+    let yellow_days = lock(&data.scheduler_config).yellow_days;
+    let today = Local::now().date_naive();
+
+    let mut employees = lock(&data.employees);
+    let before = employees.clone();
+    let mut changed = 0;
+    for e in employees.iter_mut() {
+        let Ok(due) = NaiveDate::parse_from_str(&e.duedate, "%Y-%m-%d") else {
+            continue;
+        };
+        let status = if due < today {
+            Status::Rot
+        } else if e.status == Status::Gruen && due <= today + Duration::days(yellow_days) {
+            Status::Gelb
+        } else {
+            continue;
+        };
+        if e.status != status {
+            e.status = status;
+            e.version += 1;
+            changed += 1;
+        }
+    }
+
+    let after = employees.clone();
+    drop(employees);
+
+    if changed > 0 {
+        data.save_employees()?;
+        live::employees_changed(data, &before, &after);
+        webhooks::employees_changed(data, &before, &after);
+    }
+    Ok(format!("{} Einträge aktualisiert", changed))
+}
+
+async fn send_reminders(data: &AppState) -> Result<String, ApiError> {
+    if !lock(&data.reminder_settings).enabled {
+        return Ok("Erinnerungen sind deaktiviert".to_string());
+    }
+    let report = reminders::run(data, false).await?;
+    let message = format!(
+        "{} verschickt, {} übersprungen, {} fehlgeschlagen",
+        report.sent, report.skipped, report.failed
+    );
+    if report.failed > 0 {
+        return Err(ApiError::bad_gateway("smtp_failed", message));
+    }
+    Ok(message)
+}
+
+async fn send_chat(data: &AppState) -> Result<String, ApiError> {
+    if lock(&data.chat_settings).channels.is_empty() {
+        return Ok("Keine Chat-Kanäle konfiguriert".to_string());
+    }
+    let report = chat::run(data, false).await?;
+    let sent = report.channels.iter().filter(|c| c.sent).count();
+    let failed: Vec<String> = report
+        .channels
+        .iter()
+        .filter_map(|c| c.error.as_ref().map(|e| format!("{}: {}", c.channel, e)))
+        .collect();
+    if !failed.is_empty() {
+        return Err(ApiError::bad_gateway("chat_failed", failed.join("; ")));
+    }
+    Ok(format!("{} Kanäle benachrichtigt", sent))
+}
+
+fn cleanup_sessions(data: &AppState) -> Result<String, ApiError> {
+    // Abgelaufene Sitzungen gelten schon vorher als ungültig, hier wird nur aufgeräumt
+    let now = Utc::now();
+    let mut sessions = lock(&data.sessions);
+    let before = sessions.len();
+    sessions.retain(|s| !data.config.sessions.expired(&s.created_at, &s.last_seen, now));
+    Ok(format!("{} Sitzungen entfernt", before - sessions.len()))
+}
+
+/// Während des Kopierens werden Änderungen abgelehnt, damit die Dateien
+/// der Sicherung zueinander passen.
+async fn backup(data: &AppState) -> Result<String, ApiError> {
+    data.backup_running.store(true, Ordering::SeqCst);
+    // Neue Änderungen werden ab jetzt abgelehnt, laufende noch abgewartet
+    let result = {
+        let _exclusive = data.write_gate.write().await;
+        copy_data_files()
+    };
+    data.backup_running.store(false, Ordering::SeqCst);
+    result
+}
+
+fn copy_data_files() -> Result<String, ApiError> {
+    let target = Path::new(BACKUP_DIR).join(Local::now().format("%Y%m%d-%H%M%S").to_string());
+    std::fs::create_dir_all(&target)?;
+
+    let mut copied = 0;
+    for file in DATA_FILES {
+        if Path::new(file).exists() {
+            std::fs::copy(file, target.join(file))?;
+            copied += 1;
+        }
+    }
+    Ok(format!("{} Dateien gesichert nach {}", copied, target.display()))
+}
+
+fn purge(data: &AppState) -> Result<String, ApiError> {
+    let keep = lock(&data.scheduler_config).backup_keep;
+
+    // Verzeichnisnamen sind Zeitstempel, sortieren also chronologisch
+    let mut backups: Vec<_> = match std::fs::read_dir(BACKUP_DIR) {
+        Ok(entries) => entries
+            .flatten()
+            .filter(|e| e.path().is_dir())
+            .map(|e| e.path())
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    backups.sort();
+    let remove = backups.len().saturating_sub(keep);
+    for path in &backups[..remove] {
+        std::fs::remove_dir_all(path)?;
+    }
+
+    let cutoff = (Utc::now() - Duration::days(reminders::SENT_RETENTION_DAYS)).to_rfc3339();
+    let mut sent = lock(&data.reminders_sent);
+    let before = sent.len();
+    sent.retain(|s| s.sent_at >= cutoff);
+    let mut purged = before - sent.len();
+    drop(sent);
+    if purged > 0 {
+        data.save_reminders_sent()?;
+    }
+
+    let mut chat_sent = lock(&data.chat_sent);
+    let before = chat_sent.len();
+    chat_sent.retain(|s| s.sent_at >= cutoff);
+    let chat_purged = before - chat_sent.len();
+    drop(chat_sent);
+    if chat_purged > 0 {
+        data.save_chat_sent()?;
+    }
+    purged += chat_purged;
+
+    Ok(format!("{} Sicherungen und {} Protokolleinträge gelöscht", remove, purged))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn hourly() -> JobConfig {
+        JobConfig { schedule: "0 0 * * * *".to_string(), enabled: true }
+    }
+
+    fn at(hour: u32, minute: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 3, 10, hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn next_run_without_last_run_is_after_now() {
+        let next = next_run(&hourly(), None, at(9, 30)).unwrap();
+        assert_eq!(next, at(10, 0));
+    }
+
+    #[test]
+    fn next_run_after_missed_slot_is_due() {
+        let last = at(7, 0).to_rfc3339();
+        let next = next_run(&hourly(), Some(&last), at(9, 30)).unwrap();
+        assert_eq!(next, at(8, 0));
+        assert!(next <= at(9, 30));
+    }
+
+    #[test]
+    fn next_run_disabled_or_invalid() {
+        let disabled = JobConfig { enabled: false, ..hourly() };
+        assert_eq!(next_run(&disabled, None, at(9, 30)), None);
+        let invalid = JobConfig { schedule: "jede Stunde".to_string(), enabled: true };
+        assert_eq!(next_run(&invalid, None, at(9, 30)), None);
+    }
+
+    #[test]
+    fn never_run_job_becomes_due_after_seeding() {
+        let mut state = SchedulerState::default();
+        assert!(state.seed_last_runs(at(9, 30)));
+        assert!(!state.seed_last_runs(at(9, 45)));
+        let now = at(10, 0);
+        let next = next_run(&hourly(), state.last_runs.get(&Job::Reminders), now).unwrap();
+        assert!(next <= now);
+    }
+
+    #[test]
+    fn seeding_keeps_existing_last_runs() {
+        let mut state = SchedulerState::default();
+        let last = at(7, 0).to_rfc3339();
+        state.last_runs.insert(Job::Backup, last.clone());
+        state.seed_last_runs(at(9, 30));
+        assert_eq!(state.last_runs.get(&Job::Backup), Some(&last));
+        assert_eq!(state.last_runs.len(), Job::ALL.len());
+    }
+}
@@ -0,0 +1,60 @@
+use actix_web::dev::ServerHandle;
+use actix_web::web;
+use std::time::{Duration, Instant};
+
+use crate::{lock, AppState};
+
+// Wie oft beim Beenden nachgesehen wird, ob noch Jobs laufen
+const POLL_MILLIS: u64 = 200;
+
+async fn signal() -> &'static str {
+    #[cfg(unix)]
+    {
+        use actix_web::rt::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => tokio::select! {
+                _ = actix_web::rt::signal::ctrl_c() => "SIGINT",
+                _ = terminate.recv() => "SIGTERM",
+            },
+            Err(_) => {
+                let _ = actix_web::rt::signal::ctrl_c().await;
+                "SIGINT"
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = actix_web::rt::signal::ctrl_c().await;
+        "Ctrl+C"
+    }
+}
+
+/// Wartet auf SIGINT/SIGTERM und hält dann die Server an: keine neuen
+/// Verbindungen, laufende Anfragen dürfen bis `shutdown_timeout_seconds`
+/// zu Ende laufen. Live-Streams enden sofort, Änderungen ergeben 503.
+pub async fn on_signal(data: web::Data<AppState>, servers: Vec<ServerHandle>) {
+    let signal = signal().await;
+    tracing::info!(signal, "Beende Backend, laufende Anfragen werden abgeschlossen");
+    data.shutdown.send_replace(true);
+    futures_util::future::join_all(servers.iter().map(|server| server.stop(true))).await;
+}
+
+/// Nach dem Anhalten der Server: laufende Jobs abwarten und – falls
+/// konfiguriert – die Sitzungen speichern.
+pub async fn flush(data: &AppState) {
+    let deadline = Instant::now() + Duration::from_secs(data.config.server.shutdown_timeout_seconds);
+    while !lock(&data.running_jobs).is_empty() && Instant::now() < deadline {
+        actix_web::rt::time::sleep(Duration::from_millis(POLL_MILLIS)).await;
+    }
+    let running: Vec<&str> = lock(&data.running_jobs).iter().map(|job| job.name()).collect();
+    if !running.is_empty() {
+        tracing::warn!(jobs = ?running, "Jobs beim Beenden abgebrochen");
+    }
+
+    if data.config.sessions.persist {
+        match data.save_sessions() {
+            Ok(count) => tracing::info!(count, "Sitzungen gespeichert"),
+            Err(err) => tracing::error!(error = %err, "Sitzungen nicht gespeichert"),
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use actix_tls::accept::rustls_0_23::TlsStream;
+use actix_web::dev::Extensions;
+use actix_web::http::header;
+use actix_web::rt::net::TcpStream;
+use actix_web::{HttpRequest, HttpResponse};
+use rustls::crypto::CryptoProvider;
+use rustls::server::{ClientHello, ResolvesServerCert, WebPkiClientVerifier};
+use rustls::sign::CertifiedKey;
+use rustls::{RootCertStore, ServerConfig};
+use rustls_pki_types::pem::PemObject;
+use rustls_pki_types::{CertificateDer, PrivateKeyDer};
+use std::any::Any;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+use x509_parser::prelude::{FromDer, X509Certificate};
+
+use crate::config::TlsConfig;
+
+// So oft wird geprüft, ob Zertifikat oder Schlüssel ersetzt wurden
+const RELOAD_CHECK_SECONDS: u64 = 10;
+
+/// Liefert bei jedem Handshake das aktuell geladene Zertifikat; `watch`
+/// tauscht es aus, ohne dass der Server neu starten muss.
+#[derive(Debug)]
+pub struct CertResolver {
+    current: RwLock<Arc<CertifiedKey>>,
+}
+
+impl ResolvesServerCert for CertResolver {
+    fn resolve(&self, _hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        Some(self.current.read().unwrap_or_else(|e| e.into_inner()).clone())
+    }
+}
+
+/// Benutzername aus dem Client-Zertifikat (Common Name), bei mTLS.
+#[derive(Clone)]
+pub struct ClientIdentity(pub String);
+
+fn provider() -> Arc<CryptoProvider> {
+    Arc::new(rustls::crypto::ring::default_provider())
+}
+
+fn read_certs(path: &Path) -> Result<Vec<CertificateDer<'static>>, String> {
+    let certs = CertificateDer::pem_file_iter(path)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+    if certs.is_empty() {
+        return Err(format!("{}: kein Zertifikat gefunden", path.display()));
+    }
+    Ok(certs)
+}
+
+fn load_key(tls: &TlsConfig) -> Result<CertifiedKey, String> {
+    let certs = read_certs(&tls.cert)?;
+    let key = PrivateKeyDer::from_pem_file(&tls.key).map_err(|e| format!("{}: {}", tls.key.display(), e))?;
+    CertifiedKey::from_der(certs, key, &provider()).map_err(|e| format!("Zertifikat und Schlüssel: {}", e))
+}
+
+/// rustls-Konfiguration samt Resolver für das spätere Neuladen.
+pub fn server_config(tls: &TlsConfig) -> Result<(ServerConfig, Arc<CertResolver>), String> {
+    let resolver = Arc::new(CertResolver { current: RwLock::new(Arc::new(load_key(tls)?)) });
+    let builder = ServerConfig::builder_with_provider(provider())
+        .with_safe_default_protocol_versions()
+        .map_err(|e| e.to_string())?;
+
+    let builder = if tls.client_ca.as_os_str().is_empty() {
+        builder.with_no_client_auth()
+    } else {
+        let mut roots = RootCertStore::empty();
+        for cert in read_certs(&tls.client_ca)? {
+            roots.add(cert).map_err(|e| format!("{}: {}", tls.client_ca.display(), e))?;
+        }
+        let mut verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider());
+        if tls.client_auth == "optional" {
+            // Ohne Zertifikat geht es mit Token weiter wie bisher
+            verifier = verifier.allow_unauthenticated();
+        }
+        builder.with_client_cert_verifier(verifier.build().map_err(|e| e.to_string())?)
+    };
+    Ok((builder.with_cert_resolver(resolver.clone()), resolver))
+}
+
+fn modified(tls: &TlsConfig) -> Option<(SystemTime, SystemTime)> {
+    let time = |path: &Path| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+    Some((time(&tls.cert)?, time(&tls.key)?))
+}
+
+/// Lädt Zertifikat und Schlüssel neu, sobald sich eine der Dateien ändert
+/// (z.B. nach einer Verlängerung durch certbot). Schlägt das fehl, bleibt
+/// das bisherige Zertifikat aktiv.
+pub async fn watch(tls: TlsConfig, resolver: Arc<CertResolver>) {
+    let mut seen = modified(&tls);
+    loop {
+        actix_web::rt::time::sleep(Duration::from_secs(RELOAD_CHECK_SECONDS)).await;
+        let current = modified(&tls);
+        if current.is_none() || current == seen {
+            continue;
+        }
+        // Zertifikat und Schlüssel werden selten gleichzeitig geschrieben;
+        // ein Fehler wird bei der nächsten Änderung erneut versucht
+        seen = current;
+        match load_key(&tls) {
+            Ok(key) => {
+                *resolver.current.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(key);
+                tracing::info!("TLS-Zertifikat neu geladen");
+            }
+            Err(err) => tracing::error!(error = %err, "TLS-Zertifikat nicht neu geladen"),
+        }
+    }
+}
+
+/// Antwort des zusätzlichen HTTP-Ports: dieselbe Adresse per HTTPS. 308
+/// statt 301, damit auch POST-Anfragen nicht zu GET werden.
+pub async fn redirect(req: HttpRequest, https_port: u16) -> HttpResponse {
+    let host = req.connection_info().host().to_string();
+    // Port abschneiden, IPv6-Adressen wie [::1]:8081 beachten
+    let name = match host.rfind(':') {
+        Some(index) if !host[index..].contains(']') => &host[..index],
+        _ => host.as_str(),
+    };
+    let path = req.uri().path_and_query().map_or("/", |p| p.as_str());
+    let location = if https_port == 443 {
+        format!("https://{}{}", name, path)
+    } else {
+        format!("https://{}:{}{}", name, https_port, path)
+    };
+    HttpResponse::PermanentRedirect().insert_header((header::LOCATION, location)).finish()
+}
+
+/// Merkt sich beim Verbindungsaufbau den Common Name eines geprüften
+/// Client-Zertifikats für `extract_session`.
+pub fn on_connect(connection: &dyn Any, extensions: &mut Extensions) {
+    let Some(stream) = connection.downcast_ref::<TlsStream<TcpStream>>() else {
+        return;
+    };
+    let common_name = stream
+        .get_ref()
+        .1
+        .peer_certificates()
+        .and_then(|certs| certs.first())
+        .and_then(|cert| {
+            let (_, cert) = X509Certificate::from_der(cert).ok()?;
+            let name = cert.subject().iter_common_name().next()?.as_str().ok()?.to_string();
+            Some(name)
+        });
+    if let Some(name) = common_name {
+        extensions.insert(ClientIdentity(name));
+    }
+}
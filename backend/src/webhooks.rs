@@ -0,0 +1,419 @@
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+use crate::error::ApiError;
+use crate::maintenance::WriteGate;
+use crate::{lock, save_json, AppState, Employee, Status, UserResponse};
+
+pub const WEBHOOKS_FILE: &str = "webhooks.json";
+pub const DELIVERIES_FILE: &str = "webhook_deliveries.json";
+const DELIVERY_LOG_LIMIT: usize = 500;
+const MAX_ATTEMPTS: u32 = 5;
+// Wartezeit vor dem 2., 3., ... Versuch: 5 s, 10 s, 20 s, 40 s
+const BASE_DELAY_SECONDS: u64 = 5;
+pub const TIMEOUT_SECONDS: u64 = 10;
+const MIN_SECRET_LEN: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Event {
+    #[serde(rename = "training.created")]
+    TrainingCreated,
+    #[serde(rename = "training.updated")]
+    TrainingUpdated,
+    #[serde(rename = "training.deleted")]
+    TrainingDeleted,
+    #[serde(rename = "status.gelb")]
+    StatusGelb,
+    #[serde(rename = "status.rot")]
+    StatusRot,
+    #[serde(rename = "user.created")]
+    UserCreated,
+    #[serde(rename = "user.deactivated")]
+    UserDeactivated,
+    // Nur für Testzustellungen
+    #[serde(rename = "ping")]
+    Ping,
+}
+
+impl Event {
+    fn name(self) -> &'static str {
+        match self {
+            Event::TrainingCreated => "training.created",
+            Event::TrainingUpdated => "training.updated",
+            Event::TrainingDeleted => "training.deleted",
+            Event::StatusGelb => "status.gelb",
+            Event::StatusRot => "status.rot",
+            Event::UserCreated => "user.created",
+            Event::UserDeactivated => "user.deactivated",
+            Event::Ping => "ping",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Webhook {
+    pub id: String,
+    pub name: String,
+    pub url: String,
+    // Schlüssel für die HMAC-Signatur, der Empfänger braucht ihn zum Prüfen
+    pub secret: String,
+    pub events: Vec<Event>,
+    pub active: bool,
+    pub created_at: String,
+}
+
+#[derive(Deserialize)]
+pub struct WebhookInput {
+    pub name: String,
+    pub url: String,
+    // Fehlt beim Anlegen: wird erzeugt; fehlt beim Ändern: bleibt
+    #[serde(default)]
+    pub secret: Option<String>,
+    pub events: Vec<Event>,
+    #[serde(default = "default_true")]
+    pub active: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Ein Zustellversuch im Protokoll.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Delivery {
+    pub id: String,
+    pub webhook_id: String,
+    pub event: Event,
+    // Gleich für alle Versuche derselben Nachricht
+    pub payload_id: String,
+    pub attempt: u32,
+    pub at: String,
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status_code: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub duration_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_retry_at: Option<String>,
+}
+
+#[derive(Serialize)]
+struct Payload<'a> {
+    id: &'a str,
+    event: Event,
+    created_at: String,
+    data: Value,
+}
+
+#[derive(Deserialize)]
+pub struct DeliveryParams {
+    pub webhook: Option<String>,
+    pub limit: Option<usize>,
+}
+
+impl WebhookInput {
+    pub fn validate(&self) -> Result<(), ApiError> {
+        let mut errors = BTreeMap::new();
+        if self.name.trim().is_empty() {
+            errors.insert("name".to_string(), "Name fehlt".to_string());
+        }
+        match reqwest::Url::parse(self.url.trim()) {
+            Ok(url) if url.scheme() == "http" || url.scheme() == "https" => {}
+            _ => {
+                errors.insert("url".to_string(), "http(s)-URL erwartet".to_string());
+            }
+        }
+        if self.events.is_empty() {
+            errors.insert("events".to_string(), "Mindestens ein Ereignis".to_string());
+        } else if self.events.contains(&Event::Ping) {
+            errors.insert("events".to_string(), "ping ist nur für Testzustellungen".to_string());
+        }
+        if let Some(secret) = &self.secret {
+            if !secret.is_empty() && secret.len() < MIN_SECRET_LEN {
+                errors.insert("secret".to_string(), format!("Mindestens {} Zeichen", MIN_SECRET_LEN));
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ApiError::Validation(errors))
+        }
+    }
+}
+
+pub fn create(data: &AppState, input: WebhookInput) -> Result<Webhook, ApiError> {
+    input.validate()?;
+    let secret = match input.secret.filter(|s| !s.is_empty()) {
+        Some(secret) => secret,
+        None => format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple()),
+    };
+    let webhook = Webhook {
+        id: Uuid::new_v4().to_string(),
+        name: input.name.trim().to_string(),
+        url: input.url.trim().to_string(),
+        secret,
+        events: input.events,
+        active: input.active,
+        created_at: Utc::now().to_rfc3339(),
+    };
+    lock(&data.webhooks).push(webhook.clone());
+    data.save_webhooks()?;
+    Ok(webhook)
+}
+
+pub fn update(data: &AppState, id: &str, input: WebhookInput) -> Result<Webhook, ApiError> {
+    input.validate()?;
+    let mut webhooks = lock(&data.webhooks);
+    let webhook = webhooks
+        .iter_mut()
+        .find(|w| w.id == id)
+        .ok_or(ApiError::NotFound("Webhook"))?;
+    webhook.name = input.name.trim().to_string();
+    webhook.url = input.url.trim().to_string();
+    if let Some(secret) = input.secret.filter(|s| !s.is_empty()) {
+        webhook.secret = secret;
+    }
+    webhook.events = input.events;
+    webhook.active = input.active;
+    let webhook = webhook.clone();
+    drop(webhooks);
+
+    data.save_webhooks()?;
+    Ok(webhook)
+}
+
+pub fn delete(data: &AppState, id: &str) -> Result<(), ApiError> {
+    let mut webhooks = lock(&data.webhooks);
+    let before = webhooks.len();
+    webhooks.retain(|w| w.id != id);
+    if webhooks.len() == before {
+        return Err(ApiError::NotFound("Webhook"));
+    }
+    drop(webhooks);
+    data.save_webhooks()
+}
+
+pub fn deliveries(data: &AppState, params: &DeliveryParams) -> Vec<Delivery> {
+    let limit = params.limit.unwrap_or(100).min(DELIVERY_LOG_LIMIT);
+    lock(&data.webhook_deliveries)
+        .iter()
+        .rev()
+        .filter(|d| params.webhook.as_ref().is_none_or(|w| &d.webhook_id == w))
+        .take(limit)
+        .cloned()
+        .collect()
+}
+
+/// `sha256=<hex>` über `<timestamp>.<body>` – der Zeitstempel verhindert,
+/// dass abgefangene Nachrichten später erneut eingespielt werden.
+fn signature(secret: &str, timestamp: &str, body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC akzeptiert jede Schlüssellänge");
+    mac.update(timestamp.as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+// Der Aufrufer hält die `WriteGate`, sonst könnte die Sicherung mitten im Schreiben kopieren
+fn record(log: &Mutex<Vec<Delivery>>, delivery: Delivery) {
+    let mut log = lock(log);
+    log.push(delivery);
+    let excess = log.len().saturating_sub(DELIVERY_LOG_LIMIT);
+    log.drain(..excess);
+    if let Err(err) = save_json(DELIVERIES_FILE, &*log) {
+        tracing::error!(error = %err, "Webhook-Protokoll nicht gespeichert");
+    }
+}
+
+async fn attempt(client: &reqwest::Client, webhook: &Webhook, event: Event, payload_id: &str, body: &str, n: u32) -> Delivery {
+    let timestamp = Utc::now().timestamp().to_string();
+    let started = Instant::now();
+    let result = client
+        .post(&webhook.url)
+        .header("Content-Type", "application/json")
+        .header("X-Webhook-Id", payload_id)
+        .header("X-Webhook-Event", event.name())
+        .header("X-Webhook-Timestamp", &timestamp)
+        .header("X-Webhook-Signature", signature(&webhook.secret, &timestamp, body))
+        .body(body.to_string())
+        .send()
+        .await;
+
+    let (ok, status_code, error) = match result {
+        Ok(response) if response.status().is_success() => (true, Some(response.status().as_u16()), None),
+        Ok(response) => (false, Some(response.status().as_u16()), Some(format!("HTTP {}", response.status()))),
+        Err(err) => (false, None, Some(err.to_string())),
+    };
+    Delivery {
+        id: Uuid::new_v4().to_string(),
+        webhook_id: webhook.id.clone(),
+        event,
+        payload_id: payload_id.to_string(),
+        attempt: n,
+        at: Utc::now().to_rfc3339(),
+        ok,
+        status_code,
+        error,
+        duration_ms: started.elapsed().as_millis() as u64,
+        next_retry_at: None,
+    }
+}
+
+/// Stellt zu und wiederholt Fehlschläge mit wachsendem Abstand. Offene
+/// Wiederholungen gehen bei einem Neustart verloren.
+async fn deliver(
+    client: reqwest::Client,
+    gate: WriteGate,
+    log: Arc<Mutex<Vec<Delivery>>>,
+    webhook: Webhook,
+    event: Event,
+    payload_id: String,
+    body: String,
+) {
+    for n in 1..=MAX_ATTEMPTS {
+        let mut delivery = attempt(&client, &webhook, event, &payload_id, &body, n).await;
+        let ok = delivery.ok;
+        let delay = Duration::from_secs(BASE_DELAY_SECONDS << (n - 1));
+        if !ok && n < MAX_ATTEMPTS {
+            delivery.next_retry_at = Some((Utc::now() + chrono::Duration::from_std(delay).unwrap_or_default()).to_rfc3339());
+        }
+        {
+            let _writing = gate.read().await;
+            record(&log, delivery);
+        }
+        if ok || n == MAX_ATTEMPTS {
+            return;
+        }
+        actix_web::rt::time::sleep(delay).await;
+    }
+}
+
+fn body(event: Event, payload_id: &str, data: Value) -> String {
+    let payload = Payload {
+        id: payload_id,
+        event,
+        created_at: Utc::now().to_rfc3339(),
+        data,
+    };
+    serde_json::to_string(&payload).unwrap_or_default()
+}
+
+/// Verschickt ein Ereignis an alle aktiven Webhooks, die es abonniert haben.
+/// Die Zustellung läuft im Hintergrund, der Aufrufer wartet nicht.
+pub fn emit(data: &AppState, event: Event, payload: Value) {
+    if !data.config.notifications.enabled {
+        return;
+    }
+    let webhooks: Vec<Webhook> = lock(&data.webhooks)
+        .iter()
+        .filter(|w| w.active && w.events.contains(&event))
+        .cloned()
+        .collect();
+    if webhooks.is_empty() {
+        return;
+    }
+
+    let payload_id = Uuid::new_v4().to_string();
+    let body = body(event, &payload_id, payload);
+    for webhook in webhooks {
+        actix_web::rt::spawn(deliver(
+            data.http.clone(),
+            data.write_gate.clone(),
+            data.webhook_deliveries.clone(),
+            webhook,
+            event,
+            payload_id.clone(),
+            body.clone(),
+        ));
+    }
+}
+
+/// Leitet aus dem Stand vor und nach einer Änderung die Ereignisse ab –
+/// so lösen Einzel-, Sammel- und Importänderungen dieselben Webhooks aus.
+pub fn employees_changed(data: &AppState, before: &[Employee], after: &[Employee]) {
+    let previous: BTreeMap<u32, &Employee> = before.iter().map(|e| (e.id, e)).collect();
+    for e in after {
+        let old = previous.get(&e.id);
+        match old {
+            None => emit(data, Event::TrainingCreated, json!({ "employee": e })),
+            Some(old) if old.version != e.version => emit(data, Event::TrainingUpdated, json!({ "employee": e })),
+            Some(_) => continue,
+        }
+        let old_status = old.map(|o| o.status);
+        if old_status == Some(e.status) {
+            continue;
+        }
+        let event = match e.status {
+            Status::Gelb => Event::StatusGelb,
+            Status::Rot => Event::StatusRot,
+            Status::Gruen => continue,
+        };
+        emit(
+            data,
+            event,
+            json!({ "employee": e, "previous_status": old_status.map(Status::as_str) }),
+        );
+    }
+    let current: BTreeMap<u32, &Employee> = after.iter().map(|e| (e.id, e)).collect();
+    for e in before.iter().filter(|e| !current.contains_key(&e.id)) {
+        emit(data, Event::TrainingDeleted, json!({ "employee": e }));
+    }
+}
+
+pub fn user_event(data: &AppState, event: Event, user: &UserResponse) {
+    emit(data, event, json!({ "user": user }));
+}
+
+/// Einzelner Versuch ohne Wiederholung; das Ergebnis kommt direkt zurück.
+pub async fn send_test(data: &AppState, id: &str) -> Result<Delivery, ApiError> {
+    data.require_notifications()?;
+    let webhook = lock(&data.webhooks)
+        .iter()
+        .find(|w| w.id == id)
+        .cloned()
+        .ok_or(ApiError::NotFound("Webhook"))?;
+    let payload_id = Uuid::new_v4().to_string();
+    let body = body(Event::Ping, &payload_id, json!({ "webhook": webhook.name }));
+    let delivery = attempt(&data.http, &webhook, Event::Ping, &payload_id, &body, 1).await;
+    // Läuft in einer schreibenden Anfrage, die `WriteGate` ist schon gehalten
+    record(&data.webhook_deliveries, delivery.clone());
+    Ok(delivery)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signature_matches_reference_hmac() {
+        // Referenzwert: HMAC-SHA256("geheim", "1700000000.{\"event\":\"ping\"}")
+        assert_eq!(
+            signature("geheim", "1700000000", r#"{"event":"ping"}"#),
+            "sha256=39dbe93a8b8c5806b486935791ecb557dffbb47c50080c5dca5ac78038e29f10"
+        );
+    }
+
+    #[test]
+    fn signature_covers_timestamp_body_and_secret() {
+        let reference = signature("geheim", "1700000000", "{}");
+        assert_ne!(signature("geheim", "1700000001", "{}"), reference);
+        assert_ne!(signature("geheim", "1700000000", "{ }"), reference);
+        assert_ne!(signature("anders", "1700000000", "{}"), reference);
+    }
+
+    #[test]
+    fn body_carries_event_and_payload_id() {
+        let body: Value = serde_json::from_str(&body(Event::Ping, "abc", json!({ "webhook": "Test" }))).unwrap();
+        assert_eq!(body["id"], "abc");
+        assert_eq!(body["event"], Event::Ping.name());
+        assert_eq!(body["data"]["webhook"], "Test");
+    }
+}
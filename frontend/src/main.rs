@@ -1,5 +1,11 @@
+use base64::Engine;
 use eframe::egui;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct Employee {
@@ -9,6 +15,12 @@ struct Employee {
     duedate: String,
     status: Status,
     created_by: String,
+    #[serde(default)]
+    custom_fields: BTreeMap<String, String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    version: u64,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
@@ -20,7 +32,7 @@ enum Status {
 }
 
 impl Status {
-    fn to_string(&self) -> &str {
+    fn to_string(self) -> &'static str {
         match self {
             Status::Gruen => "Aktuell",
             Status::Gelb => "Bald fällig",
@@ -28,7 +40,7 @@ impl Status {
         }
     }
 
-    fn to_api_string(&self) -> &str {
+    fn to_api_string(self) -> &'static str {
         match self {
             Status::Gruen => "gruen",
             Status::Gelb => "gelb",
@@ -45,6 +57,37 @@ impl Status {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum FieldKind {
+    Text,
+    Number,
+    Date,
+    Enum,
+}
+
+impl FieldKind {
+    fn to_string(self) -> &'static str {
+        match self {
+            FieldKind::Text => "Text",
+            FieldKind::Number => "Zahl",
+            FieldKind::Date => "Datum",
+            FieldKind::Enum => "Auswahl",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CustomFieldDef {
+    name: String,
+    label: String,
+    kind: FieldKind,
+    #[serde(default)]
+    options: Vec<String>,
+    #[serde(default)]
+    required: bool,
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 enum Role {
@@ -83,12 +126,248 @@ struct LoginResponse {
     user: User,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Clone)]
 struct CreateEmployee {
     name: String,
     training: String,
     duedate: String,
     status: String,
+    custom_fields: BTreeMap<String, String>,
+    tags: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct BulkCreateRequest {
+    items: Vec<CreateEmployee>,
+}
+
+#[derive(Serialize)]
+struct BulkTarget {
+    id: u32,
+    version: u64,
+}
+
+#[derive(Serialize, Default)]
+struct BulkChanges {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    shift_days: Option<i64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    add_tags: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    remove_tags: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct BulkUpdateRequest {
+    items: Vec<BulkTarget>,
+    changes: BulkChanges,
+}
+
+#[derive(Serialize)]
+struct BulkDeleteRequest {
+    ids: Vec<u32>,
+}
+
+#[derive(Deserialize)]
+struct BulkItemResult {
+    index: usize,
+    #[serde(default)]
+    id: Option<u32>,
+    ok: bool,
+    #[serde(default)]
+    error: Option<String>,
+    #[serde(default)]
+    fields: BTreeMap<String, String>,
+    #[serde(default)]
+    missing: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct BulkResponse {
+    items: Vec<BulkItemResult>,
+}
+
+#[derive(Serialize)]
+struct ImportRequest {
+    format: String,
+    data: String,
+    mapping: BTreeMap<String, String>,
+    dry_run: bool,
+    skip_errors: bool,
+}
+
+#[derive(Deserialize, Default)]
+struct ImportSummary {
+    create: usize,
+    update: usize,
+    unchanged: usize,
+    error: usize,
+}
+
+#[derive(Deserialize)]
+struct ImportRow {
+    row: usize,
+    action: String,
+    #[serde(default)]
+    employee: Option<Employee>,
+    #[serde(default)]
+    error: Option<String>,
+    #[serde(default)]
+    fields: BTreeMap<String, String>,
+    #[serde(default)]
+    warnings: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct ImportReport {
+    columns: Vec<String>,
+    mapping: BTreeMap<String, String>,
+    committed: bool,
+    summary: ImportSummary,
+    rows: Vec<ImportRow>,
+}
+
+// Zielfelder für die Spaltenzuordnung (ohne Zusatzfelder)
+const IMPORT_FIELDS: [(&str, &str); 6] = [
+    ("id", "ID"),
+    ("name", "Name"),
+    ("training", "Training"),
+    ("duedate", "Fällig"),
+    ("status", "Status"),
+    ("tags", "Tags"),
+];
+
+#[derive(Deserialize)]
+struct CalendarFeed {
+    url: String,
+    created_at: String,
+}
+
+#[derive(Clone, Deserialize)]
+struct Webhook {
+    id: String,
+    name: String,
+    url: String,
+    secret: String,
+    events: Vec<String>,
+    active: bool,
+}
+
+#[derive(Serialize)]
+struct WebhookInput {
+    name: String,
+    url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    secret: Option<String>,
+    events: Vec<String>,
+    active: bool,
+}
+
+#[derive(Deserialize)]
+struct WebhookDelivery {
+    webhook_id: String,
+    event: String,
+    attempt: u32,
+    at: String,
+    ok: bool,
+    #[serde(default)]
+    status_code: Option<u16>,
+    #[serde(default)]
+    error: Option<String>,
+    duration_ms: u64,
+}
+
+const WEBHOOK_EVENTS: [(&str, &str); 7] = [
+    ("training.created", "Eintrag angelegt"),
+    ("training.updated", "Eintrag geändert"),
+    ("training.deleted", "Eintrag gelöscht"),
+    ("status.gelb", "Status → Bald fällig"),
+    ("status.rot", "Status → Überfällig"),
+    ("user.created", "Benutzer angelegt"),
+    ("user.deactivated", "Benutzer deaktiviert"),
+];
+
+#[derive(Clone, Deserialize)]
+struct EscalationNote {
+    by: String,
+    at: String,
+    comment: String,
+}
+
+#[derive(Clone, Deserialize)]
+struct EscalationEvent {
+    at: String,
+    action: String,
+    level: usize,
+    #[serde(default)]
+    by: Option<String>,
+    comment: String,
+}
+
+#[derive(Clone, Deserialize)]
+struct Escalation {
+    employee_id: u32,
+    level: usize,
+    escalated_at: String,
+    #[serde(default)]
+    acknowledged: Option<EscalationNote>,
+    #[serde(default)]
+    cleared: Option<EscalationNote>,
+    #[serde(default)]
+    events: Vec<EscalationEvent>,
+}
+
+impl Escalation {
+    fn color(&self) -> egui::Color32 {
+        if self.cleared.is_some() {
+            egui::Color32::GRAY
+        } else if self.acknowledged.is_some() {
+            Status::Gelb.color()
+        } else {
+            Status::Rot.color()
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct CommentRequest {
+    comment: String,
+}
+
+#[derive(Clone, Deserialize)]
+struct JobRun {
+    job: String,
+    trigger: String,
+    started_at: String,
+    finished_at: String,
+    ok: bool,
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct JobInfo {
+    job: String,
+    label: String,
+    schedule: String,
+    enabled: bool,
+    running: bool,
+    #[serde(default)]
+    next_run: Option<String>,
+    #[serde(default)]
+    last_run: Option<JobRun>,
+}
+
+#[derive(Serialize)]
+struct JobUpdate {
+    schedule: String,
+    enabled: bool,
+}
+
+#[derive(Serialize)]
+struct TagRequest {
+    name: String,
 }
 
 #[derive(Serialize)]
@@ -104,11 +383,372 @@ struct UpdateUserRequest {
     role: Option<Role>,
 }
 
-#[derive(PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CatalogEntry {
+    name: String,
+    #[serde(default)]
+    prerequisites: Vec<String>,
+    #[serde(default)]
+    strict: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum PrerequisiteState {
+    Ok,
+    Missing,
+    Expired,
+}
+
+impl PrerequisiteState {
+    fn to_string(self) -> &'static str {
+        match self {
+            PrerequisiteState::Ok => "Erfüllt",
+            PrerequisiteState::Missing => "Fehlt",
+            PrerequisiteState::Expired => "Abgelaufen",
+        }
+    }
+
+    fn color(&self) -> egui::Color32 {
+        match self {
+            PrerequisiteState::Ok => Status::Gruen.color(),
+            PrerequisiteState::Missing => Status::Rot.color(),
+            PrerequisiteState::Expired => Status::Gelb.color(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct PrerequisiteNode {
+    training: String,
+    state: PrerequisiteState,
+    requires: Vec<PrerequisiteNode>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct EmployeeStats {
+    total: usize,
+    gruen: usize,
+    gelb: usize,
+    rot: usize,
+    #[serde(default)]
+    tags: BTreeMap<String, usize>,
+}
+
+const PAGE_SIZE: usize = 50;
+
+const SORT_FIELDS: [(&str, &str); 5] = [
+    ("id", "ID"),
+    ("name", "Name"),
+    ("training", "Training"),
+    ("duedate", "Fälligkeit"),
+    ("status", "Status"),
+];
+
+#[derive(Deserialize)]
+struct CreateEmployeeResponse {
+    #[serde(default)]
+    warnings: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct ApiError {
+    #[serde(default)]
+    code: String,
+    error: String,
+    #[serde(default)]
+    missing: Vec<String>,
+    #[serde(default)]
+    fields: BTreeMap<String, String>,
+    // Aktueller Serverstand bei version_conflict
+    #[serde(default)]
+    current: Option<Employee>,
+    // Bericht je Element bei bulk_rejected
+    #[serde(default)]
+    items: Vec<BulkItemResult>,
+    // Aus `X-Request-Id`, damit sich der Fehler im Server-Log finden lässt
+    #[serde(skip)]
+    request_id: Option<String>,
+}
+
+// Nur geänderte Felder für PATCH /api/employees/{id}
+#[derive(Serialize, Default)]
+struct EmployeePatch {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    training: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    duedate: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    custom_fields: Option<BTreeMap<String, String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tags: Option<Vec<String>>,
+}
+
+impl EmployeePatch {
+    fn diff(original: &Employee, edited: &Employee) -> Self {
+        let changed = |a: &String, b: &String| (a != b).then(|| b.clone());
+        // Leere Zusatzfelder entstehen schon durch das Anzeigen im Formular
+        let filled = |fields: &BTreeMap<String, String>| -> BTreeMap<String, String> {
+            fields
+                .iter()
+                .filter(|(_, v)| !v.trim().is_empty())
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect()
+        };
+        let custom_fields = filled(&edited.custom_fields);
+
+        EmployeePatch {
+            name: changed(&original.name, &edited.name),
+            training: changed(&original.training, &edited.training),
+            duedate: changed(&original.duedate, &edited.duedate),
+            status: (original.status != edited.status).then(|| edited.status.to_api_string().to_string()),
+            custom_fields: (filled(&original.custom_fields) != custom_fields).then_some(custom_fields),
+            tags: (original.tags != edited.tags).then(|| edited.tags.clone()),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.name.is_none()
+            && self.training.is_none()
+            && self.duedate.is_none()
+            && self.status.is_none()
+            && self.custom_fields.is_none()
+            && self.tags.is_none()
+    }
+}
+
+impl ApiError {
+    async fn from_response(response: reqwest::Response) -> reqwest::Result<Self> {
+        let request_id = response
+            .headers()
+            .get("X-Request-Id")
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        let mut error = response.json::<ApiError>().await?;
+        error.request_id = request_id;
+        Ok(error)
+    }
+
+    fn message(&self) -> String {
+        let message = self.summary();
+        match &self.request_id {
+            Some(id) if self.code != "unauthorized" => format!("{} (Anfrage-ID: {})", message, id),
+            _ => message,
+        }
+    }
+
+    fn summary(&self) -> String {
+        if self.code == "unauthorized" {
+            "✗ Sitzung abgelaufen, bitte neu anmelden".to_string()
+        } else if !self.missing.is_empty() {
+            format!("✗ {}: {}", self.error, self.missing.join(", "))
+        } else if !self.items.is_empty() {
+            let failed: Vec<String> = self
+                .items
+                .iter()
+                .filter(|item| !item.ok)
+                .take(3)
+                .map(|item| {
+                    let target = item.id.map(|id| format!("#{}", id)).unwrap_or_else(|| format!("Zeile {}", item.index + 1));
+                    let detail = if item.fields.is_empty() {
+                        item.error.clone().unwrap_or_default()
+                    } else {
+                        item.fields.values().cloned().collect::<Vec<_>>().join(", ")
+                    };
+                    format!("{}: {}", target, detail)
+                })
+                .collect();
+            format!("✗ {} – {}", self.error, failed.join("; "))
+        } else {
+            format!("✗ {}", self.error)
+        }
+    }
+}
+
+/// Änderung aus dem Ereignis-Stream (`GET /api/events`).
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum LiveChange {
+    Employee { employee: Employee },
+    EmployeeDeleted { id: u32 },
+    User { user: User },
+    UserDeleted { id: String },
+}
+
+enum LiveMessage {
+    Connected,
+    Disconnected,
+    // Änderungen verpasst – alles neu laden
+    Resync,
+    Change(LiveChange),
+}
+
+/// Läuft im Hintergrund, solange jemand angemeldet ist; beim Verwerfen
+/// wird die Verbindung beendet.
+struct LiveFeed {
+    receiver: mpsc::Receiver<LiveMessage>,
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl Drop for LiveFeed {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+// So lange bleibt ein live geänderter Eintrag markiert
+const LIVE_HIGHLIGHT: Duration = Duration::from_secs(10);
+
+// Verbindungsaufbau und gewöhnliche Anfragen; Import, Downloads und
+// manuell gestartete Jobs dürfen länger dauern
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+const LONG_REQUEST_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Gespeicherte Serververbindung.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct ConnectionProfile {
+    name: String,
+    url: String,
+    // PEM-Datei einer eigenen CA, leer = nur die Zertifikate des Systems
+    #[serde(default)]
+    ca_cert: String,
+}
+
+impl ConnectionProfile {
+    fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Name fehlt".to_string());
+        }
+        match reqwest::Url::parse(self.url.trim()) {
+            Ok(url) if url.scheme() == "http" || url.scheme() == "https" => Ok(()),
+            _ => Err("Adresse wie https://schulungen.example.com:8443 erwartet".to_string()),
+        }
+    }
+
+    fn base_url(&self) -> String {
+        self.url.trim().trim_end_matches('/').to_string()
+    }
+}
+
+/// Verbindungsprofile, gespeichert unter `~/.config/training-dashboard/`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ConnectionSettings {
+    profiles: Vec<ConnectionProfile>,
+    // Name des aktiven Profils
+    active: String,
+}
+
+impl Default for ConnectionSettings {
+    fn default() -> Self {
+        ConnectionSettings {
+            profiles: vec![ConnectionProfile {
+                name: "Lokal".to_string(),
+                url: "http://127.0.0.1:8080".to_string(),
+                ca_cert: String::new(),
+            }],
+            active: "Lokal".to_string(),
+        }
+    }
+}
+
+impl ConnectionSettings {
+    fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("training-dashboard").join("connections.json"))
+    }
+
+    /// Fehlende oder unlesbare Datei ergibt das Standardprofil.
+    fn load() -> Self {
+        Self::path()
+            .and_then(|path| std::fs::read(path).ok())
+            .and_then(|bytes| serde_json::from_slice::<ConnectionSettings>(&bytes).ok())
+            .filter(|settings| !settings.profiles.is_empty())
+            .unwrap_or_default()
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let path = Self::path().ok_or("Kein Konfigurationsverzeichnis gefunden")?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let json = serde_json::to_vec_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(&path, json).map_err(|e| e.to_string())
+    }
+
+    fn active(&self) -> &ConnectionProfile {
+        self.profiles
+            .iter()
+            .find(|p| p.name == self.active)
+            .unwrap_or(&self.profiles[0])
+    }
+}
+
+/// Offenes Fenster „Verbindung“; bearbeitet wird eine Kopie der Profile.
+struct ConnectionDialog {
+    draft: ConnectionSettings,
+    selected: usize,
+    error: Option<String>,
+    // Ergebnis des letzten Verbindungstests
+    test: Option<String>,
+}
+
+/// Client für ein Profil; ohne `timeout` für den dauerhaft offenen
+/// Ereignis-Stream.
+fn build_client(profile: &ConnectionProfile, timeout: Option<Duration>) -> Result<reqwest::Client, String> {
+    let mut builder = reqwest::Client::builder().connect_timeout(CONNECT_TIMEOUT);
+    if let Some(timeout) = timeout {
+        builder = builder.timeout(timeout);
+    }
+    let ca_cert = profile.ca_cert.trim();
+    if !ca_cert.is_empty() {
+        let pem = std::fs::read(ca_cert).map_err(|e| format!("CA-Zertifikat nicht lesbar: {}", e))?;
+        let certificate = reqwest::Certificate::from_pem(&pem).map_err(|e| format!("CA-Zertifikat ungültig: {}", e))?;
+        builder = builder.add_root_certificate(certificate);
+    }
+    builder.build().map_err(|e| format!("HTTP-Client: {}", e))
+}
+
+/// Fehlermeldung samt Ursachen – bei TLS-Problemen steht das Wesentliche
+/// meist erst in der Ursache.
+fn error_chain(error: &dyn std::error::Error) -> String {
+    let mut message = error.to_string();
+    let mut source = error.source();
+    while let Some(cause) = source {
+        // Manche Fehler nennen ihre Ursache schon selbst
+        let cause_message = cause.to_string();
+        if !message.contains(&cause_message) {
+            message.push_str(": ");
+            message.push_str(&cause_message);
+        }
+        source = cause.source();
+    }
+    message
+}
+
+/// Ergebnisverarbeitung einer fertigen Anfrage, läuft im UI-Thread.
+type Completion = Box<dyn FnOnce(&mut TrainingDashboard) + Send>;
+
+struct PendingRequest {
+    id: u64,
+    key: &'static str,
+    // Gesetzt = gehört zu diesem Bildschirm und wird beim Verlassen abgebrochen
+    screen: Option<Screen>,
+    task: tokio::task::JoinHandle<()>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
 enum Screen {
     Login,
     Dashboard,
     AdminPanel,
+    Catalog,
+    CustomFields,
+    Import,
+    Jobs,
 }
 
 struct TrainingDashboard {
@@ -123,22 +763,115 @@ struct TrainingDashboard {
     new_training: String,
     new_duedate: String,
     new_status: Status,
+    new_custom_fields: BTreeMap<String, String>,
+    new_tags: Vec<String>,
+    new_errors: BTreeMap<String, String>,
+    // Ein Name pro Zeile, angelegt per Sammelanfrage
+    new_multi: bool,
+    // Ausgewählte Einträge (ID -> Version) für Sammelaktionen
+    selected: BTreeMap<u32, u64>,
+    bulk_status: Status,
+    bulk_shift_days: i64,
+    bulk_tag: String,
+    import_file_name: Option<String>,
+    import_format: String,
+    // Dateiinhalt Base64-kodiert, wie ihn der Server erwartet
+    import_data: String,
+    import_mapping: BTreeMap<String, String>,
+    import_report: Option<ImportReport>,
+    import_errors: BTreeMap<String, String>,
+    import_skip_errors: bool,
+    // Eskalationen nach Eintrags-ID, Fenster mit Verlauf und Kommentar
+    escalations: BTreeMap<u32, Escalation>,
+    escalation_open: Option<u32>,
+    escalation_comment: String,
+    // Geöffnetes Fenster „Kalender abonnieren“
+    calendar_feed: Option<CalendarFeed>,
+    export_format: &'static str,
+    // Gruppierung des Compliance-Berichts, leer = nur Gesamtzahlen
+    report_group_by: String,
+    editing: Option<Employee>,
+    // Stand beim Öffnen des Editors – Grundlage für PATCH und If-Match
+    edit_original: Option<Employee>,
+    edit_conflict: Option<Employee>,
+    edit_errors: BTreeMap<String, String>,
     search_term: String,
     filter_critical: bool,
+    filter_tags: Vec<String>,
+    sort_field: String,
+    sort_desc: bool,
+    page: usize,
+    total_count: usize,
+    stats: EmployeeStats,
     
     users: Vec<User>,
     new_user_username: String,
     new_user_password: String,
     new_user_role: Role,
     
+    webhooks: Vec<Webhook>,
+    webhook_deliveries: Vec<WebhookDelivery>,
+    // ID des bearbeiteten Webhooks, None = neuer
+    webhook_editing: Option<String>,
+    webhook_name: String,
+    webhook_url: String,
+    // Leer = erzeugen bzw. bisherigen behalten
+    webhook_secret: String,
+    webhook_events: BTreeSet<String>,
+    
+    catalog: Vec<CatalogEntry>,
+    new_catalog_name: String,
+    new_catalog_prerequisites: String,
+    new_catalog_strict: bool,
+    chain_person: Option<String>,
+    chain: Vec<PrerequisiteNode>,
+    
+    tags: Vec<String>,
+    new_tag_name: String,
+    renaming_tag: Option<String>,
+    
+    custom_field_defs: Vec<CustomFieldDef>,
+    new_field_name: String,
+    new_field_label: String,
+    new_field_kind: FieldKind,
+    new_field_options: String,
+    new_field_required: bool,
+    
+    // Zeitplan und Aktiv-Schalter werden direkt in der Liste bearbeitet
+    jobs: Vec<JobInfo>,
+    job_history: Vec<JobRun>,
+    
+    live: Option<LiveFeed>,
+    live_connected: bool,
+    // Von anderer Stelle geänderte Einträge und wann
+    live_changed: BTreeMap<u32, Instant>,
+    // Änderungen an Einträgen, die nicht auf der aktuellen Seite stehen
+    live_pending: usize,
+    
     message: Option<String>,
     show_menu: bool,
+    connection: ConnectionSettings,
+    connection_dialog: Option<ConnectionDialog>,
     api_url: String,
     rt: tokio::runtime::Runtime,
+    // Gemeinsamer Client – hält Verbindungen zum Server offen
+    client: reqwest::Client,
+    ctx: egui::Context,
+    requests: Vec<PendingRequest>,
+    next_request_id: u64,
+    completion_sender: mpsc::Sender<(u64, Completion)>,
+    completion_receiver: mpsc::Receiver<(u64, Completion)>,
 }
 
 impl Default for TrainingDashboard {
     fn default() -> Self {
+        let (completion_sender, completion_receiver) = mpsc::channel();
+        let connection = ConnectionSettings::load();
+        let mut message = None;
+        let client = build_client(connection.active(), Some(REQUEST_TIMEOUT)).unwrap_or_else(|error| {
+            message = Some(format!("✗ {}", error));
+            reqwest::Client::new()
+        });
         Self {
             screen: Screen::Login,
             token: None,
@@ -151,319 +884,2939 @@ impl Default for TrainingDashboard {
             new_training: String::new(),
             new_duedate: String::new(),
             new_status: Status::Gruen,
+            new_custom_fields: BTreeMap::new(),
+            new_tags: Vec::new(),
+            new_errors: BTreeMap::new(),
+            new_multi: false,
+            selected: BTreeMap::new(),
+            bulk_status: Status::Gruen,
+            bulk_shift_days: 30,
+            bulk_tag: String::new(),
+            import_file_name: None,
+            import_format: String::new(),
+            import_data: String::new(),
+            import_mapping: BTreeMap::new(),
+            import_report: None,
+            import_errors: BTreeMap::new(),
+            import_skip_errors: false,
+            escalations: BTreeMap::new(),
+            escalation_open: None,
+            escalation_comment: String::new(),
+            calendar_feed: None,
+            export_format: "xlsx",
+            report_group_by: String::new(),
+            editing: None,
+            edit_original: None,
+            edit_conflict: None,
+            edit_errors: BTreeMap::new(),
             search_term: String::new(),
             filter_critical: false,
+            filter_tags: Vec::new(),
+            sort_field: "id".to_string(),
+            sort_desc: false,
+            page: 1,
+            total_count: 0,
+            stats: EmployeeStats::default(),
             
             users: Vec::new(),
             new_user_username: String::new(),
             new_user_password: String::new(),
             new_user_role: Role::User,
             
-            message: None,
+            webhooks: Vec::new(),
+            webhook_deliveries: Vec::new(),
+            webhook_editing: None,
+            webhook_name: String::new(),
+            webhook_url: String::new(),
+            webhook_secret: String::new(),
+            webhook_events: BTreeSet::new(),
+            
+            catalog: Vec::new(),
+            new_catalog_name: String::new(),
+            new_catalog_prerequisites: String::new(),
+            new_catalog_strict: false,
+            chain_person: None,
+            chain: Vec::new(),
+            
+            tags: Vec::new(),
+            new_tag_name: String::new(),
+            renaming_tag: None,
+            
+            custom_field_defs: Vec::new(),
+            new_field_name: String::new(),
+            new_field_label: String::new(),
+            new_field_kind: FieldKind::Text,
+            new_field_options: String::new(),
+            new_field_required: false,
+            
+            jobs: Vec::new(),
+            job_history: Vec::new(),
+            
+            live: None,
+            live_connected: false,
+            live_changed: BTreeMap::new(),
+            live_pending: 0,
+            
+            message,
             show_menu: false,
-            api_url: "http://127.0.0.1:8080".to_string(),
+            api_url: connection.active().base_url(),
+            connection,
+            connection_dialog: None,
             rt: tokio::runtime::Runtime::new().unwrap(),
+            client,
+            ctx: egui::Context::default(),
+            requests: Vec::new(),
+            next_request_id: 0,
+            completion_sender,
+            completion_receiver,
         }
     }
 }
 
 impl TrainingDashboard {
-    fn login(&mut self) {
-        let login_data = LoginRequest {
-            username: self.login_username.clone(),
-            password: self.login_password.clone(),
-        };
+    /// Lädt Daten im Hintergrund. Eine neue Anfrage mit demselben Schlüssel
+    /// ersetzt die laufende; mit `screen` wird sie beim Wechsel auf einen
+    /// anderen Bildschirm abgebrochen.
+    fn fetch<T: Send + 'static>(
+        &mut self,
+        key: &'static str,
+        screen: Option<Screen>,
+        future: impl Future<Output = T> + Send + 'static,
+        done: impl FnOnce(&mut Self, T) + Send + 'static,
+    ) {
+        self.cancel(key);
+        self.start(key, screen, future, done);
+    }
 
-        match self.rt.block_on(async {
-            reqwest::Client::new()
-                .post(format!("{}/api/auth/login", self.api_url))
-                .json(&login_data)
-                .send()
-                .await?
-                .json::<LoginResponse>()
-                .await
-        }) {
-            Ok(response) => {
-                self.token = Some(response.token);
-                self.current_user = Some(response.user);
-                self.screen = Screen::Dashboard;
-                self.login_password.clear();
-                self.load_employees();
-                self.message = Some("✓ Angemeldet".to_string());
-            }
-            Err(_) => {
-                self.message = Some("✗ Login fehlgeschlagen".to_string());
-            }
-        }
+    /// Schickt eine Änderung ab; sie läuft auch nach einem Bildschirmwechsel
+    /// zu Ende.
+    fn submit<T: Send + 'static>(
+        &mut self,
+        key: &'static str,
+        future: impl Future<Output = T> + Send + 'static,
+        done: impl FnOnce(&mut Self, T) + Send + 'static,
+    ) {
+        self.start(key, None, future, done);
     }
 
-    fn logout(&mut self) {
-        self.token = None;
-        self.current_user = None;
-        self.screen = Screen::Login;
-        self.employees.clear();
-        self.users.clear();
-        self.message = Some("✓ Abgemeldet".to_string());
+    fn start<T: Send + 'static>(
+        &mut self,
+        key: &'static str,
+        screen: Option<Screen>,
+        future: impl Future<Output = T> + Send + 'static,
+        done: impl FnOnce(&mut Self, T) + Send + 'static,
+    ) {
+        self.next_request_id += 1;
+        let id = self.next_request_id;
+        let sender = self.completion_sender.clone();
+        let ctx = self.ctx.clone();
+        let task = self.rt.spawn(async move {
+            let result = future.await;
+            let completion: Completion = Box::new(move |app| done(app, result));
+            let _ = sender.send((id, completion));
+            ctx.request_repaint();
+        });
+        self.requests.push(PendingRequest { id, key, screen, task });
     }
 
-    fn load_employees(&mut self) {
-        if let Some(token) = &self.token {
-            match self.rt.block_on(async {
-                reqwest::Client::new()
-                    .get(format!("{}/api/employees", self.api_url))
-                    .header("Authorization", format!("Bearer {}", token))
-                    .send()
-                    .await?
-                    .json::<Vec<Employee>>()
-                    .await
-            }) {
-                Ok(employees) => self.employees = employees,
-                Err(_) => self.message = Some("✗ Fehler beim Laden".to_string()),
+    fn cancel(&mut self, key: &str) {
+        self.requests.retain(|request| {
+            if request.key == key {
+                request.task.abort();
             }
-        }
+            request.key != key
+        });
     }
 
-    fn add_employee(&mut self) {
-        if !self.new_name.is_empty() && !self.new_training.is_empty() && !self.new_duedate.is_empty() {
-            if let Some(token) = &self.token {
-                let create_data = CreateEmployee {
-                    name: self.new_name.clone(),
-                    training: self.new_training.clone(),
-                    duedate: self.new_duedate.clone(),
-                    status: self.new_status.to_api_string().to_string(),
-                };
-
-                match self.rt.block_on(async {
-                    reqwest::Client::new()
-                        .post(format!("{}/api/employees", self.api_url))
-                        .header("Authorization", format!("Bearer {}", token))
-                        .json(&create_data)
-                        .send()
-                        .await
-                }) {
-                    Ok(_) => {
-                        self.new_name.clear();
-                        self.new_training.clear();
-                        self.new_duedate.clear();
-                        self.new_status = Status::Gruen;
-                        self.load_employees();
-                        self.message = Some("✓ Hinzugefügt".to_string());
-                    }
-                    Err(_) => self.message = Some("✗ Fehler".to_string()),
-                }
-            }
-        }
+    fn is_loading(&self, key: &str) -> bool {
+        self.requests.iter().any(|request| request.key == key)
     }
 
-    fn delete_employee(&mut self, id: u32) {
-        if let Some(token) = &self.token {
-            match self.rt.block_on(async {
-                reqwest::Client::new()
-                    .delete(format!("{}/api/employees/{}", self.api_url, id))
-                    .header("Authorization", format!("Bearer {}", token))
-                    .send()
-                    .await
-            }) {
-                Ok(_) => {
-                    self.load_employees();
-                    self.message = Some("✓ Gelöscht".to_string());
-                }
-                Err(_) => self.message = Some("✗ Fehler".to_string()),
+    /// Übernimmt die Ergebnisse fertiger Anfragen in den Zustand.
+    fn poll_requests(&mut self) {
+        let finished: Vec<(u64, Completion)> = self.completion_receiver.try_iter().collect();
+        for (id, completion) in finished {
+            // Abgebrochene Anfragen sind nicht mehr eingetragen
+            if let Some(index) = self.requests.iter().position(|request| request.id == id) {
+                self.requests.remove(index);
+                completion(self);
             }
         }
     }
 
-    fn load_users(&mut self) {
-        if let Some(token) = &self.token {
-            match self.rt.block_on(async {
-                reqwest::Client::new()
-                    .get(format!("{}/api/users", self.api_url))
-                    .header("Authorization", format!("Bearer {}", token))
-                    .send()
-                    .await?
-                    .json::<Vec<User>>()
-                    .await
-            }) {
-                Ok(users) => self.users = users,
-                Err(_) => self.message = Some("✗ Fehler".to_string()),
+    fn navigate(&mut self, screen: Screen) {
+        self.requests.retain(|request| {
+            let stale = request.screen.is_some_and(|s| s != screen);
+            if stale {
+                request.task.abort();
             }
-        }
+            !stale
+        });
+        self.screen = screen;
     }
 
-    fn create_user(&mut self) {
-        if !self.new_user_username.is_empty() && !self.new_user_password.is_empty() {
-            if let Some(token) = &self.token {
-                let create_data = CreateUserRequest {
-                    username: self.new_user_username.clone(),
-                    password: self.new_user_password.clone(),
-                    role: self.new_user_role.clone(),
+    fn login(&mut self) {
+        let login_data = LoginRequest {
+            username: self.login_username.clone(),
+            password: self.login_password.clone(),
+        };
+
+        let request = self.client.post(format!("{}/api/auth/login", self.api_url)).json(&login_data);
+        self.submit(
+            "login",
+            async move { api_json::<LoginResponse>(request.send().await?).await },
+            |app, result| match result {
+                Ok(Ok(response)) => {
+                    app.token = Some(response.token);
+                    app.current_user = Some(response.user);
+                    app.navigate(Screen::Dashboard);
+                    app.login_password.clear();
+                    app.load_custom_fields();
+                    app.load_tags();
+                    app.load_employees();
+                    app.message = Some("✓ Angemeldet".to_string());
+                }
+                Ok(Err(error)) => app.message = Some(error.message()),
+                Err(error) => {
+                    app.message = Some(format!("✗ Login fehlgeschlagen: {}", error_chain(&error)));
+                }
+            },
+        );
+    }
+
+    fn open_connection_dialog(&mut self) {
+        let selected = self
+            .connection
+            .profiles
+            .iter()
+            .position(|p| p.name == self.connection.active)
+            .unwrap_or(0);
+        self.connection_dialog = Some(ConnectionDialog {
+            draft: self.connection.clone(),
+            selected,
+            error: None,
+            test: None,
+        });
+    }
+
+    /// Prüft das gewählte Profil mit einer Anfrage ohne Anmeldung – der
+    /// Server muss mit 401 und einer API-Fehlermeldung antworten.
+    fn test_connection(&mut self) {
+        let Some(dialog) = self.connection_dialog.as_mut() else {
+            return;
+        };
+        let profile = dialog.draft.profiles[dialog.selected].clone();
+        let client = match profile.validate().and_then(|()| build_client(&profile, Some(REQUEST_TIMEOUT))) {
+            Ok(client) => client,
+            Err(error) => {
+                dialog.test = Some(format!("✗ {}", error));
+                return;
+            }
+        };
+        dialog.test = None;
+
+        let request = client.get(format!("{}/api/auth/me", profile.base_url()));
+        self.fetch(
+            "connection_test",
+            None,
+            async move {
+                let response = request.send().await?;
+                let status = response.status();
+                Ok::<_, reqwest::Error>((status, response.json::<ApiError>().await.is_ok()))
+            },
+            |app, result| {
+                let text = match result {
+                    Ok((reqwest::StatusCode::UNAUTHORIZED, true)) => "✓ Server erreichbar".to_string(),
+                    Ok((status, _)) => format!("✗ Unerwartete Antwort ({}) – kein Training-Dashboard-Server?", status),
+                    Err(error) => format!("✗ {}", error_chain(&error)),
                 };
+                if let Some(dialog) = app.connection_dialog.as_mut() {
+                    dialog.test = Some(text);
+                }
+            },
+        );
+    }
 
-                match self.rt.block_on(async {
-                    reqwest::Client::new()
-                        .post(format!("{}/api/users", self.api_url))
-                        .header("Authorization", format!("Bearer {}", token))
-                        .json(&create_data)
-                        .send()
-                        .await
-                }) {
-                    Ok(_) => {
-                        self.new_user_username.clear();
-                        self.new_user_password.clear();
-                        self.load_users();
-                        self.message = Some("✓ Benutzer erstellt".to_string());
-                    }
-                    Err(_) => self.message = Some("✗ Fehler".to_string()),
+    fn pick_ca_cert(&mut self) {
+        self.submit(
+            "pick_ca",
+            async {
+                rfd::AsyncFileDialog::new()
+                    .add_filter("Zertifikate", &["pem", "crt", "cer"])
+                    .pick_file()
+                    .await
+                    .map(|file| file.path().display().to_string())
+            },
+            |app, path| {
+                if let (Some(path), Some(dialog)) = (path, app.connection_dialog.as_mut()) {
+                    dialog.draft.profiles[dialog.selected].ca_cert = path;
+                    dialog.test = None;
                 }
+            },
+        );
+    }
+
+    /// Übernimmt die Profile, macht das gewählte aktiv und speichert sie.
+    fn apply_connection(&mut self) {
+        let Some(dialog) = self.connection_dialog.as_mut() else {
+            return;
+        };
+        let mut names = std::collections::HashSet::new();
+        for profile in &mut dialog.draft.profiles {
+            profile.name = profile.name.trim().to_string();
+            profile.url = profile.base_url();
+            profile.ca_cert = profile.ca_cert.trim().to_string();
+        }
+        for profile in &dialog.draft.profiles {
+            if let Err(error) = profile.validate() {
+                dialog.error = Some(format!("{}: {}", profile.name, error));
+                return;
             }
+            if !names.insert(profile.name.as_str()) {
+                dialog.error = Some(format!("Name doppelt: {}", profile.name));
+                return;
+            }
+        }
+        let profile = &dialog.draft.profiles[dialog.selected];
+        let client = match build_client(profile, Some(REQUEST_TIMEOUT)) {
+            Ok(client) => client,
+            Err(error) => {
+                dialog.error = Some(error);
+                return;
+            }
+        };
+
+        dialog.draft.active = profile.name.clone();
+        let Some(dialog) = self.connection_dialog.take() else {
+            return;
+        };
+        self.connection = dialog.draft;
+        self.api_url = self.connection.active().base_url();
+        self.client = client;
+        self.message = match self.connection.save() {
+            Ok(()) => None,
+            Err(error) => Some(format!("✗ Verbindung nicht gespeichert: {}", error)),
+        };
+    }
+
+    fn logout(&mut self) {
+        // Ergebnisse laufender Anfragen gehören zur alten Sitzung
+        for request in self.requests.drain(..) {
+            request.task.abort();
         }
+        self.token = None;
+        self.current_user = None;
+        self.screen = Screen::Login;
+        self.employees.clear();
+        self.selected.clear();
+        self.users.clear();
+        self.webhooks.clear();
+        self.webhook_deliveries.clear();
+        self.catalog.clear();
+        self.custom_field_defs.clear();
+        self.tags.clear();
+        self.jobs.clear();
+        self.job_history.clear();
+        self.filter_tags.clear();
+        self.page = 1;
+        self.total_count = 0;
+        self.stats = EmployeeStats::default();
+        self.close_editor();
+        self.chain_person = None;
+        self.calendar_feed = None;
+        self.escalations.clear();
+        self.escalation_open = None;
+        self.live = None;
+        self.live_connected = false;
+        self.live_changed.clear();
+        self.live_pending = 0;
+        self.message = Some("✓ Abgemeldet".to_string());
     }
 
-    fn toggle_user_active(&mut self, user_id: String, active: bool) {
+    fn load_employees(&mut self) {
         if let Some(token) = &self.token {
-            let update_data = UpdateUserRequest {
-                active: Some(!active),
-                role: None,
+            let request = self
+                .client
+                .get(format!("{}/api/employees", self.api_url))
+                .header("Authorization", format!("Bearer {}", token))
+                .query(&self.list_query());
+            self.fetch(
+                "employees",
+                None,
+                async move {
+                    let response = request.send().await?;
+                    let total = response
+                        .headers()
+                        .get("X-Total-Count")
+                        .and_then(|v| v.to_str().ok())
+                        .and_then(|v| v.parse::<usize>().ok());
+                    let employees = response.json::<Vec<Employee>>().await?;
+                    Ok::<_, reqwest::Error>((employees, total))
+                },
+                |app, result| match result {
+                    Ok((employees, total)) => {
+                        app.total_count = total.unwrap_or(employees.len());
+                        // Auswahl bezieht sich auf den zuletzt angezeigten Stand
+                        for emp in &employees {
+                            if let Some(version) = app.selected.get_mut(&emp.id) {
+                                *version = emp.version;
+                            }
+                        }
+                        app.employees = employees;
+                        app.live_pending = 0;
+                        // Seite ist nach Löschen/Filtern evtl. leer geworden
+                        if app.employees.is_empty() && app.page > 1 {
+                            app.page = app.page_count();
+                            app.load_employees();
+                        }
+                    }
+                    Err(_) => app.message = Some("✗ Fehler beim Laden".to_string()),
+                },
+            );
+            self.load_stats();
+            self.load_escalations();
+        }
+    }
+
+    fn start_live(&mut self, ctx: &egui::Context) {
+        if let Some(token) = &self.token {
+            // Eigener Client ohne Gesamt-Timeout, der Stream bleibt dauerhaft offen
+            let Ok(client) = build_client(self.connection.active(), None) else {
+                return;
             };
+            let (sender, receiver) = mpsc::channel();
+            let url = format!("{}/api/events", self.api_url);
+            let task = self.rt.spawn(live_feed(client, url, token.clone(), sender, ctx.clone()));
+            self.live = Some(LiveFeed { receiver, task });
+        }
+    }
 
-            match self.rt.block_on(async {
-                reqwest::Client::new()
-                    .put(format!("{}/api/users/{}", self.api_url, user_id))
-                    .header("Authorization", format!("Bearer {}", token))
-                    .json(&update_data)
-                    .send()
-                    .await
-            }) {
-                Ok(_) => {
-                    self.load_users();
-                    self.message = Some("✓ Aktualisiert".to_string());
+    fn poll_live(&mut self, ctx: &egui::Context) {
+        let Some(live) = &self.live else {
+            return;
+        };
+        let messages: Vec<LiveMessage> = live.receiver.try_iter().collect();
+        let mut resync = false;
+        let mut employees_changed = false;
+        for message in messages {
+            match message {
+                LiveMessage::Connected => self.live_connected = true,
+                LiveMessage::Disconnected => self.live_connected = false,
+                LiveMessage::Resync => resync = true,
+                LiveMessage::Change(change) => employees_changed |= self.apply_live_change(change),
+            }
+        }
+        if resync {
+            self.load_employees();
+            if self.screen == Screen::AdminPanel {
+                self.load_users();
+            }
+        } else if employees_changed {
+            self.load_stats();
+            self.load_escalations();
+        }
+
+        self.live_changed.retain(|_, at| at.elapsed() < LIVE_HIGHLIGHT);
+        if !self.live_changed.is_empty() {
+            ctx.request_repaint_after(Duration::from_secs(1));
+        }
+    }
+
+    /// Übernimmt eine Änderung in die geladenen Listen; true, wenn Einträge
+    /// betroffen sind.
+    fn apply_live_change(&mut self, change: LiveChange) -> bool {
+        match change {
+            LiveChange::Employee { employee } => {
+                match self.employees.iter_mut().find(|e| e.id == employee.id) {
+                    // Eigene Änderungen sind nach dem Neuladen schon da
+                    Some(current) if current.version == employee.version => return false,
+                    Some(current) => {
+                        if let Some(version) = self.selected.get_mut(&employee.id) {
+                            *version = employee.version;
+                        }
+                        self.live_changed.insert(employee.id, Instant::now());
+                        *current = employee;
+                    }
+                    // Ob der Eintrag zu Filter und Seite passt, weiß nur der Server
+                    None => self.live_pending += 1,
                 }
-                Err(_) => self.message = Some("✗ Fehler".to_string()),
+            }
+            LiveChange::EmployeeDeleted { id } => {
+                let before = self.employees.len();
+                self.employees.retain(|e| e.id != id);
+                if self.employees.len() != before {
+                    self.selected.remove(&id);
+                    self.total_count = self.total_count.saturating_sub(1);
+                }
+            }
+            LiveChange::User { user } => {
+                match self.users.iter_mut().find(|u| u.id == user.id) {
+                    Some(current) => *current = user,
+                    None if self.screen == Screen::AdminPanel => self.users.push(user),
+                    None => {}
+                }
+                return false;
+            }
+            LiveChange::UserDeleted { id } => {
+                self.users.retain(|u| u.id != id);
+                return false;
             }
         }
+        true
     }
 
-    fn delete_user(&mut self, user_id: String) {
+    fn load_escalations(&mut self) {
         if let Some(token) = &self.token {
-            match self.rt.block_on(async {
-                reqwest::Client::new()
-                    .delete(format!("{}/api/users/{}", self.api_url, user_id))
-                    .header("Authorization", format!("Bearer {}", token))
-                    .send()
+            let request = self
+                .client
+                .get(format!("{}/api/escalations", self.api_url))
+                .header("Authorization", format!("Bearer {}", token));
+            self.fetch(
+                "escalations",
+                None,
+                async move { request.send().await?.json::<Vec<Escalation>>().await },
+                |app, result| match result {
+                    Ok(escalations) => {
+                        app.escalations = escalations.into_iter().map(|e| (e.employee_id, e)).collect();
+                    }
+                    Err(_) => app.message = Some("✗ Fehler beim Laden der Eskalationen".to_string()),
+                },
+            );
+        }
+    }
+
+    /// `action` ist "acknowledge" oder "clear".
+    fn annotate_escalation(&mut self, id: u32, action: &str) {
+        if let Some(token) = &self.token {
+            let url = api_url_with_segments(&self.api_url, &["api", "escalations", &id.to_string(), action]);
+            let request = self
+                .client
+                .post(url)
+                .header("Authorization", format!("Bearer {}", token))
+                .json(&CommentRequest { comment: self.escalation_comment.clone() });
+            self.submit(
+                "escalation",
+                async move { api_json::<Escalation>(request.send().await?).await },
+                move |app, result| match result {
+                    Ok(Ok(escalation)) => {
+                        app.escalations.insert(id, escalation);
+                        app.escalation_comment.clear();
+                        app.message = Some("✓ Gespeichert".to_string());
+                    }
+                    Ok(Err(error)) => app.message = Some(error.message()),
+                    Err(_) => app.message = Some("✗ Fehler".to_string()),
+                },
+            );
+        }
+    }
+
+    fn load_stats(&mut self) {
+        if let Some(token) = &self.token {
+            let request = self
+                .client
+                .get(format!("{}/api/employees/stats", self.api_url))
+                .header("Authorization", format!("Bearer {}", token));
+            self.fetch(
+                "stats",
+                None,
+                async move { request.send().await?.json::<EmployeeStats>().await },
+                |app, result| match result {
+                    Ok(stats) => app.stats = stats,
+                    Err(_) => app.message = Some("✗ Fehler beim Laden der Statistik".to_string()),
+                },
+            );
+        }
+    }
+
+    fn add_employee(&mut self) {
+        if self.new_multi {
+            self.add_employees_bulk();
+            return;
+        }
+        if let Some(token) = &self.token {
+            let create_data = CreateEmployee {
+                name: self.new_name.clone(),
+                training: self.new_training.clone(),
+                duedate: self.new_duedate.clone(),
+                status: self.new_status.to_api_string().to_string(),
+                custom_fields: self.new_custom_fields.clone(),
+                tags: self.new_tags.clone(),
+            };
+
+            let request = self
+                .client
+                .post(format!("{}/api/employees", self.api_url))
+                .header("Authorization", format!("Bearer {}", token))
+                .json(&create_data);
+            self.submit(
+                "add_employee",
+                async move { api_json::<CreateEmployeeResponse>(request.send().await?).await },
+                |app, result| match result {
+                    Ok(Ok(created)) => {
+                        app.clear_new_form();
+                        app.load_employees();
+                        app.message = Some(if created.warnings.is_empty() {
+                            "✓ Hinzugefügt".to_string()
+                        } else {
+                            format!("✓ Hinzugefügt, Voraussetzungen fehlen: {}", created.warnings.join(", "))
+                        });
+                    }
+                    Ok(Err(error)) => {
+                        app.message = Some(error.message());
+                        app.new_errors = error.fields;
+                    }
+                    Err(_) => app.message = Some("✗ Fehler".to_string()),
+                },
+            );
+        }
+    }
+
+    fn start_editing(&mut self, employee: Employee) {
+        self.edit_original = Some(employee.clone());
+        self.editing = Some(employee);
+        self.edit_conflict = None;
+        self.edit_errors.clear();
+    }
+
+    fn close_editor(&mut self) {
+        self.editing = None;
+        self.edit_original = None;
+        self.edit_conflict = None;
+        self.edit_errors.clear();
+    }
+
+    fn save_edited_employee(&mut self) {
+        let (Some(token), Some(original), Some(edited)) = (&self.token, &self.edit_original, &self.editing) else {
+            return;
+        };
+
+        let patch = EmployeePatch::diff(original, edited);
+        if patch.is_empty() {
+            self.close_editor();
+            return;
+        }
+
+        let request = self
+            .client
+            .patch(format!("{}/api/employees/{}", self.api_url, original.id))
+            .header("Authorization", format!("Bearer {}", token))
+            .header("If-Match", format!("\"{}\"", original.version))
+            .json(&patch);
+        self.submit(
+            "save_employee",
+            async move { api_result(request.send().await?).await },
+            |app, result| match result {
+                Ok(Ok(())) => {
+                    app.close_editor();
+                    app.load_employees();
+                    app.message = Some("✓ Gespeichert".to_string());
+                }
+                Ok(Err(error)) if error.code == "version_conflict" && error.current.is_some() => {
+                    app.message = Some(error.message());
+                    app.edit_conflict = error.current;
+                }
+                Ok(Err(error)) => {
+                    app.message = Some(error.message());
+                    app.edit_errors = error.fields;
+                }
+                Err(_) => app.message = Some("✗ Fehler".to_string()),
+            },
+        );
+    }
+
+    fn clear_new_form(&mut self) {
+        self.new_name.clear();
+        self.new_training.clear();
+        self.new_duedate.clear();
+        self.new_status = Status::Gruen;
+        self.new_custom_fields.clear();
+        self.new_tags.clear();
+        self.new_errors.clear();
+    }
+
+    fn add_employees_bulk(&mut self) {
+        let Some(token) = &self.token else {
+            return;
+        };
+
+        let template = CreateEmployee {
+            name: String::new(),
+            training: self.new_training.clone(),
+            duedate: self.new_duedate.clone(),
+            status: self.new_status.to_api_string().to_string(),
+            custom_fields: self.new_custom_fields.clone(),
+            tags: self.new_tags.clone(),
+        };
+        let items: Vec<CreateEmployee> = self
+            .new_name
+            .lines()
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(|name| CreateEmployee { name: name.to_string(), ..template.clone() })
+            .collect();
+
+        let request = self
+            .client
+            .post(format!("{}/api/employees/bulk", self.api_url))
+            .header("Authorization", format!("Bearer {}", token))
+            .json(&BulkCreateRequest { items });
+        self.submit(
+            "add_employee",
+            async move { api_json::<BulkResponse>(request.send().await?).await },
+            |app, result| match result {
+                Ok(Ok(created)) => {
+                    app.clear_new_form();
+                    app.load_employees();
+                    let warnings = created.items.iter().filter(|item| !item.missing.is_empty()).count();
+                    app.message = Some(if warnings == 0 {
+                        format!("✓ {} Einträge angelegt", created.items.len())
+                    } else {
+                        format!("✓ {} Einträge angelegt, bei {} fehlen Voraussetzungen", created.items.len(), warnings)
+                    });
+                }
+                Ok(Err(error)) => {
+                    app.message = Some(error.message());
+                    // Bis auf den Namen teilen sich alle Zeilen dieselben Eingaben
+                    app.new_errors = error
+                        .items
+                        .into_iter()
+                        .find(|item| !item.ok)
+                        .map(|item| item.fields)
+                        .unwrap_or(error.fields);
+                }
+                Err(_) => app.message = Some("✗ Fehler".to_string()),
+            },
+        );
+    }
+
+    fn bulk_update(&mut self, changes: BulkChanges) {
+        let Some(token) = &self.token else {
+            return;
+        };
+
+        let items = self
+            .selected
+            .iter()
+            .map(|(&id, &version)| BulkTarget { id, version })
+            .collect();
+        let count = self.selected.len();
+
+        let request = self
+            .client
+            .patch(format!("{}/api/employees/bulk", self.api_url))
+            .header("Authorization", format!("Bearer {}", token))
+            .json(&BulkUpdateRequest { items, changes });
+        self.submit(
+            "bulk",
+            async move { api_result(request.send().await?).await },
+            move |app, result| match result {
+                Ok(Ok(())) => {
+                    app.message = Some(format!("✓ {} Einträge geändert", count));
+                    app.selected.clear();
+                    app.load_employees();
+                }
+                Ok(Err(error)) => app.message = Some(error.message()),
+                Err(_) => app.message = Some("✗ Fehler".to_string()),
+            },
+        );
+    }
+
+    fn bulk_delete(&mut self) {
+        let Some(token) = &self.token else {
+            return;
+        };
+
+        let ids = self.selected.keys().copied().collect();
+        let count = self.selected.len();
+        let request = self
+            .client
+            .delete(format!("{}/api/employees/bulk", self.api_url))
+            .header("Authorization", format!("Bearer {}", token))
+            .json(&BulkDeleteRequest { ids });
+        self.submit(
+            "bulk",
+            async move { api_result(request.send().await?).await },
+            move |app, result| match result {
+                Ok(Ok(())) => {
+                    app.message = Some(format!("✓ {} Einträge gelöscht", count));
+                    app.selected.clear();
+                    app.load_employees();
+                }
+                Ok(Err(error)) => app.message = Some(error.message()),
+                Err(_) => app.message = Some("✗ Fehler".to_string()),
+            },
+        );
+    }
+
+    fn pick_import_file(&mut self) {
+        self.submit(
+            "pick_file",
+            async {
+                let file = rfd::AsyncFileDialog::new()
+                    .add_filter("Tabellen", &["csv", "xlsx"])
+                    .pick_file()
+                    .await?;
+                Some((file.file_name(), file.read().await))
+            },
+            |app, file| {
+                let Some((file_name, bytes)) = file else {
+                    return;
+                };
+
+                app.import_format = if file_name.to_lowercase().ends_with(".xlsx") { "xlsx" } else { "csv" }.to_string();
+                app.import_data = base64::engine::general_purpose::STANDARD.encode(bytes);
+                app.import_file_name = Some(file_name);
+                // Neue Datei: Zuordnung wieder automatisch anhand der Überschriften
+                app.import_mapping.clear();
+                app.import_report = None;
+                app.run_import(true);
+            },
+        );
+    }
+
+    fn run_import(&mut self, dry_run: bool) {
+        let Some(token) = &self.token else {
+            return;
+        };
+
+        let import = ImportRequest {
+            format: self.import_format.clone(),
+            data: self.import_data.clone(),
+            mapping: self.import_mapping.clone(),
+            dry_run,
+            skip_errors: self.import_skip_errors,
+        };
+
+        let request = self
+            .client
+            .post(format!("{}/api/employees/import", self.api_url))
+            .header("Authorization", format!("Bearer {}", token))
+            .timeout(LONG_REQUEST_TIMEOUT)
+            .json(&import);
+        let future = async move { api_json::<ImportReport>(request.send().await?).await };
+        let done = |app: &mut Self, result: reqwest::Result<Result<ImportReport, ApiError>>| match result {
+            Ok(Ok(report)) if report.committed => {
+                app.message = Some(format!(
+                    "✓ Import abgeschlossen: {} neu, {} geändert",
+                    report.summary.create, report.summary.update
+                ));
+                app.import_file_name = None;
+                app.import_data.clear();
+                app.import_mapping.clear();
+                app.import_report = None;
+                app.import_errors.clear();
+                app.load_employees();
+            }
+            Ok(Ok(report)) => {
+                app.message = None;
+                app.import_mapping = report.mapping.clone();
+                app.import_report = Some(report);
+                app.import_errors.clear();
+            }
+            Ok(Err(error)) => {
+                app.message = Some(error.message());
+                app.import_errors = error.fields;
+            }
+            Err(_) => app.message = Some("✗ Fehler".to_string()),
+        };
+        // Die Vorschau ändert nichts und darf beim Verlassen abbrechen
+        if dry_run {
+            self.fetch("import_preview", Some(Screen::Import), future, done);
+        } else {
+            self.cancel("import_preview");
+            self.submit("import", future, done);
+        }
+    }
+
+    fn export_employees(&mut self) {
+        let mut query = self.filter_query();
+        query.push(("format", self.export_format.to_string()));
+        self.download(
+            format!("{}/api/employees/export", self.api_url),
+            query,
+            format!("schulungen.{}", self.export_format),
+            self.export_format,
+        );
+    }
+
+    fn download_compliance_report(&mut self) {
+        let mut query = self.filter_query();
+        if !self.report_group_by.is_empty() {
+            query.push(("group_by", self.report_group_by.clone()));
+        }
+        self.download(format!("{}/api/reports/compliance", self.api_url), query, "compliance.pdf".to_string(), "pdf");
+    }
+
+    fn download_person_report(&mut self, name: &str) {
+        let url = api_url_with_segments(&self.api_url, &["api", "reports", "persons", name]);
+        self.download(url, Vec::new(), "schulungsnachweis.pdf".to_string(), "pdf");
+    }
+
+    fn download_certificate(&mut self, id: u32) {
+        let url = format!("{}/api/reports/certificates/{}", self.api_url, id);
+        self.download(url, Vec::new(), "bescheinigung.pdf".to_string(), "pdf");
+    }
+
+    /// Lädt eine Datei vom Server und fragt nach dem Speicherort.
+    fn download(&mut self, url: String, query: Vec<(&str, String)>, fallback_name: String, extension: &'static str) {
+        let Some(token) = &self.token else {
+            return;
+        };
+
+        let request = self
+            .client
+            .get(url)
+            .header("Authorization", format!("Bearer {}", token))
+            .timeout(LONG_REQUEST_TIMEOUT)
+            .query(&query);
+        self.submit(
+            "download",
+            async move {
+                let response = request.send().await?;
+                if !response.status().is_success() {
+                    return Ok::<_, reqwest::Error>(Err(ApiError::from_response(response).await?));
+                }
+                // Dateiname schlägt der Server vor (z.B. schulungen-<datum>.<format>)
+                let file_name = response
+                    .headers()
+                    .get("Content-Disposition")
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.split("filename=").nth(1))
+                    .map(|v| v.trim_matches('"').to_string())
+                    .unwrap_or(fallback_name);
+                let body = response.bytes().await?;
+
+                let Some(file) = rfd::AsyncFileDialog::new()
+                    .set_file_name(file_name)
+                    .add_filter(extension, &[extension])
+                    .save_file()
                     .await
-            }) {
-                Ok(_) => {
-                    self.load_users();
-                    self.message = Some("✓ Gelöscht".to_string());
+                else {
+                    return Ok(Ok(None));
+                };
+                Ok(Ok(Some((file.write(&body).await, file.file_name()))))
+            },
+            |app, result| match result {
+                Ok(Ok(Some((Ok(()), file_name)))) => app.message = Some(format!("✓ Gespeichert als {}", file_name)),
+                Ok(Ok(Some((Err(_), _)))) => app.message = Some("✗ Datei konnte nicht gespeichert werden".to_string()),
+                Ok(Ok(None)) => {}
+                Ok(Err(error)) => app.message = Some(error.message()),
+                Err(_) => app.message = Some("✗ Fehler".to_string()),
+            },
+        );
+    }
+
+    /// Holt die Abo-Adresse; mit `reset` wird eine neue erzeugt und die
+    /// alte ungültig.
+    fn load_calendar_feed(&mut self, reset: bool) {
+        let Some(token) = &self.token else {
+            return;
+        };
+        let url = format!("{}/api/calendar/feed", self.api_url);
+
+        let request = if reset { self.client.post(url) } else { self.client.get(url) }
+            .header("Authorization", format!("Bearer {}", token));
+        let future = async move { api_json::<CalendarFeed>(request.send().await?).await };
+        let done = move |app: &mut Self, result: reqwest::Result<Result<CalendarFeed, ApiError>>| match result {
+            Ok(Ok(feed)) => {
+                if reset {
+                    app.message = Some("✓ Neue Kalender-Adresse erzeugt".to_string());
                 }
-                Err(_) => self.message = Some("✗ Fehler".to_string()),
+                app.calendar_feed = Some(feed);
             }
+            Ok(Err(error)) => app.message = Some(error.message()),
+            Err(_) => app.message = Some("✗ Fehler".to_string()),
+        };
+        if reset {
+            self.submit("calendar_feed_reset", future, done);
+        } else {
+            self.fetch("calendar_feed", None, future, done);
         }
     }
 
-    fn filtered_employees(&self) -> Vec<Employee> {
-        let mut filtered = self.employees.clone();
+    fn delete_employee(&mut self, id: u32, version: u64) {
+        if let Some(token) = &self.token {
+            // Zwischenzeitlich geänderte Einträge nicht ungesehen löschen
+            let request = self
+                .client
+                .delete(format!("{}/api/employees/{}", self.api_url, id))
+                .header("Authorization", format!("Bearer {}", token))
+                .header("If-Match", format!("\"{}\"", version));
+            self.submit(
+                "delete_employee",
+                async move { api_result(request.send().await?).await },
+                move |app, result| match result {
+                    Ok(Ok(())) => {
+                        app.selected.remove(&id);
+                        app.load_employees();
+                        app.message = Some("✓ Gelöscht".to_string());
+                    }
+                    Ok(Err(error)) => app.message = Some(error.message()),
+                    Err(_) => app.message = Some("✗ Fehler".to_string()),
+                },
+            );
+        }
+    }
 
-        if self.filter_critical {
-            filtered.retain(|e| e.status != Status::Gruen);
+    fn load_users(&mut self) {
+        if let Some(token) = &self.token {
+            let request = self
+                .client
+                .get(format!("{}/api/users", self.api_url))
+                .header("Authorization", format!("Bearer {}", token));
+            self.fetch(
+                "users",
+                Some(Screen::AdminPanel),
+                async move { request.send().await?.json::<Vec<User>>().await },
+                |app, result| match result {
+                    Ok(users) => app.users = users,
+                    Err(_) => app.message = Some("✗ Fehler".to_string()),
+                },
+            );
         }
+    }
 
-        if !self.search_term.is_empty() {
-            let term = self.search_term.to_lowercase();
-            filtered.retain(|e| {
-                e.name.to_lowercase().contains(&term)
-                    || e.training.to_lowercase().contains(&term)
-                    || e.id.to_string().contains(&term)
-            });
+    fn load_webhooks(&mut self) {
+        if let Some(token) = &self.token {
+            let webhooks = self
+                .client
+                .get(format!("{}/api/webhooks", self.api_url))
+                .header("Authorization", format!("Bearer {}", token));
+            let deliveries = self
+                .client
+                .get(format!("{}/api/webhooks/deliveries", self.api_url))
+                .header("Authorization", format!("Bearer {}", token))
+                .query(&[("limit", "50")]);
+            self.fetch(
+                "webhooks",
+                Some(Screen::AdminPanel),
+                async move {
+                    tokio::try_join!(
+                        async { webhooks.send().await?.json::<Vec<Webhook>>().await },
+                        async { deliveries.send().await?.json::<Vec<WebhookDelivery>>().await },
+                    )
+                },
+                |app, result| match result {
+                    Ok((webhooks, deliveries)) => {
+                        app.webhooks = webhooks;
+                        app.webhook_deliveries = deliveries;
+                    }
+                    Err(_) => app.message = Some("✗ Fehler beim Laden der Webhooks".to_string()),
+                },
+            );
         }
+    }
 
-        filtered
+    fn reset_webhook_form(&mut self) {
+        self.webhook_editing = None;
+        self.webhook_name.clear();
+        self.webhook_url.clear();
+        self.webhook_secret.clear();
+        self.webhook_events.clear();
     }
 
-    fn stats(&self) -> (usize, usize, usize, usize) {
-        let total = self.employees.len();
-        let green = self.employees.iter().filter(|e| e.status == Status::Gruen).count();
-        let yellow = self.employees.iter().filter(|e| e.status == Status::Gelb).count();
-        let red = self.employees.iter().filter(|e| e.status == Status::Rot).count();
-        (total, green, yellow, red)
+    fn save_webhook(&mut self) {
+        let active = match &self.webhook_editing {
+            Some(id) => self.webhooks.iter().find(|w| &w.id == id).map(|w| w.active).unwrap_or(true),
+            None => true,
+        };
+        let input = WebhookInput {
+            name: self.webhook_name.clone(),
+            url: self.webhook_url.clone(),
+            secret: (!self.webhook_secret.is_empty()).then(|| self.webhook_secret.clone()),
+            events: self.webhook_events.iter().cloned().collect(),
+            active,
+        };
+        self.send_webhook(self.webhook_editing.clone(), input, true);
     }
 
-    fn is_admin(&self) -> bool {
-        self.current_user.as_ref().map(|u| u.role == Role::Admin).unwrap_or(false)
+    fn toggle_webhook(&mut self, webhook: &Webhook) {
+        let input = WebhookInput {
+            name: webhook.name.clone(),
+            url: webhook.url.clone(),
+            secret: None,
+            events: webhook.events.clone(),
+            active: !webhook.active,
+        };
+        self.send_webhook(Some(webhook.id.clone()), input, false);
     }
 
-    fn is_manager_or_admin(&self) -> bool {
-        self.current_user.as_ref().map(|u| {
-            u.role == Role::Admin || u.role == Role::Manager
-        }).unwrap_or(false)
+    fn send_webhook(&mut self, id: Option<String>, input: WebhookInput, reset_form: bool) {
+        if let Some(token) = &self.token {
+            let request = match &id {
+                Some(id) => self.client.put(api_url_with_segments(&self.api_url, &["api", "webhooks", id])),
+                None => self.client.post(format!("{}/api/webhooks", self.api_url)),
+            }
+            .header("Authorization", format!("Bearer {}", token))
+            .json(&input);
+            self.submit(
+                "save_webhook",
+                async move { api_result(request.send().await?).await },
+                move |app, result| match result {
+                    Ok(Ok(())) => {
+                        if reset_form {
+                            app.reset_webhook_form();
+                        }
+                        app.load_webhooks();
+                        app.message = Some("✓ Gespeichert".to_string());
+                    }
+                    Ok(Err(error)) => app.message = Some(error.message()),
+                    Err(_) => app.message = Some("✗ Fehler".to_string()),
+                },
+            );
+        }
     }
-}
 
-impl eframe::App for TrainingDashboard {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        match self.screen {
-            Screen::Login => self.render_login(ctx),
-            Screen::Dashboard => self.render_dashboard(ctx),
-            Screen::AdminPanel => self.render_admin_panel(ctx),
+    fn delete_webhook(&mut self, id: &str) {
+        if let Some(token) = &self.token {
+            let url = api_url_with_segments(&self.api_url, &["api", "webhooks", id]);
+            let request = self.client.delete(url).header("Authorization", format!("Bearer {}", token));
+            let id = id.to_string();
+            self.submit(
+                "delete_webhook",
+                async move { api_result(request.send().await?).await },
+                move |app, result| match result {
+                    Ok(Ok(())) => {
+                        if app.webhook_editing.as_deref() == Some(id.as_str()) {
+                            app.reset_webhook_form();
+                        }
+                        app.load_webhooks();
+                        app.message = Some("✓ Gelöscht".to_string());
+                    }
+                    Ok(Err(error)) => app.message = Some(error.message()),
+                    Err(_) => app.message = Some("✗ Fehler".to_string()),
+                },
+            );
         }
     }
-}
 
-impl TrainingDashboard {
-    fn render_login(&mut self, ctx: &egui::Context) {
-        egui::CentralPanel::default().show(ctx, |ui| {
-            ui.vertical_centered(|ui| {
-                ui.add_space(100.0);
-                
-                ui.heading(egui::RichText::new("🎓 Training Dashboard").size(32.0));
-                ui.label("Bitte anmelden");
-                
-                ui.add_space(30.0);
-                
-                egui::Frame::none()
-                    .fill(egui::Color32::from_gray(30))
-                    .rounding(10.0)
-                    .inner_margin(30.0)
-                    .show(ui, |ui| {
-                        ui.set_max_width(400.0);
-                        
-                        ui.label("Benutzername:");
-                        ui.text_edit_singleline(&mut self.login_username);
-                        ui.add_space(10.0);
-                        
-                        ui.label("Passwort:");
-                        let pwd = ui.add(egui::TextEdit::singleline(&mut self.login_password).password(true));
-                        ui.add_space(20.0);
-                        
-                        if ui.add_sized([ui.available_width(), 40.0], egui::Button::new("Anmelden")).clicked() 
-                            || (pwd.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter))) {
-                            self.login();
+    fn test_webhook(&mut self, id: &str) {
+        if let Some(token) = &self.token {
+            let url = api_url_with_segments(&self.api_url, &["api", "webhooks", id, "test"]);
+            let request = self.client.post(url).header("Authorization", format!("Bearer {}", token));
+            self.submit(
+                "test_webhook",
+                async move { api_json::<WebhookDelivery>(request.send().await?).await },
+                |app, result| match result {
+                    Ok(Ok(delivery)) => {
+                        app.message = Some(if delivery.ok {
+                            format!("✓ Testzustellung erfolgreich ({} ms)", delivery.duration_ms)
+                        } else {
+                            format!("✗ Testzustellung fehlgeschlagen: {}", delivery.error.unwrap_or_default())
+                        });
+                        app.load_webhooks();
+                    }
+                    Ok(Err(error)) => app.message = Some(error.message()),
+                    Err(_) => app.message = Some("✗ Fehler".to_string()),
+                },
+            );
+        }
+    }
+
+    fn create_user(&mut self) {
+        if !self.new_user_username.is_empty() && !self.new_user_password.is_empty() {
+            if let Some(token) = &self.token {
+                let create_data = CreateUserRequest {
+                    username: self.new_user_username.clone(),
+                    password: self.new_user_password.clone(),
+                    role: self.new_user_role.clone(),
+                };
+
+                let request = self
+                    .client
+                    .post(format!("{}/api/users", self.api_url))
+                    .header("Authorization", format!("Bearer {}", token))
+                    .json(&create_data);
+                self.submit(
+                    "create_user",
+                    async move { api_result(request.send().await?).await },
+                    |app, result| match result {
+                        Ok(Ok(())) => {
+                            app.new_user_username.clear();
+                            app.new_user_password.clear();
+                            app.load_users();
+                            app.message = Some("✓ Benutzer erstellt".to_string());
+                        }
+                        Ok(Err(error)) => app.message = Some(error.message()),
+                        Err(_) => app.message = Some("✗ Fehler".to_string()),
+                    },
+                );
+            }
+        }
+    }
+
+    fn toggle_user_active(&mut self, user_id: String, active: bool) {
+        if let Some(token) = &self.token {
+            let update_data = UpdateUserRequest {
+                active: Some(!active),
+                role: None,
+            };
+
+            let request = self
+                .client
+                .put(format!("{}/api/users/{}", self.api_url, user_id))
+                .header("Authorization", format!("Bearer {}", token))
+                .json(&update_data);
+            self.submit(
+                "update_user",
+                async move { api_result(request.send().await?).await },
+                |app, result| match result {
+                    Ok(Ok(())) => {
+                        app.load_users();
+                        app.message = Some("✓ Aktualisiert".to_string());
+                    }
+                    Ok(Err(error)) => app.message = Some(error.message()),
+                    Err(_) => app.message = Some("✗ Fehler".to_string()),
+                },
+            );
+        }
+    }
+
+    fn delete_user(&mut self, user_id: String) {
+        if let Some(token) = &self.token {
+            let request = self
+                .client
+                .delete(format!("{}/api/users/{}", self.api_url, user_id))
+                .header("Authorization", format!("Bearer {}", token));
+            self.submit(
+                "delete_user",
+                async move { api_result(request.send().await?).await },
+                |app, result| match result {
+                    Ok(Ok(())) => {
+                        app.load_users();
+                        app.message = Some("✓ Gelöscht".to_string());
+                    }
+                    Ok(Err(error)) => app.message = Some(error.message()),
+                    Err(_) => app.message = Some("✗ Fehler".to_string()),
+                },
+            );
+        }
+    }
+
+    fn load_catalog(&mut self) {
+        if let Some(token) = &self.token {
+            let request = self
+                .client
+                .get(format!("{}/api/catalog", self.api_url))
+                .header("Authorization", format!("Bearer {}", token));
+            self.fetch(
+                "catalog",
+                Some(Screen::Catalog),
+                async move { request.send().await?.json::<Vec<CatalogEntry>>().await },
+                |app, result| match result {
+                    Ok(catalog) => app.catalog = catalog,
+                    Err(_) => app.message = Some("✗ Fehler beim Laden des Katalogs".to_string()),
+                },
+            );
+        }
+    }
+
+    fn save_catalog_entry(&mut self) {
+        if !self.new_catalog_name.is_empty() {
+            if let Some(token) = &self.token {
+                let entry = CatalogEntry {
+                    name: self.new_catalog_name.clone(),
+                    prerequisites: self
+                        .new_catalog_prerequisites
+                        .split(',')
+                        .map(|p| p.trim().to_string())
+                        .filter(|p| !p.is_empty())
+                        .collect(),
+                    strict: self.new_catalog_strict,
+                };
+
+                let request = self
+                    .client
+                    .post(format!("{}/api/catalog", self.api_url))
+                    .header("Authorization", format!("Bearer {}", token))
+                    .json(&entry);
+                self.submit(
+                    "save_catalog",
+                    async move { api_result(request.send().await?).await },
+                    |app, result| match result {
+                        Ok(Ok(())) => {
+                            app.new_catalog_name.clear();
+                            app.new_catalog_prerequisites.clear();
+                            app.new_catalog_strict = false;
+                            app.load_catalog();
+                            app.message = Some("✓ Gespeichert".to_string());
+                        }
+                        Ok(Err(error)) => app.message = Some(error.message()),
+                        Err(_) => app.message = Some("✗ Fehler".to_string()),
+                    },
+                );
+            }
+        }
+    }
+
+    fn delete_catalog_entry(&mut self, name: &str) {
+        if let Some(token) = &self.token {
+            let url = api_url_with_segments(&self.api_url, &["api", "catalog", name]);
+            let request = self.client.delete(url).header("Authorization", format!("Bearer {}", token));
+            self.submit(
+                "delete_catalog",
+                async move { api_result(request.send().await?).await },
+                |app, result| match result {
+                    Ok(Ok(())) => {
+                        app.load_catalog();
+                        app.message = Some("✓ Gelöscht".to_string());
+                    }
+                    Ok(Err(error)) => app.message = Some(error.message()),
+                    Err(_) => app.message = Some("✗ Fehler".to_string()),
+                },
+            );
+        }
+    }
+
+    fn load_prerequisite_chain(&mut self, person: String) {
+        if let Some(token) = &self.token {
+            let url = api_url_with_segments(&self.api_url, &["api", "persons", &person, "prerequisites"]);
+            let request = self.client.get(url).header("Authorization", format!("Bearer {}", token));
+            self.fetch(
+                "chain",
+                None,
+                async move { request.send().await?.json::<Vec<PrerequisiteNode>>().await },
+                move |app, result| match result {
+                    Ok(chain) => {
+                        app.chain = chain;
+                        app.chain_person = Some(person);
+                    }
+                    Err(_) => app.message = Some("✗ Fehler beim Laden der Voraussetzungen".to_string()),
+                },
+            );
+        }
+    }
+
+    fn load_custom_fields(&mut self) {
+        if let Some(token) = &self.token {
+            let request = self
+                .client
+                .get(format!("{}/api/custom-fields", self.api_url))
+                .header("Authorization", format!("Bearer {}", token));
+            self.fetch(
+                "custom_fields",
+                None,
+                async move { request.send().await?.json::<Vec<CustomFieldDef>>().await },
+                |app, result| match result {
+                    Ok(defs) => app.custom_field_defs = defs,
+                    Err(_) => app.message = Some("✗ Fehler beim Laden der Zusatzfelder".to_string()),
+                },
+            );
+        }
+    }
+
+    fn save_custom_field(&mut self) {
+        if !self.new_field_name.is_empty() {
+            if let Some(token) = &self.token {
+                let def = CustomFieldDef {
+                    name: self.new_field_name.clone(),
+                    label: self.new_field_label.clone(),
+                    kind: self.new_field_kind,
+                    options: self
+                        .new_field_options
+                        .split(',')
+                        .map(|o| o.trim().to_string())
+                        .filter(|o| !o.is_empty())
+                        .collect(),
+                    required: self.new_field_required,
+                };
+
+                let request = self
+                    .client
+                    .post(format!("{}/api/custom-fields", self.api_url))
+                    .header("Authorization", format!("Bearer {}", token))
+                    .json(&def);
+                self.submit(
+                    "save_custom_field",
+                    async move { api_result(request.send().await?).await },
+                    |app, result| match result {
+                        Ok(Ok(())) => {
+                            app.new_field_name.clear();
+                            app.new_field_label.clear();
+                            app.new_field_kind = FieldKind::Text;
+                            app.new_field_options.clear();
+                            app.new_field_required = false;
+                            app.load_custom_fields();
+                            app.message = Some("✓ Gespeichert".to_string());
+                        }
+                        Ok(Err(error)) => app.message = Some(error.message()),
+                        Err(_) => app.message = Some("✗ Fehler".to_string()),
+                    },
+                );
+            }
+        }
+    }
+
+    fn delete_custom_field(&mut self, name: &str) {
+        if let Some(token) = &self.token {
+            let url = api_url_with_segments(&self.api_url, &["api", "custom-fields", name]);
+            let request = self.client.delete(url).header("Authorization", format!("Bearer {}", token));
+            self.submit(
+                "delete_custom_field",
+                async move { api_result(request.send().await?).await },
+                |app, result| match result {
+                    Ok(Ok(())) => {
+                        app.load_custom_fields();
+                        app.load_employees();
+                        app.message = Some("✓ Gelöscht".to_string());
+                    }
+                    Ok(Err(error)) => app.message = Some(error.message()),
+                    Err(_) => app.message = Some("✗ Fehler".to_string()),
+                },
+            );
+        }
+    }
+
+    fn load_jobs(&mut self) {
+        if let Some(token) = &self.token {
+            let jobs = self
+                .client
+                .get(format!("{}/api/jobs", self.api_url))
+                .header("Authorization", format!("Bearer {}", token));
+            let history = self
+                .client
+                .get(format!("{}/api/jobs/history", self.api_url))
+                .header("Authorization", format!("Bearer {}", token))
+                .query(&[("limit", "50")]);
+            self.fetch(
+                "jobs",
+                Some(Screen::Jobs),
+                async move {
+                    tokio::try_join!(
+                        async { jobs.send().await?.json::<Vec<JobInfo>>().await },
+                        async { history.send().await?.json::<Vec<JobRun>>().await },
+                    )
+                },
+                |app, result| match result {
+                    Ok((jobs, history)) => {
+                        app.jobs = jobs;
+                        app.job_history = history;
+                    }
+                    Err(_) => app.message = Some("✗ Fehler beim Laden der Jobs".to_string()),
+                },
+            );
+        }
+    }
+
+    fn save_job(&mut self, index: usize) {
+        let Some(job) = self.jobs.get(index) else {
+            return;
+        };
+        if let Some(token) = &self.token {
+            let url = api_url_with_segments(&self.api_url, &["api", "jobs", &job.job]);
+            let update = JobUpdate { schedule: job.schedule.clone(), enabled: job.enabled };
+            let request = self
+                .client
+                .put(url)
+                .header("Authorization", format!("Bearer {}", token))
+                .json(&update);
+            self.submit(
+                "save_job",
+                async move { api_result(request.send().await?).await },
+                |app, result| match result {
+                    Ok(Ok(())) => {
+                        app.load_jobs();
+                        app.message = Some("✓ Gespeichert".to_string());
+                    }
+                    Ok(Err(error)) => app.message = Some(error.message()),
+                    Err(_) => app.message = Some("✗ Fehler".to_string()),
+                },
+            );
+        }
+    }
+
+    fn run_job(&mut self, job: &str) {
+        if let Some(token) = &self.token {
+            let url = api_url_with_segments(&self.api_url, &["api", "jobs", job, "run"]);
+            // Der Server antwortet erst, wenn der Job fertig ist – bis dahin
+            // zeigt die Liste ihn als laufend
+            if let Some(info) = self.jobs.iter_mut().find(|j| j.job == job) {
+                info.running = true;
+            }
+            let request = self
+                .client
+                .post(url)
+                .header("Authorization", format!("Bearer {}", token))
+                .timeout(LONG_REQUEST_TIMEOUT);
+            self.submit(
+                "run_job",
+                async move { api_json::<JobRun>(request.send().await?).await },
+                |app, result| {
+                    app.load_jobs();
+                    app.message = Some(match result {
+                        Ok(Ok(run)) if run.ok => format!("✓ {}", run.message),
+                        Ok(Ok(run)) => format!("✗ {}", run.message),
+                        Ok(Err(error)) => error.message(),
+                        Err(_) => "✗ Fehler".to_string(),
+                    });
+                },
+            );
+        }
+    }
+
+    fn load_tags(&mut self) {
+        if let Some(token) = &self.token {
+            let request = self
+                .client
+                .get(format!("{}/api/tags", self.api_url))
+                .header("Authorization", format!("Bearer {}", token));
+            self.fetch(
+                "tags",
+                None,
+                async move { request.send().await?.json::<Vec<String>>().await },
+                |app, result| match result {
+                    Ok(tags) => {
+                        app.filter_tags.retain(|t| tags.contains(t));
+                        app.tags = tags;
+                    }
+                    Err(_) => app.message = Some("✗ Fehler beim Laden der Tags".to_string()),
+                },
+            );
+        }
+    }
+
+    fn save_tag(&mut self) {
+        if !self.new_tag_name.is_empty() {
+            if let Some(token) = &self.token {
+                let tag = TagRequest {
+                    name: self.new_tag_name.clone(),
+                };
+                let request = match &self.renaming_tag {
+                    Some(old) => self.client.put(api_url_with_segments(&self.api_url, &["api", "tags", old])),
+                    None => self.client.post(format!("{}/api/tags", self.api_url)),
+                }
+                .header("Authorization", format!("Bearer {}", token))
+                .json(&tag);
+
+                self.submit(
+                    "save_tag",
+                    async move { api_result(request.send().await?).await },
+                    |app, result| match result {
+                        Ok(Ok(())) => {
+                            app.new_tag_name.clear();
+                            app.renaming_tag = None;
+                            app.load_tags();
+                            app.load_employees();
+                            app.message = Some("✓ Gespeichert".to_string());
+                        }
+                        Ok(Err(error)) => app.message = Some(error.message()),
+                        Err(_) => app.message = Some("✗ Fehler".to_string()),
+                    },
+                );
+            }
+        }
+    }
+
+    fn delete_tag(&mut self, name: &str) {
+        if let Some(token) = &self.token {
+            let url = api_url_with_segments(&self.api_url, &["api", "tags", name]);
+            let request = self.client.delete(url).header("Authorization", format!("Bearer {}", token));
+            self.submit(
+                "delete_tag",
+                async move { api_result(request.send().await?).await },
+                |app, result| match result {
+                    Ok(Ok(())) => {
+                        app.load_tags();
+                        app.load_employees();
+                        app.message = Some("✓ Gelöscht".to_string());
+                    }
+                    Ok(Err(error)) => app.message = Some(error.message()),
+                    Err(_) => app.message = Some("✗ Fehler".to_string()),
+                },
+            );
+        }
+    }
+
+    /// Such-, Filter-, Sortier- und Seitenparameter für `GET /api/employees`.
+    fn list_query(&self) -> Vec<(&'static str, String)> {
+        let mut query = self.filter_query();
+        query.push(("page", self.page.to_string()));
+        query.push(("limit", PAGE_SIZE.to_string()));
+        query
+    }
+
+    /// Filter und Sortierung der Übersicht, ohne Seitenaufteilung.
+    fn filter_query(&self) -> Vec<(&'static str, String)> {
+        let mut query = vec![
+            ("sort", self.sort_field.clone()),
+            ("order", if self.sort_desc { "desc" } else { "asc" }.to_string()),
+        ];
+
+        if self.filter_critical {
+            query.push(("status", "gelb,rot".to_string()));
+        }
+        if !self.filter_tags.is_empty() {
+            query.push(("tags", self.filter_tags.join(",")));
+        }
+        if !self.search_term.trim().is_empty() {
+            query.push(("q", self.search_term.trim().to_string()));
+        }
+
+        query
+    }
+
+    fn page_count(&self) -> usize {
+        self.total_count.div_ceil(PAGE_SIZE).max(1)
+    }
+
+    fn stats(&self) -> (usize, usize, usize, usize) {
+        (self.stats.total, self.stats.gruen, self.stats.gelb, self.stats.rot)
+    }
+
+    fn tag_counts(&self) -> Vec<(String, usize)> {
+        self.tags
+            .iter()
+            .map(|tag| (tag.clone(), self.stats.tags.get(tag).copied().unwrap_or(0)))
+            .collect()
+    }
+
+    fn is_admin(&self) -> bool {
+        self.current_user.as_ref().map(|u| u.role == Role::Admin).unwrap_or(false)
+    }
+
+    fn is_manager_or_admin(&self) -> bool {
+        self.current_user.as_ref().map(|u| {
+            u.role == Role::Admin || u.role == Role::Manager
+        }).unwrap_or(false)
+    }
+}
+
+impl eframe::App for TrainingDashboard {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.poll_requests();
+        if self.token.is_some() && self.live.is_none() {
+            self.start_live(ctx);
+        }
+        self.poll_live(ctx);
+
+        match self.screen {
+            Screen::Login => self.render_login(ctx),
+            Screen::Dashboard => self.render_dashboard(ctx),
+            Screen::AdminPanel => self.render_admin_panel(ctx),
+            Screen::Catalog => self.render_catalog(ctx),
+            Screen::CustomFields => self.render_custom_fields(ctx),
+            Screen::Import => self.render_import(ctx),
+            Screen::Jobs => self.render_jobs(ctx),
+        }
+        self.render_calendar_window(ctx);
+    }
+}
+
+impl TrainingDashboard {
+    fn render_menu(&mut self, ctx: &egui::Context) {
+        if self.show_menu {
+            egui::SidePanel::left("menu").min_width(200.0).show(ctx, |ui| {
+                ui.heading("Menü");
+                ui.separator();
+                
+                if ui.button("📊 Dashboard").clicked() {
+                    self.navigate(Screen::Dashboard);
+                    self.show_menu = false;
+                }
+                
+                if ui.button("📚 Schulungskatalog").clicked() {
+                    self.navigate(Screen::Catalog);
+                    self.load_catalog();
+                    self.show_menu = false;
+                }
+                
+                if ui.button("📥 Import").clicked() {
+                    self.navigate(Screen::Import);
+                    self.show_menu = false;
+                }
+                
+                if ui.button("📅 Kalender abonnieren").clicked() {
+                    self.load_calendar_feed(false);
+                    self.show_menu = false;
+                }
+                
+                if self.is_admin() && ui.button("👥 Benutzerverwaltung").clicked() {
+                    self.navigate(Screen::AdminPanel);
+                    self.load_users();
+                    self.load_webhooks();
+                    self.show_menu = false;
+                }
+                
+                if self.is_admin() && ui.button("🧩 Zusatzfelder").clicked() {
+                    self.navigate(Screen::CustomFields);
+                    self.load_custom_fields();
+                    self.show_menu = false;
+                }
+                
+                if self.is_admin() && ui.button("⏱ Hintergrundjobs").clicked() {
+                    self.navigate(Screen::Jobs);
+                    self.load_jobs();
+                    self.show_menu = false;
+                }
+                
+                ui.separator();
+                
+                if ui.button("🚪 Abmelden").clicked() {
+                    self.logout();
+                }
+            });
+        }
+    }
+
+    /// Drehendes Symbol, solange Anfragen laufen.
+    fn render_activity(&self, ui: &mut egui::Ui) {
+        if !self.requests.is_empty() {
+            ui.spinner().on_hover_text(format!("{} Anfrage(n) laufen", self.requests.len()));
+        }
+    }
+
+    fn render_login(&mut self, ctx: &egui::Context) {
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.vertical_centered(|ui| {
+                ui.add_space(100.0);
+                
+                ui.heading(egui::RichText::new("🎓 Training Dashboard").size(32.0));
+                ui.label("Bitte anmelden");
+                
+                ui.add_space(30.0);
+                
+                egui::Frame::none()
+                    .fill(egui::Color32::from_gray(30))
+                    .rounding(10.0)
+                    .inner_margin(30.0)
+                    .show(ui, |ui| {
+                        ui.set_max_width(400.0);
+                        
+                        ui.label("Benutzername:");
+                        ui.text_edit_singleline(&mut self.login_username);
+                        ui.add_space(10.0);
+                        
+                        ui.label("Passwort:");
+                        let pwd = ui.add(egui::TextEdit::singleline(&mut self.login_password).password(true));
+                        ui.add_space(20.0);
+                        
+                        let idle = !self.is_loading("login");
+                        let button = ui.add_enabled_ui(idle, |ui| {
+                            ui.add_sized([ui.available_width(), 40.0], egui::Button::new("Anmelden"))
+                        });
+                        if idle && (button.inner.clicked()
+                            || (pwd.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)))) {
+                            self.login();
+                        }
+                        if !idle {
+                            ui.spinner();
+                        }
+                    });
+                
+                ui.add_space(20.0);
+                
+                if let Some(msg) = &self.message {
+                    ui.colored_label(
+                        if msg.starts_with('✓') { egui::Color32::GREEN } else { egui::Color32::RED },
+                        msg
+                    );
+                }
+                
+                ui.add_space(20.0);
+                ui.label(egui::RichText::new("Standard: admin / admin123").small().weak());
+
+                ui.add_space(20.0);
+                ui.horizontal(|ui| {
+                    ui.label(
+                        egui::RichText::new(format!("Server: {} ({})", self.connection.active, self.api_url))
+                            .small()
+                            .weak(),
+                    );
+                    if ui.small_button("🔌 Verbindung…").clicked() {
+                        self.open_connection_dialog();
+                    }
+                });
+            });
+        });
+        self.render_connection_window(ctx);
+    }
+
+    fn render_connection_window(&mut self, ctx: &egui::Context) {
+        let testing = self.is_loading("connection_test");
+        let Some(dialog) = self.connection_dialog.as_mut() else {
+            return;
+        };
+
+        let mut open = true;
+        let mut test = false;
+        let mut pick = false;
+        let mut apply = false;
+        let mut cancel = false;
+        egui::Window::new("🔌 Verbindung")
+            .open(&mut open)
+            .default_width(500.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    for (index, profile) in dialog.draft.profiles.iter().enumerate() {
+                        if ui.selectable_label(index == dialog.selected, &profile.name).clicked() {
+                            dialog.selected = index;
+                            dialog.test = None;
+                        }
+                    }
+                    if ui.button("➕").on_hover_text("Neues Profil").clicked() {
+                        let mut profile = dialog.draft.profiles[dialog.selected].clone();
+                        profile.name = format!("Profil {}", dialog.draft.profiles.len() + 1);
+                        dialog.draft.profiles.push(profile);
+                        dialog.selected = dialog.draft.profiles.len() - 1;
+                        dialog.test = None;
+                    }
+                    if dialog.draft.profiles.len() > 1 && ui.button("🗑").on_hover_text("Profil löschen").clicked() {
+                        dialog.draft.profiles.remove(dialog.selected);
+                        dialog.selected = dialog.selected.min(dialog.draft.profiles.len() - 1);
+                        dialog.test = None;
+                    }
+                });
+                ui.separator();
+
+                let profile = &mut dialog.draft.profiles[dialog.selected];
+                egui::Grid::new("connection_profile").num_columns(2).show(ui, |ui| {
+                    ui.label("Name:");
+                    ui.text_edit_singleline(&mut profile.name);
+                    ui.end_row();
+
+                    ui.label("Server-URL:");
+                    if ui
+                        .add(egui::TextEdit::singleline(&mut profile.url).hint_text("https://schulungen.example.com"))
+                        .changed()
+                    {
+                        dialog.test = None;
+                    }
+                    ui.end_row();
+
+                    ui.label("CA-Zertifikat:");
+                    ui.horizontal(|ui| {
+                        if ui
+                            .add(egui::TextEdit::singleline(&mut profile.ca_cert).hint_text("optional, PEM-Datei"))
+                            .changed()
+                        {
+                            dialog.test = None;
+                        }
+                        if ui.button("📂").clicked() {
+                            pick = true;
+                        }
+                    });
+                    ui.end_row();
+                });
+                ui.label(
+                    egui::RichText::new("Ein CA-Zertifikat ist nur für HTTPS mit eigener Zertifizierungsstelle nötig.")
+                        .small(),
+                );
+
+                ui.add_space(10.0);
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(!testing, egui::Button::new("🔍 Verbindung testen")).clicked() {
+                        test = true;
+                    }
+                    if testing {
+                        ui.spinner();
+                    }
+                });
+                if let Some(result) = &dialog.test {
+                    ui.colored_label(
+                        if result.starts_with('✓') { egui::Color32::GREEN } else { egui::Color32::RED },
+                        result,
+                    );
+                }
+                if let Some(error) = &dialog.error {
+                    ui.colored_label(egui::Color32::RED, format!("✗ {}", error));
+                }
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button("✓ Verwenden").clicked() {
+                        apply = true;
+                    }
+                    if ui.button("Abbrechen").clicked() {
+                        cancel = true;
+                    }
+                });
+            });
+
+        if test {
+            self.test_connection();
+        }
+        if pick {
+            self.pick_ca_cert();
+        }
+        if apply {
+            self.apply_connection();
+        }
+        if !open || cancel {
+            self.connection_dialog = None;
+            self.cancel("connection_test");
+        }
+    }
+
+    fn render_dashboard(&mut self, ctx: &egui::Context) {
+        egui::TopBottomPanel::top("top").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                if ui.button("☰").clicked() {
+                    self.show_menu = !self.show_menu;
+                }
+                
+                ui.heading("🎓 Training Dashboard");
+                self.render_activity(ui);
+                
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui.button("🚪").clicked() {
+                        self.logout();
+                    }
+                    
+                    if let Some(user) = &self.current_user {
+                        ui.label(format!("👤 {} ({})", user.username, user.role.to_string()));
+                    }
+
+                    if self.live_connected {
+                        ui.colored_label(egui::Color32::GREEN, "● Live")
+                            .on_hover_text("Änderungen anderer erscheinen sofort");
+                    } else {
+                        ui.colored_label(egui::Color32::GRAY, "○ Offline")
+                            .on_hover_text("Keine Verbindung für Live-Updates – 🔄 lädt neu");
+                    }
+                });
+            });
+        });
+
+        self.render_menu(ctx);
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            if let Some(msg) = &self.message {
+                ui.colored_label(
+                    if msg.starts_with('✓') { egui::Color32::GREEN } else { egui::Color32::RED },
+                    msg
+                );
+            }
+
+            let (total, green, yellow, red) = self.stats();
+            ui.horizontal(|ui| {
+                ui.group(|ui| {
+                    ui.vertical(|ui| {
+                        ui.label(egui::RichText::new(format!("{}", total)).size(28.0).strong());
+                        ui.label("Gesamt");
+                    });
+                });
+                ui.group(|ui| {
+                    ui.vertical(|ui| {
+                        ui.label(egui::RichText::new(format!("{}", green)).size(28.0).color(Status::Gruen.color()).strong());
+                        ui.label("Aktuell");
+                    });
+                });
+                ui.group(|ui| {
+                    ui.vertical(|ui| {
+                        ui.label(egui::RichText::new(format!("{}", yellow)).size(28.0).color(Status::Gelb.color()).strong());
+                        ui.label("Bald fällig");
+                    });
+                });
+                ui.group(|ui| {
+                    ui.vertical(|ui| {
+                        ui.label(egui::RichText::new(format!("{}", red)).size(28.0).color(Status::Rot.color()).strong());
+                        ui.label("Überfällig");
+                    });
+                });
+                for (tag, count) in self.tag_counts() {
+                    ui.group(|ui| {
+                        ui.vertical(|ui| {
+                            ui.label(egui::RichText::new(format!("{}", count)).size(28.0).strong());
+                            ui.label(format!("🏷 {}", tag));
+                        });
+                    });
+                }
+            });
+
+            ui.add_space(10.0);
+
+            ui.columns(2, |columns| {
+                columns[0].group(|ui| {
+                    ui.heading("Neuer Eintrag");
+                    ui.checkbox(&mut self.new_multi, "Mehrere Personen");
+                    if self.new_multi {
+                        ui.label("Namen (einer pro Zeile):");
+                        ui.add(egui::TextEdit::multiline(&mut self.new_name).desired_rows(5));
+                    } else {
+                        ui.label("Name:");
+                        ui.text_edit_singleline(&mut self.new_name);
+                    }
+                    field_error(ui, &self.new_errors, "name");
+                    ui.label("Training:");
+                    ui.text_edit_singleline(&mut self.new_training);
+                    field_error(ui, &self.new_errors, "training");
+                    ui.label("Fällig (YYYY-MM-DD):");
+                    ui.text_edit_singleline(&mut self.new_duedate);
+                    field_error(ui, &self.new_errors, "duedate");
+                    ui.label("Status:");
+                    egui::ComboBox::from_id_source("status")
+                        .selected_text(self.new_status.to_string())
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut self.new_status, Status::Gruen, Status::Gruen.to_string());
+                            ui.selectable_value(&mut self.new_status, Status::Gelb, Status::Gelb.to_string());
+                            ui.selectable_value(&mut self.new_status, Status::Rot, Status::Rot.to_string());
+                        });
+                    field_error(ui, &self.new_errors, "status");
+                    for def in &self.custom_field_defs {
+                        let value = self.new_custom_fields.entry(def.name.clone()).or_default();
+                        custom_field_input(ui, def, value, "new");
+                        field_error(ui, &self.new_errors, &format!("custom_fields.{}", def.name));
+                    }
+                    if !self.tags.is_empty() {
+                        ui.label("Tags:");
+                        tag_selector(ui, &self.tags, &mut self.new_tags);
+                        field_error(ui, &self.new_errors, "tags");
+                    }
+                    
+                    if ui.add_enabled(!self.is_loading("add_employee"), egui::Button::new("➕ Hinzufügen")).clicked() {
+                        self.add_employee();
+                    }
+                });
+
+                columns[1].group(|ui| {
+                    ui.heading("Übersicht");
+                    let mut reload = false;
+                    ui.horizontal(|ui| {
+                        ui.label("🔍");
+                        reload |= ui.text_edit_singleline(&mut self.search_term).changed();
+                        if ui.button("🔄").clicked() {
+                            self.load_employees();
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        reload |= ui.checkbox(&mut self.filter_critical, "Nur Kritische").changed();
+                        ui.separator();
+                        ui.label("Sortierung:");
+                        let current = SORT_FIELDS
+                            .iter()
+                            .find(|(key, _)| *key == self.sort_field)
+                            .map(|(_, label)| *label)
+                            .unwrap_or("ID");
+                        egui::ComboBox::from_id_source("sort")
+                            .selected_text(current)
+                            .show_ui(ui, |ui| {
+                                for (key, label) in SORT_FIELDS {
+                                    reload |= ui.selectable_value(&mut self.sort_field, key.to_string(), label).changed();
+                                }
+                            });
+                        if ui.button(if self.sort_desc { "⬇" } else { "⬆" }).clicked() {
+                            self.sort_desc = !self.sort_desc;
+                            reload = true;
+                        }
+                    });
+                    if !self.tags.is_empty() {
+                        reload |= tag_selector(ui, &self.tags, &mut self.filter_tags);
+                    }
+                    ui.horizontal(|ui| {
+                        egui::ComboBox::from_id_source("export_format")
+                            .selected_text(self.export_format.to_uppercase())
+                            .show_ui(ui, |ui| {
+                                for format in ["xlsx", "csv", "json"] {
+                                    ui.selectable_value(&mut self.export_format, format, format.to_uppercase());
+                                }
+                            });
+                        if ui.button("📤 Exportieren").on_hover_text("Aktuelle Auswahl ohne Seitenaufteilung speichern").clicked() {
+                            self.export_employees();
+                        }
+                        ui.separator();
+                        let group_label = match self.report_group_by.as_str() {
+                            "" => "Ohne Gruppierung".to_string(),
+                            "training" => "Nach Training".to_string(),
+                            "tags" => "Nach Tag".to_string(),
+                            other => self
+                                .custom_field_defs
+                                .iter()
+                                .find(|d| other.strip_prefix("custom_fields.") == Some(d.name.as_str()))
+                                .map(|d| format!("Nach {}", d.label))
+                                .unwrap_or_else(|| other.to_string()),
+                        };
+                        egui::ComboBox::from_id_source("report_group_by")
+                            .selected_text(group_label)
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut self.report_group_by, String::new(), "Ohne Gruppierung");
+                                ui.selectable_value(&mut self.report_group_by, "training".to_string(), "Nach Training");
+                                ui.selectable_value(&mut self.report_group_by, "tags".to_string(), "Nach Tag");
+                                for def in &self.custom_field_defs {
+                                    ui.selectable_value(
+                                        &mut self.report_group_by,
+                                        format!("custom_fields.{}", def.name),
+                                        format!("Nach {}", def.label),
+                                    );
+                                }
+                            });
+                        if ui.button("📊 Compliance-Bericht").on_hover_text("PDF für die aktuelle Auswahl").clicked() {
+                            self.download_compliance_report();
+                        }
+                    });
+                    if reload {
+                        self.page = 1;
+                        self.load_employees();
+                    }
+
+                    ui.horizontal(|ui| {
+                        if ui.add_enabled(self.page > 1, egui::Button::new("◀")).clicked() {
+                            self.page -= 1;
+                            self.load_employees();
+                        }
+                        ui.label(format!("Seite {} / {} ({} Einträge)", self.page, self.page_count(), self.total_count));
+                        if self.is_loading("employees") {
+                            ui.spinner();
+                        }
+                        if ui.add_enabled(self.page < self.page_count(), egui::Button::new("▶")).clicked() {
+                            self.page += 1;
+                            self.load_employees();
+                        }
+                        if self.live_pending > 0
+                            && ui
+                                .link(format!("↻ {} weitere Änderungen – neu laden", self.live_pending))
+                                .clicked()
+                        {
+                            self.load_employees();
+                        }
+                    });
+
+                    self.render_bulk_bar(ui);
+
+                    egui::ScrollArea::vertical().max_height(500.0).show(ui, |ui| {
+                        for emp in self.employees.clone() {
+                            ui.group(|ui| {
+                                ui.horizontal(|ui| {
+                                    let mut checked = self.selected.contains_key(&emp.id);
+                                    if ui.checkbox(&mut checked, "").changed() {
+                                        if checked {
+                                            self.selected.insert(emp.id, emp.version);
+                                        } else {
+                                            self.selected.remove(&emp.id);
+                                        }
+                                    }
+                                    ui.label(format!("#{}", emp.id));
+                                    if self.live_changed.contains_key(&emp.id) {
+                                        ui.colored_label(egui::Color32::LIGHT_BLUE, "●")
+                                            .on_hover_text("Gerade von anderer Stelle geändert");
+                                    }
+                                    ui.separator();
+                                    ui.vertical(|ui| {
+                                        ui.label(egui::RichText::new(&emp.name).strong());
+                                        ui.label(format!("{} | {}", emp.training, emp.duedate));
+                                        ui.horizontal(|ui| {
+                                            ui.label(egui::RichText::new(emp.status.to_string()).color(emp.status.color()));
+                                            if let Some(escalation) = self.escalations.get(&emp.id) {
+                                                let text = egui::RichText::new(format!("⚠ Eskalation Stufe {}", escalation.level))
+                                                    .color(escalation.color());
+                                                if ui.small_button(text).clicked() {
+                                                    self.escalation_open = Some(emp.id);
+                                                    self.escalation_comment.clear();
+                                                }
+                                            }
+                                        });
+                                        let extras: Vec<String> = self
+                                            .custom_field_defs
+                                            .iter()
+                                            .filter_map(|def| {
+                                                emp.custom_fields.get(&def.name).map(|v| format!("{}: {}", def.label, v))
+                                            })
+                                            .collect();
+                                        if !extras.is_empty() {
+                                            ui.label(egui::RichText::new(extras.join(" · ")).small());
+                                        }
+                                        if !emp.tags.is_empty() {
+                                            ui.label(egui::RichText::new(format!("🏷 {}", emp.tags.join(", "))).small());
+                                        }
+                                    });
+                                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                        if self.is_manager_or_admin() && ui.button("🗑").clicked() {
+                                            self.delete_employee(emp.id, emp.version);
+                                        }
+                                        if ui.button("✏").clicked() {
+                                            self.start_editing(emp.clone());
+                                        }
+                                        if ui.button("🔗").on_hover_text("Voraussetzungen").clicked() {
+                                            self.load_prerequisite_chain(emp.name.clone());
+                                        }
+                                        if ui.button("📄").on_hover_text("Schulungsnachweis (PDF)").clicked() {
+                                            self.download_person_report(&emp.name);
+                                        }
+                                        if emp.status == Status::Gruen
+                                            && ui.button("🎓").on_hover_text("Teilnahmebescheinigung (PDF)").clicked()
+                                        {
+                                            self.download_certificate(emp.id);
+                                        }
+                                    });
+                                });
+                            });
+                        }
+                    });
+                });
+            });
+        });
+
+        self.render_prerequisite_chain(ctx);
+        self.render_edit_window(ctx);
+        self.render_escalation_window(ctx);
+    }
+
+    fn render_escalation_window(&mut self, ctx: &egui::Context) {
+        let Some(id) = self.escalation_open else {
+            return;
+        };
+        let Some(escalation) = self.escalations.get(&id).cloned() else {
+            self.escalation_open = None;
+            return;
+        };
+        let title = self
+            .employees
+            .iter()
+            .find(|e| e.id == id)
+            .map(|e| format!("⚠ Eskalation: {} – {}", e.name, e.training))
+            .unwrap_or_else(|| format!("⚠ Eskalation #{}", id));
+
+        let mut open = true;
+        let mut action = None;
+        egui::Window::new(title)
+            .open(&mut open)
+            .default_width(450.0)
+            .show(ctx, |ui| {
+                ui.colored_label(
+                    escalation.color(),
+                    format!("Stufe {} seit {}", escalation.level, short_timestamp(&escalation.escalated_at)),
+                );
+                if let Some(note) = &escalation.acknowledged {
+                    ui.label(format!("Zur Kenntnis genommen von {} am {}: {}", note.by, short_timestamp(&note.at), note.comment));
+                }
+                if let Some(note) = &escalation.cleared {
+                    ui.label(format!("Aufgehoben von {} am {}: {}", note.by, short_timestamp(&note.at), note.comment));
+                }
+
+                ui.separator();
+                egui::ScrollArea::vertical().max_height(250.0).show(ui, |ui| {
+                    for event in escalation.events.iter().rev() {
+                        let what = match event.action.as_str() {
+                            "escalated" => format!("Stufe {} erreicht", event.level),
+                            "notified" => "Benachrichtigt".to_string(),
+                            "acknowledged" => "Zur Kenntnis genommen".to_string(),
+                            "cleared" => "Aufgehoben".to_string(),
+                            other => other.to_string(),
+                        };
+                        let by = event.by.as_deref().map(|b| format!(" ({})", b)).unwrap_or_default();
+                        ui.label(format!("{} – {}{}", short_timestamp(&event.at), what, by));
+                        if !event.comment.is_empty() {
+                            ui.label(egui::RichText::new(&event.comment).small());
+                        }
+                    }
+                });
+
+                if self.is_manager_or_admin() && escalation.cleared.is_none() {
+                    ui.separator();
+                    ui.label("Kommentar:");
+                    ui.text_edit_multiline(&mut self.escalation_comment);
+                    ui.horizontal(|ui| {
+                        if ui.button("✔ Zur Kenntnis genommen").clicked() {
+                            action = Some("acknowledge");
+                        }
+                        if ui.button("✖ Aufheben").on_hover_text("Keine weitere Eskalation für diese Fälligkeit").clicked() {
+                            action = Some("clear");
+                        }
+                    });
+                }
+            });
+
+        if let Some(action) = action {
+            self.annotate_escalation(id, action);
+        }
+        if !open {
+            self.escalation_open = None;
+        }
+    }
+
+    fn render_bulk_bar(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            let page_ids: BTreeSet<u32> = self.employees.iter().map(|e| e.id).collect();
+            let mut all = !page_ids.is_empty() && page_ids.iter().all(|id| self.selected.contains_key(id));
+            if ui.checkbox(&mut all, "Alle auf dieser Seite").changed() {
+                for emp in &self.employees {
+                    if all {
+                        self.selected.insert(emp.id, emp.version);
+                    } else {
+                        self.selected.remove(&emp.id);
+                    }
+                }
+            }
+            if !self.selected.is_empty() {
+                ui.label(format!("{} ausgewählt", self.selected.len()));
+                if ui.small_button("✖").on_hover_text("Auswahl aufheben").clicked() {
+                    self.selected.clear();
+                }
+            }
+        });
+
+        if self.selected.is_empty() {
+            return;
+        }
+
+        let mut changes = None;
+        let mut delete = false;
+        ui.group(|ui| {
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_id_source("bulk_status")
+                    .selected_text(self.bulk_status.to_string())
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut self.bulk_status, Status::Gruen, Status::Gruen.to_string());
+                        ui.selectable_value(&mut self.bulk_status, Status::Gelb, Status::Gelb.to_string());
+                        ui.selectable_value(&mut self.bulk_status, Status::Rot, Status::Rot.to_string());
+                    });
+                if ui.button("Status setzen").clicked() {
+                    changes = Some(BulkChanges {
+                        status: Some(self.bulk_status.to_api_string().to_string()),
+                        ..Default::default()
+                    });
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.add(egui::DragValue::new(&mut self.bulk_shift_days).suffix(" Tage"));
+                if ui.button("Fälligkeit verschieben").clicked() {
+                    changes = Some(BulkChanges {
+                        shift_days: Some(self.bulk_shift_days),
+                        ..Default::default()
+                    });
+                }
+            });
+            if !self.tags.is_empty() {
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_id_source("bulk_tag")
+                        .selected_text(if self.bulk_tag.is_empty() { "Tag wählen" } else { self.bulk_tag.as_str() })
+                        .show_ui(ui, |ui| {
+                            for tag in &self.tags {
+                                ui.selectable_value(&mut self.bulk_tag, tag.clone(), tag);
+                            }
+                        });
+                    let has_tag = !self.bulk_tag.is_empty();
+                    if ui.add_enabled(has_tag, egui::Button::new("🏷 +")).clicked() {
+                        changes = Some(BulkChanges {
+                            add_tags: vec![self.bulk_tag.clone()],
+                            ..Default::default()
+                        });
+                    }
+                    if ui.add_enabled(has_tag, egui::Button::new("🏷 −")).clicked() {
+                        changes = Some(BulkChanges {
+                            remove_tags: vec![self.bulk_tag.clone()],
+                            ..Default::default()
+                        });
+                    }
+                });
+            }
+            if self.is_manager_or_admin() && ui.button(format!("🗑 {} löschen", self.selected.len())).clicked() {
+                delete = true;
+            }
+        });
+
+        if let Some(changes) = changes {
+            self.bulk_update(changes);
+        } else if delete {
+            self.bulk_delete();
+        }
+    }
+
+    fn render_calendar_window(&mut self, ctx: &egui::Context) {
+        let Some(feed) = &self.calendar_feed else {
+            return;
+        };
+
+        let mut open = true;
+        let mut reset = false;
+        egui::Window::new("📅 Kalender abonnieren")
+            .open(&mut open)
+            .default_width(450.0)
+            .show(ctx, |ui| {
+                ui.label("Diese Adresse in Outlook, Thunderbird oder einer anderen Kalender-App als Internetkalender abonnieren:");
+                let mut url = feed.url.clone();
+                ui.add(egui::TextEdit::singleline(&mut url).desired_width(f32::INFINITY));
+                ui.label(
+                    egui::RichText::new(format!("Erzeugt am {}", feed.created_at.get(..10).unwrap_or(&feed.created_at)))
+                        .small(),
+                );
+                ui.horizontal(|ui| {
+                    if ui.button("📋 Kopieren").clicked() {
+                        ui.output_mut(|o| o.copied_text = feed.url.clone());
+                    }
+                    if ui
+                        .button("🔄 Neue Adresse")
+                        .on_hover_text("Die bisherige Adresse funktioniert danach nicht mehr")
+                        .clicked()
+                    {
+                        reset = true;
+                    }
+                });
+                ui.label(
+                    egui::RichText::new(
+                        "Erinnerungen 7 und 1 Tag vor Fälligkeit. Anpassbar mit ?alarms=30,7 – Filter wie ?status=gelb,rot funktionieren ebenfalls.",
+                    )
+                    .small(),
+                );
+                ui.label(egui::RichText::new("Die Adresse ist persönlich – wer sie kennt, sieht deine Termine.").small());
+            });
+
+        if reset {
+            self.load_calendar_feed(true);
+        }
+        if !open {
+            self.calendar_feed = None;
+        }
+    }
+
+    fn render_edit_window(&mut self, ctx: &egui::Context) {
+        let changed_elsewhere = self
+            .edit_original
+            .as_ref()
+            .is_some_and(|o| self.employees.iter().any(|e| e.id == o.id && e.version != o.version));
+        let saving = self.is_loading("save_employee");
+        let Some(employee) = self.editing.as_mut() else {
+            return;
+        };
+
+        let mut open = true;
+        let mut save = false;
+        egui::Window::new(format!("✏ Eintrag #{} bearbeiten", employee.id))
+            .open(&mut open)
+            .default_width(350.0)
+            .show(ctx, |ui| {
+                let errors = &self.edit_errors;
+                if changed_elsewhere {
+                    ui.colored_label(
+                        egui::Color32::YELLOW,
+                        "⚠ Inzwischen von anderer Stelle geändert – beim Speichern wird abgeglichen",
+                    );
+                }
+                ui.label("Name:");
+                ui.text_edit_singleline(&mut employee.name);
+                field_error(ui, errors, "name");
+                ui.label("Training:");
+                ui.text_edit_singleline(&mut employee.training);
+                field_error(ui, errors, "training");
+                ui.label("Fällig (YYYY-MM-DD):");
+                ui.text_edit_singleline(&mut employee.duedate);
+                field_error(ui, errors, "duedate");
+                ui.label("Status:");
+                egui::ComboBox::from_id_source("edit_status")
+                    .selected_text(employee.status.to_string())
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut employee.status, Status::Gruen, Status::Gruen.to_string());
+                        ui.selectable_value(&mut employee.status, Status::Gelb, Status::Gelb.to_string());
+                        ui.selectable_value(&mut employee.status, Status::Rot, Status::Rot.to_string());
+                    });
+                field_error(ui, errors, "status");
+                for def in &self.custom_field_defs {
+                    let value = employee.custom_fields.entry(def.name.clone()).or_default();
+                    custom_field_input(ui, def, value, "edit");
+                    field_error(ui, errors, &format!("custom_fields.{}", def.name));
+                }
+                if !self.tags.is_empty() {
+                    ui.label("Tags:");
+                    tag_selector(ui, &self.tags, &mut employee.tags);
+                    field_error(ui, errors, "tags");
+                }
+
+                ui.add_space(10.0);
+                if ui.add_enabled(!saving, egui::Button::new("💾 Speichern")).clicked() {
+                    save = true;
+                }
+            });
+
+        if save {
+            self.save_edited_employee();
+        } else if !open {
+            self.close_editor();
+        }
+
+        self.render_edit_conflict(ctx);
+    }
+
+    fn render_edit_conflict(&mut self, ctx: &egui::Context) {
+        let (Some(current), Some(edited)) = (&self.edit_conflict, &self.editing) else {
+            return;
+        };
+
+        let mut reload = false;
+        let mut keep_mine = false;
+        let mut cancel = false;
+        egui::Window::new("⚠ Eintrag wurde zwischenzeitlich geändert")
+            .collapsible(false)
+            .default_width(420.0)
+            .show(ctx, |ui| {
+                ui.label(format!("Eintrag #{} wurde geändert, während du ihn bearbeitet hast.", current.id));
+                ui.add_space(5.0);
+
+                egui::Grid::new("conflict_grid").striped(true).show(ui, |ui| {
+                    ui.strong("Feld");
+                    ui.strong("Server");
+                    ui.strong("Deine Eingabe");
+                    ui.end_row();
+
+                    let rows = [
+                        ("Name", current.name.clone(), edited.name.clone()),
+                        ("Training", current.training.clone(), edited.training.clone()),
+                        ("Fällig", current.duedate.clone(), edited.duedate.clone()),
+                        ("Status", current.status.to_string().to_string(), edited.status.to_string().to_string()),
+                        ("Tags", current.tags.join(", "), edited.tags.join(", ")),
+                    ];
+                    for (label, server, mine) in rows {
+                        if server != mine {
+                            ui.label(label);
+                            ui.label(server);
+                            ui.colored_label(egui::Color32::from_rgb(255, 200, 0), mine);
+                            ui.end_row();
+                        }
+                    }
+                    for def in &self.custom_field_defs {
+                        let server = current.custom_fields.get(&def.name).cloned().unwrap_or_default();
+                        let mine = edited.custom_fields.get(&def.name).cloned().unwrap_or_default();
+                        if server != mine {
+                            ui.label(&def.label);
+                            ui.label(server);
+                            ui.colored_label(egui::Color32::from_rgb(255, 200, 0), mine);
+                            ui.end_row();
+                        }
+                    }
+                });
+
+                ui.add_space(10.0);
+                ui.horizontal(|ui| {
+                    if ui.button("🔄 Neu laden").on_hover_text("Eigene Änderungen verwerfen").clicked() {
+                        reload = true;
+                    }
+                    if ui
+                        .button("💾 Meine Änderungen übernehmen")
+                        .on_hover_text("Nur die von dir geänderten Felder überschreiben")
+                        .clicked()
+                    {
+                        keep_mine = true;
+                    }
+                    if ui.button("Abbrechen").clicked() {
+                        cancel = true;
+                    }
+                });
+            });
+
+        if reload {
+            let current = current.clone();
+            self.start_editing(current);
+            self.load_employees();
+        } else if keep_mine {
+            // Eigene Änderungen gegen den neuen Stand erneut senden
+            let version = current.version;
+            if let Some(original) = self.edit_original.as_mut() {
+                original.version = version;
+            }
+            self.edit_conflict = None;
+            self.save_edited_employee();
+        } else if cancel {
+            self.edit_conflict = None;
+        }
+    }
+
+    fn render_import(&mut self, ctx: &egui::Context) {
+        egui::TopBottomPanel::top("top").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                if ui.button("☰").clicked() {
+                    self.show_menu = !self.show_menu;
+                }
+                
+                ui.heading("📥 Import");
+                self.render_activity(ui);
+                
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui.button("🚪").clicked() {
+                        self.logout();
+                    }
+                    
+                    if let Some(user) = &self.current_user {
+                        ui.label(format!("👤 {}", user.username));
+                    }
+                });
+            });
+        });
+
+        self.render_menu(ctx);
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            if let Some(msg) = &self.message {
+                ui.colored_label(
+                    if msg.starts_with('✓') { egui::Color32::GREEN } else { egui::Color32::RED },
+                    msg
+                );
+            }
+
+            ui.horizontal(|ui| {
+                if ui.button("📂 Datei wählen (CSV/XLSX)").clicked() {
+                    self.pick_import_file();
+                }
+                if let Some(name) = &self.import_file_name {
+                    ui.label(name);
+                }
+                if self.is_loading("import_preview") || self.is_loading("import") {
+                    ui.spinner();
+                }
+            });
+            field_error(ui, &self.import_errors, "mapping");
+
+            // Übernahme erst, wenn die Vorschau zur aktuellen Zuordnung passt
+            let busy = self.is_loading("import_preview") || self.is_loading("import");
+            let Some(report) = &self.import_report else {
+                return;
+            };
+
+            let mut preview = false;
+            let mut commit = false;
+            ui.add_space(10.0);
+            ui.columns(2, |columns| {
+                columns[0].group(|ui| {
+                    ui.heading("Spaltenzuordnung");
+                    let targets: Vec<(String, String)> = IMPORT_FIELDS
+                        .iter()
+                        .map(|(key, label)| (key.to_string(), label.to_string()))
+                        .chain(
+                            self.custom_field_defs
+                                .iter()
+                                .map(|def| (format!("custom_fields.{}", def.name), def.label.clone())),
+                        )
+                        .collect();
+                    egui::Grid::new("import_mapping").show(ui, |ui| {
+                        for (field, label) in targets {
+                            ui.label(label);
+                            let current = self.import_mapping.get(&field).cloned();
+                            let mut selected = current.clone();
+                            egui::ComboBox::from_id_source(format!("map_{}", field))
+                                .selected_text(selected.as_deref().unwrap_or("–"))
+                                .show_ui(ui, |ui| {
+                                    ui.selectable_value(&mut selected, None, "–");
+                                    for column in &report.columns {
+                                        ui.selectable_value(&mut selected, Some(column.clone()), column);
+                                    }
+                                });
+                            if selected != current {
+                                match selected {
+                                    Some(column) => self.import_mapping.insert(field.clone(), column),
+                                    None => self.import_mapping.remove(&field),
+                                };
+                            }
+                            field_error(ui, &self.import_errors, &format!("mapping.{}", field));
+                            ui.end_row();
+                        }
+                    });
+                    ui.add_space(5.0);
+                    if ui.button("🔄 Vorschau aktualisieren").clicked() {
+                        preview = true;
+                    }
+                });
+
+                columns[1].group(|ui| {
+                    ui.heading("Vorschau");
+                    let summary = &report.summary;
+                    ui.label(format!(
+                        "{} neu · {} geändert · {} unverändert · {} fehlerhaft",
+                        summary.create, summary.update, summary.unchanged, summary.error
+                    ));
+                    if summary.error > 0 {
+                        ui.checkbox(&mut self.import_skip_errors, "Fehlerhafte Zeilen überspringen");
+                    }
+                    let can_commit = summary.create + summary.update > 0 && (summary.error == 0 || self.import_skip_errors);
+                    if ui.add_enabled(can_commit && !busy, egui::Button::new("✅ Importieren")).clicked() {
+                        commit = true;
+                    }
+                });
+            });
+
+            ui.add_space(10.0);
+            egui::ScrollArea::both().show(ui, |ui| {
+                egui::Grid::new("import_preview").striped(true).show(ui, |ui| {
+                    for header in ["Zeile", "Aktion", "ID", "Name", "Training", "Fällig", "Status", "Hinweis"] {
+                        ui.strong(header);
+                    }
+                    ui.end_row();
+
+                    for row in &report.rows {
+                        ui.label(row.row.to_string());
+                        let (label, color) = match row.action.as_str() {
+                            "create" => ("neu", Status::Gruen.color()),
+                            "update" => ("ändern", Status::Gelb.color()),
+                            "unchanged" => ("unverändert", egui::Color32::GRAY),
+                            _ => ("Fehler", Status::Rot.color()),
+                        };
+                        ui.colored_label(color, label);
+                        match &row.employee {
+                            Some(emp) => {
+                                ui.label(format!("#{}", emp.id));
+                                ui.label(&emp.name);
+                                ui.label(&emp.training);
+                                ui.label(&emp.duedate);
+                                ui.colored_label(emp.status.color(), emp.status.to_string());
+                            }
+                            None => {
+                                for _ in 0..5 {
+                                    ui.label("");
+                                }
+                            }
+                        }
+                        let mut hints: Vec<String> = row.fields.iter().map(|(k, v)| format!("{}: {}", k, v)).collect();
+                        if hints.is_empty() {
+                            hints.extend(row.error.clone());
+                        }
+                        if !row.warnings.is_empty() {
+                            hints.push(format!("Voraussetzungen fehlen: {}", row.warnings.join(", ")));
+                        }
+                        ui.label(hints.join("; "));
+                        ui.end_row();
+                    }
+                });
+            });
+
+            if preview {
+                self.run_import(true);
+            } else if commit {
+                self.run_import(false);
+            }
+        });
+    }
+
+    fn render_custom_fields(&mut self, ctx: &egui::Context) {
+        egui::TopBottomPanel::top("top").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                if ui.button("☰").clicked() {
+                    self.show_menu = !self.show_menu;
+                }
+                
+                ui.heading("🧩 Zusatzfelder");
+                self.render_activity(ui);
+                
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui.button("🚪").clicked() {
+                        self.logout();
+                    }
+                    
+                    if let Some(user) = &self.current_user {
+                        ui.label(format!("👤 {}", user.username));
+                    }
+                });
+            });
+        });
+
+        self.render_menu(ctx);
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            if let Some(msg) = &self.message {
+                ui.colored_label(
+                    if msg.starts_with('✓') { egui::Color32::GREEN } else { egui::Color32::RED },
+                    msg
+                );
+            }
+
+            ui.columns(2, |columns| {
+                columns[0].group(|ui| {
+                    ui.heading("Neues Feld");
+                    ui.label("Schlüssel (a-z, 0-9, _):");
+                    ui.text_edit_singleline(&mut self.new_field_name);
+                    ui.label("Bezeichnung:");
+                    ui.text_edit_singleline(&mut self.new_field_label);
+                    ui.label("Typ:");
+                    egui::ComboBox::from_id_source("field_kind")
+                        .selected_text(self.new_field_kind.to_string())
+                        .show_ui(ui, |ui| {
+                            for kind in [FieldKind::Text, FieldKind::Number, FieldKind::Date, FieldKind::Enum] {
+                                ui.selectable_value(&mut self.new_field_kind, kind, kind.to_string());
+                            }
+                        });
+                    if self.new_field_kind == FieldKind::Enum {
+                        ui.label("Optionen (kommagetrennt):");
+                        ui.text_edit_singleline(&mut self.new_field_options);
+                    }
+                    ui.checkbox(&mut self.new_field_required, "Pflichtfeld");
+                    
+                    if ui.button("💾 Speichern").clicked() {
+                        self.save_custom_field();
+                    }
+                });
+
+                columns[1].group(|ui| {
+                    ui.heading("Felder");
+                    if ui.button("🔄 Aktualisieren").clicked() {
+                        self.load_custom_fields();
+                    }
+
+                    egui::ScrollArea::vertical().max_height(600.0).show(ui, |ui| {
+                        for def in self.custom_field_defs.clone() {
+                            ui.group(|ui| {
+                                ui.horizontal(|ui| {
+                                    ui.vertical(|ui| {
+                                        ui.label(egui::RichText::new(&def.label).strong());
+                                        ui.label(format!("{} ({})", def.name, def.kind.to_string()));
+                                        if def.kind == FieldKind::Enum {
+                                            ui.label(def.options.join(", "));
+                                        }
+                                        if def.required {
+                                            ui.label("❗ Pflichtfeld");
+                                        }
+                                    });
+                                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                        if ui.button("🗑").clicked() {
+                                            self.delete_custom_field(&def.name);
+                                        }
+                                        if ui.button("✏").clicked() {
+                                            self.new_field_name = def.name.clone();
+                                            self.new_field_label = def.label.clone();
+                                            self.new_field_kind = def.kind;
+                                            self.new_field_options = def.options.join(", ");
+                                            self.new_field_required = def.required;
+                                        }
+                                    });
+                                });
+                            });
+                        }
+                    });
+                });
+            });
+        });
+    }
+
+    fn render_jobs(&mut self, ctx: &egui::Context) {
+        egui::TopBottomPanel::top("top").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                if ui.button("☰").clicked() {
+                    self.show_menu = !self.show_menu;
+                }
+                
+                ui.heading("⏱ Hintergrundjobs");
+                self.render_activity(ui);
+                
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui.button("🚪").clicked() {
+                        self.logout();
+                    }
+                    
+                    if let Some(user) = &self.current_user {
+                        ui.label(format!("👤 {}", user.username));
+                    }
+                });
+            });
+        });
+
+        self.render_menu(ctx);
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            if let Some(msg) = &self.message {
+                ui.colored_label(
+                    if msg.starts_with('✓') { egui::Color32::GREEN } else { egui::Color32::RED },
+                    msg
+                );
+            }
+
+            ui.horizontal(|ui| {
+                ui.label("Zeitplan im Cron-Format: Sekunde Minute Stunde Tag Monat Wochentag (Sekunde optional)");
+                if ui.button("🔄 Aktualisieren").clicked() {
+                    self.load_jobs();
+                }
+            });
+            ui.add_space(10.0);
+
+            let mut save = None;
+            let mut run = None;
+            egui::Grid::new("jobs").striped(true).spacing([16.0, 6.0]).show(ui, |ui| {
+                for header in ["Job", "Zeitplan", "Aktiv", "Nächster Lauf", "Letzter Lauf", ""] {
+                    ui.label(egui::RichText::new(header).strong());
+                }
+                ui.end_row();
+
+                for (index, job) in self.jobs.iter_mut().enumerate() {
+                    ui.label(&job.label);
+                    ui.add(egui::TextEdit::singleline(&mut job.schedule).desired_width(140.0));
+                    ui.checkbox(&mut job.enabled, "");
+                    ui.label(job.next_run.as_deref().map(short_timestamp).unwrap_or_else(|| "–".to_string()));
+                    match &job.last_run {
+                        Some(last) => {
+                            let color = if last.ok { Status::Gruen.color() } else { Status::Rot.color() };
+                            ui.colored_label(color, short_timestamp(&last.finished_at)).on_hover_text(&last.message);
+                        }
+                        None => {
+                            ui.label("–");
+                        }
+                    }
+                    ui.horizontal(|ui| {
+                        if ui.button("💾").on_hover_text("Speichern").clicked() {
+                            save = Some(index);
+                        }
+                        if job.running {
+                            ui.spinner();
+                        } else if ui.button("▶ Jetzt ausführen").clicked() {
+                            run = Some(job.job.clone());
                         }
                     });
-                
-                ui.add_space(20.0);
-                
-                if let Some(msg) = &self.message {
-                    ui.colored_label(
-                        if msg.starts_with('✓') { egui::Color32::GREEN } else { egui::Color32::RED },
-                        msg
-                    );
+                    ui.end_row();
                 }
-                
-                ui.add_space(20.0);
-                ui.label(egui::RichText::new("Standard: admin / admin123").small().weak());
+            });
+            if let Some(index) = save {
+                self.save_job(index);
+            }
+            if let Some(job) = run {
+                self.run_job(&job);
+            }
+
+            ui.add_space(20.0);
+            ui.heading("Verlauf");
+            egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                egui::Grid::new("job_history").striped(true).spacing([16.0, 4.0]).show(ui, |ui| {
+                    for header in ["Start", "Ende", "Job", "Auslöser", "Ergebnis"] {
+                        ui.label(egui::RichText::new(header).strong());
+                    }
+                    ui.end_row();
+
+                    for entry in &self.job_history {
+                        let label = self
+                            .jobs
+                            .iter()
+                            .find(|j| j.job == entry.job)
+                            .map(|j| j.label.as_str())
+                            .unwrap_or(&entry.job);
+                        ui.label(short_timestamp(&entry.started_at));
+                        ui.label(short_timestamp(&entry.finished_at));
+                        ui.label(label);
+                        ui.label(if entry.trigger == "manual" { "Manuell" } else { "Zeitplan" });
+                        let color = if entry.ok { Status::Gruen.color() } else { Status::Rot.color() };
+                        ui.colored_label(color, &entry.message);
+                        ui.end_row();
+                    }
+                });
             });
         });
     }
 
-    fn render_dashboard(&mut self, ctx: &egui::Context) {
+    fn render_prerequisite_chain(&mut self, ctx: &egui::Context) {
+        let Some(person) = self.chain_person.clone() else {
+            return;
+        };
+
+        let mut open = true;
+        egui::Window::new(format!("🔗 Voraussetzungen: {}", person))
+            .open(&mut open)
+            .default_width(400.0)
+            .show(ctx, |ui| {
+                if self.chain.is_empty() {
+                    ui.label("Keine Schulungen vorhanden");
+                }
+                egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                    for node in &self.chain {
+                        render_prerequisite_node(ui, node);
+                    }
+                });
+            });
+
+        if !open {
+            self.chain_person = None;
+            self.chain.clear();
+        }
+    }
+
+    fn render_catalog(&mut self, ctx: &egui::Context) {
         egui::TopBottomPanel::top("top").show(ctx, |ui| {
             ui.horizontal(|ui| {
                 if ui.button("☰").clicked() {
                     self.show_menu = !self.show_menu;
                 }
                 
-                ui.heading("🎓 Training Dashboard");
+                ui.heading("📚 Schulungskatalog");
+                self.render_activity(ui);
                 
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                     if ui.button("🚪").clicked() {
@@ -471,35 +3824,13 @@ impl TrainingDashboard {
                     }
                     
                     if let Some(user) = &self.current_user {
-                        ui.label(format!("👤 {} ({})", user.username, user.role.to_string()));
+                        ui.label(format!("👤 {}", user.username));
                     }
                 });
             });
         });
 
-        if self.show_menu {
-            egui::SidePanel::left("menu").min_width(200.0).show(ctx, |ui| {
-                ui.heading("Menü");
-                ui.separator();
-                
-                if ui.button("📊 Dashboard").clicked() {
-                    self.screen = Screen::Dashboard;
-                    self.show_menu = false;
-                }
-                
-                if self.is_admin() && ui.button("👥 Benutzerverwaltung").clicked() {
-                    self.screen = Screen::AdminPanel;
-                    self.load_users();
-                    self.show_menu = false;
-                }
-                
-                ui.separator();
-                
-                if ui.button("🚪 Abmelden").clicked() {
-                    self.logout();
-                }
-            });
-        }
+        self.render_menu(ctx);
 
         egui::CentralPanel::default().show(ctx, |ui| {
             if let Some(msg) = &self.message {
@@ -509,86 +3840,86 @@ impl TrainingDashboard {
                 );
             }
 
-            let (total, green, yellow, red) = self.stats();
-            ui.horizontal(|ui| {
-                ui.group(|ui| {
-                    ui.vertical(|ui| {
-                        ui.label(egui::RichText::new(format!("{}", total)).size(28.0).strong());
-                        ui.label("Gesamt");
-                    });
-                });
-                ui.group(|ui| {
-                    ui.vertical(|ui| {
-                        ui.label(egui::RichText::new(format!("{}", green)).size(28.0).color(Status::Gruen.color()).strong());
-                        ui.label("Aktuell");
-                    });
-                });
-                ui.group(|ui| {
-                    ui.vertical(|ui| {
-                        ui.label(egui::RichText::new(format!("{}", yellow)).size(28.0).color(Status::Gelb.color()).strong());
-                        ui.label("Bald fällig");
-                    });
-                });
-                ui.group(|ui| {
-                    ui.vertical(|ui| {
-                        ui.label(egui::RichText::new(format!("{}", red)).size(28.0).color(Status::Rot.color()).strong());
-                        ui.label("Überfällig");
-                    });
-                });
-            });
-
-            ui.add_space(10.0);
+            let can_edit = self.is_manager_or_admin();
 
             ui.columns(2, |columns| {
                 columns[0].group(|ui| {
-                    ui.heading("Neuer Eintrag");
-                    ui.label("Name:");
-                    ui.text_edit_singleline(&mut self.new_name);
-                    ui.label("Training:");
-                    ui.text_edit_singleline(&mut self.new_training);
-                    ui.label("Fällig (YYYY-MM-DD):");
-                    ui.text_edit_singleline(&mut self.new_duedate);
-                    ui.label("Status:");
-                    egui::ComboBox::from_id_source("status")
-                        .selected_text(self.new_status.to_string())
-                        .show_ui(ui, |ui| {
-                            ui.selectable_value(&mut self.new_status, Status::Gruen, Status::Gruen.to_string());
-                            ui.selectable_value(&mut self.new_status, Status::Gelb, Status::Gelb.to_string());
-                            ui.selectable_value(&mut self.new_status, Status::Rot, Status::Rot.to_string());
-                        });
+                    ui.heading("Katalogeintrag");
+                    if !can_edit {
+                        ui.label("Nur Manager und Admins können den Katalog bearbeiten.");
+                        return;
+                    }
+                    ui.label("Schulung:");
+                    ui.text_edit_singleline(&mut self.new_catalog_name);
+                    ui.label("Voraussetzungen (kommagetrennt):");
+                    ui.text_edit_singleline(&mut self.new_catalog_prerequisites);
+                    ui.checkbox(&mut self.new_catalog_strict, "Ohne Voraussetzungen ablehnen");
                     
-                    if ui.button("➕ Hinzufügen").clicked() {
-                        self.add_employee();
+                    if ui.button("💾 Speichern").clicked() {
+                        self.save_catalog_entry();
                     }
-                });
 
-                columns[1].group(|ui| {
-                    ui.heading("Übersicht");
+                    ui.add_space(20.0);
+                    ui.separator();
+                    ui.heading("Tags");
                     ui.horizontal(|ui| {
-                        ui.label("🔍");
-                        ui.text_edit_singleline(&mut self.search_term);
-                        if ui.button("🔄").clicked() {
-                            self.load_employees();
+                        ui.text_edit_singleline(&mut self.new_tag_name);
+                        let label = if self.renaming_tag.is_some() { "💾 Umbenennen" } else { "➕ Tag" };
+                        if ui.button(label).clicked() {
+                            self.save_tag();
+                        }
+                        if self.renaming_tag.is_some() && ui.button("✖").clicked() {
+                            self.renaming_tag = None;
+                            self.new_tag_name.clear();
                         }
                     });
-                    ui.checkbox(&mut self.filter_critical, "Nur Kritische");
+                    for tag in self.tags.clone() {
+                        ui.horizontal(|ui| {
+                            ui.label(format!("🏷 {}", tag));
+                            if ui.small_button("✏").clicked() {
+                                self.new_tag_name = tag.clone();
+                                self.renaming_tag = Some(tag.clone());
+                            }
+                            if ui.small_button("🗑").clicked() {
+                                self.delete_tag(&tag);
+                            }
+                        });
+                    }
+                });
 
-                    egui::ScrollArea::vertical().max_height(500.0).show(ui, |ui| {
-                        for emp in self.filtered_employees() {
+                columns[1].group(|ui| {
+                    ui.heading("Schulungen");
+                    if ui.button("🔄 Aktualisieren").clicked() {
+                        self.load_catalog();
+                    }
+
+                    egui::ScrollArea::vertical().max_height(600.0).show(ui, |ui| {
+                        for entry in self.catalog.clone() {
                             ui.group(|ui| {
                                 ui.horizontal(|ui| {
-                                    ui.label(format!("#{}", emp.id));
-                                    ui.separator();
                                     ui.vertical(|ui| {
-                                        ui.label(egui::RichText::new(&emp.name).strong());
-                                        ui.label(format!("{} | {}", emp.training, emp.duedate));
-                                        ui.label(egui::RichText::new(emp.status.to_string()).color(emp.status.color()));
-                                    });
-                                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                                        if self.is_manager_or_admin() && ui.button("🗑").clicked() {
-                                            self.delete_employee(emp.id);
+                                        ui.label(egui::RichText::new(&entry.name).strong());
+                                        if entry.prerequisites.is_empty() {
+                                            ui.label("Keine Voraussetzungen");
+                                        } else {
+                                            ui.label(format!("Benötigt: {}", entry.prerequisites.join(", ")));
+                                        }
+                                        if entry.strict {
+                                            ui.label("🔒 Strikt");
                                         }
                                     });
+                                    if can_edit {
+                                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                            if ui.button("🗑").clicked() {
+                                                self.delete_catalog_entry(&entry.name);
+                                            }
+                                            if ui.button("✏").clicked() {
+                                                self.new_catalog_name = entry.name.clone();
+                                                self.new_catalog_prerequisites = entry.prerequisites.join(", ");
+                                                self.new_catalog_strict = entry.strict;
+                                            }
+                                        });
+                                    }
                                 });
                             });
                         }
@@ -606,6 +3937,7 @@ impl TrainingDashboard {
                 }
                 
                 ui.heading("👥 Admin-Panel");
+                self.render_activity(ui);
                 
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                     if ui.button("🚪").clicked() {
@@ -619,28 +3951,7 @@ impl TrainingDashboard {
             });
         });
 
-        if self.show_menu {
-            egui::SidePanel::left("menu").min_width(200.0).show(ctx, |ui| {
-                ui.heading("Menü");
-                ui.separator();
-                
-                if ui.button("📊 Dashboard").clicked() {
-                    self.screen = Screen::Dashboard;
-                    self.show_menu = false;
-                }
-                
-                if ui.button("👥 Benutzerverwaltung").clicked() {
-                    self.screen = Screen::AdminPanel;
-                    self.show_menu = false;
-                }
-                
-                ui.separator();
-                
-                if ui.button("🚪 Abmelden").clicked() {
-                    self.logout();
-                }
-            });
-        }
+        self.render_menu(ctx);
 
         egui::CentralPanel::default().show(ctx, |ui| {
             if let Some(msg) = &self.message {
@@ -713,7 +4024,293 @@ impl TrainingDashboard {
                     });
                 });
             });
+
+            ui.add_space(10.0);
+            egui::CollapsingHeader::new(egui::RichText::new("🔔 Webhooks").heading())
+                .default_open(false)
+                .show(ui, |ui| self.render_webhooks(ui));
+        });
+    }
+
+    fn render_webhooks(&mut self, ui: &mut egui::Ui) {
+        ui.columns(2, |columns| {
+            columns[0].group(|ui| {
+                ui.heading(if self.webhook_editing.is_some() { "Webhook bearbeiten" } else { "Neuer Webhook" });
+                ui.label("Name:");
+                ui.text_edit_singleline(&mut self.webhook_name);
+                ui.label("URL:");
+                ui.add(egui::TextEdit::singleline(&mut self.webhook_url).hint_text("https://intranet.example.com/hook"));
+                ui.label("Geheimnis (leer = erzeugen bzw. behalten):");
+                ui.add(egui::TextEdit::singleline(&mut self.webhook_secret).password(true));
+                ui.label("Ereignisse:");
+                for (event, label) in WEBHOOK_EVENTS {
+                    let mut checked = self.webhook_events.contains(event);
+                    if ui.checkbox(&mut checked, label).changed() {
+                        if checked {
+                            self.webhook_events.insert(event.to_string());
+                        } else {
+                            self.webhook_events.remove(event);
+                        }
+                    }
+                }
+                ui.horizontal(|ui| {
+                    if ui.button("💾 Speichern").clicked() {
+                        self.save_webhook();
+                    }
+                    if self.webhook_editing.is_some() && ui.button("Abbrechen").clicked() {
+                        self.reset_webhook_form();
+                    }
+                });
+            });
+
+            columns[1].group(|ui| {
+                ui.horizontal(|ui| {
+                    ui.heading("Webhooks");
+                    if ui.button("🔄").on_hover_text("Aktualisieren").clicked() {
+                        self.load_webhooks();
+                    }
+                });
+                for webhook in self.webhooks.clone() {
+                    ui.group(|ui| {
+                        ui.horizontal(|ui| {
+                            ui.vertical(|ui| {
+                                ui.label(egui::RichText::new(&webhook.name).strong());
+                                ui.label(egui::RichText::new(&webhook.url).small());
+                                ui.label(egui::RichText::new(webhook.events.join(", ")).small());
+                                ui.label(if webhook.active { "✓ Aktiv" } else { "✗ Pausiert" });
+                            });
+                            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                if ui.button("🗑").clicked() {
+                                    self.delete_webhook(&webhook.id);
+                                }
+                                if ui.button(if webhook.active { "⏸" } else { "▶" }).clicked() {
+                                    self.toggle_webhook(&webhook);
+                                }
+                                if ui.button("✏").clicked() {
+                                    self.webhook_editing = Some(webhook.id.clone());
+                                    self.webhook_name = webhook.name.clone();
+                                    self.webhook_url = webhook.url.clone();
+                                    self.webhook_secret.clear();
+                                    self.webhook_events = webhook.events.iter().cloned().collect();
+                                }
+                                if ui.button("📋").on_hover_text("Geheimnis kopieren").clicked() {
+                                    ui.output_mut(|o| o.copied_text = webhook.secret.clone());
+                                    self.message = Some("✓ Geheimnis kopiert".to_string());
+                                }
+                                if ui.button("📨 Test").on_hover_text("Testzustellung (ping)").clicked() {
+                                    self.test_webhook(&webhook.id);
+                                }
+                            });
+                        });
+                    });
+                }
+            });
         });
+
+        ui.add_space(10.0);
+        ui.label(egui::RichText::new("Zustellprotokoll").strong());
+        egui::ScrollArea::vertical().id_source("webhook_deliveries").max_height(250.0).show(ui, |ui| {
+            egui::Grid::new("webhook_deliveries_grid").striped(true).spacing([16.0, 4.0]).show(ui, |ui| {
+                for header in ["Zeit", "Webhook", "Ereignis", "Versuch", "Ergebnis", "Dauer"] {
+                    ui.label(egui::RichText::new(header).strong());
+                }
+                ui.end_row();
+
+                for delivery in &self.webhook_deliveries {
+                    let name = self
+                        .webhooks
+                        .iter()
+                        .find(|w| w.id == delivery.webhook_id)
+                        .map(|w| w.name.as_str())
+                        .unwrap_or("–");
+                    ui.label(short_timestamp(&delivery.at));
+                    ui.label(name);
+                    ui.label(&delivery.event);
+                    ui.label(delivery.attempt.to_string());
+                    let result = match (delivery.status_code, &delivery.error) {
+                        (Some(code), _) if delivery.ok => format!("✓ {}", code),
+                        (_, Some(error)) => format!("✗ {}", error),
+                        _ => "✗".to_string(),
+                    };
+                    let color = if delivery.ok { Status::Gruen.color() } else { Status::Rot.color() };
+                    ui.colored_label(color, result);
+                    ui.label(format!("{} ms", delivery.duration_ms));
+                    ui.end_row();
+                }
+            });
+        });
+    }
+}
+
+fn custom_field_input(ui: &mut egui::Ui, def: &CustomFieldDef, value: &mut String, form: &str) {
+    ui.label(format!("{}{}:", def.label, if def.required { " *" } else { "" }));
+    match def.kind {
+        FieldKind::Text => {
+            ui.text_edit_singleline(value);
+        }
+        FieldKind::Number => {
+            ui.add(egui::TextEdit::singleline(value).hint_text("0.00"));
+        }
+        FieldKind::Date => {
+            ui.add(egui::TextEdit::singleline(value).hint_text("YYYY-MM-DD"));
+        }
+        FieldKind::Enum => {
+            egui::ComboBox::from_id_source((form, &def.name))
+                .selected_text(value.as_str())
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(value, String::new(), "–");
+                    for option in &def.options {
+                        ui.selectable_value(value, option.clone(), option);
+                    }
+                });
+        }
+    }
+}
+
+/// Liefert bei Fehlerstatus den strukturierten Fehler des Backends.
+async fn api_json<T: serde::de::DeserializeOwned>(response: reqwest::Response) -> reqwest::Result<Result<T, ApiError>> {
+    if response.status().is_success() {
+        Ok(Ok(response.json::<T>().await?))
+    } else {
+        Ok(Err(ApiError::from_response(response).await?))
+    }
+}
+
+async fn api_result(response: reqwest::Response) -> reqwest::Result<Result<(), ApiError>> {
+    if response.status().is_success() {
+        Ok(Ok(()))
+    } else {
+        Ok(Err(ApiError::from_response(response).await?))
+    }
+}
+
+fn field_error(ui: &mut egui::Ui, errors: &BTreeMap<String, String>, field: &str) {
+    if let Some(message) = errors.get(field) {
+        ui.label(egui::RichText::new(format!("⚠ {}", message)).small().color(Status::Rot.color()));
+    }
+}
+
+/// Liefert `true`, wenn sich die Auswahl geändert hat.
+fn tag_selector(ui: &mut egui::Ui, vocabulary: &[String], selected: &mut Vec<String>) -> bool {
+    let mut changed = false;
+    ui.horizontal_wrapped(|ui| {
+        for tag in vocabulary {
+            let active = selected.contains(tag);
+            if ui.selectable_label(active, format!("🏷 {}", tag)).clicked() {
+                if active {
+                    selected.retain(|t| t != tag);
+                } else {
+                    selected.push(tag.clone());
+                }
+                changed = true;
+            }
+        }
+    });
+    changed
+}
+
+fn render_prerequisite_node(ui: &mut egui::Ui, node: &PrerequisiteNode) {
+    let label = egui::RichText::new(format!("{} – {}", node.training, node.state.to_string()))
+        .color(node.state.color());
+
+    if node.requires.is_empty() {
+        ui.label(label);
+    } else {
+        egui::CollapsingHeader::new(label)
+            .id_source(&node.training)
+            .default_open(true)
+            .show(ui, |ui| {
+                for child in &node.requires {
+                    render_prerequisite_node(ui, child);
+                }
+            });
+    }
+}
+
+/// Liest den Ereignis-Stream und verbindet sich nach Abbrüchen neu.
+async fn live_feed(
+    client: reqwest::Client,
+    url: String,
+    token: String,
+    sender: mpsc::Sender<LiveMessage>,
+    ctx: egui::Context,
+) {
+    let mut delay = 1;
+    let mut first = true;
+    loop {
+        if let Ok(mut response) = client.get(&url).header("Authorization", format!("Bearer {}", token)).send().await {
+            // Sitzung abgelaufen – erneut verbinden hilft nicht
+            if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+                let _ = sender.send(LiveMessage::Disconnected);
+                ctx.request_repaint();
+                return;
+            }
+            if response.status().is_success() {
+                delay = 1;
+                let _ = sender.send(LiveMessage::Connected);
+                if !first {
+                    let _ = sender.send(LiveMessage::Resync);
+                }
+                first = false;
+                ctx.request_repaint();
+
+                let mut buffer = Vec::new();
+                // Der Server schickt alle 15 s ein Lebenszeichen
+                while let Ok(Ok(Some(chunk))) = tokio::time::timeout(Duration::from_secs(45), response.chunk()).await {
+                    buffer.extend_from_slice(&chunk);
+                    while let Some(end) = buffer.windows(2).position(|w| w == b"\n\n") {
+                        let block: Vec<u8> = buffer.drain(..end + 2).collect();
+                        if let Some(message) = parse_live_event(&String::from_utf8_lossy(&block)) {
+                            if sender.send(message).is_err() {
+                                return;
+                            }
+                            ctx.request_repaint();
+                        }
+                    }
+                }
+            }
+        }
+        if sender.send(LiveMessage::Disconnected).is_err() {
+            return;
+        }
+        ctx.request_repaint();
+        tokio::time::sleep(Duration::from_secs(delay)).await;
+        delay = (delay * 2).min(30);
+    }
+}
+
+fn parse_live_event(block: &str) -> Option<LiveMessage> {
+    let mut event = "message";
+    let mut data = String::new();
+    for line in block.lines() {
+        if let Some(value) = line.strip_prefix("event:") {
+            event = value.trim();
+        } else if let Some(value) = line.strip_prefix("data:") {
+            data.push_str(value.trim_start());
+        }
+    }
+    match event {
+        "resync" => Some(LiveMessage::Resync),
+        _ if data.is_empty() => None,
+        _ => serde_json::from_str(&data).ok().map(LiveMessage::Change),
+    }
+}
+
+/// RFC-3339-Zeitstempel ohne Sekundenbruchteile und Zeitzone, z.B. „2024-05-01 02:00:00“.
+fn short_timestamp(value: &str) -> String {
+    value.get(..19).unwrap_or(value).replace('T', " ")
+}
+
+/// Baut eine URL mit korrekt kodierten Pfadsegmenten (z.B. Namen mit Leerzeichen).
+fn api_url_with_segments(base: &str, segments: &[&str]) -> String {
+    match reqwest::Url::parse(base) {
+        Ok(mut url) => {
+            if let Ok(mut path) = url.path_segments_mut() {
+                path.pop_if_empty().extend(segments);
+            }
+            url.to_string()
+        }
+        Err(_) => format!("{}/{}", base, segments.join("/")),
     }
 }
 
@@ -725,6 +4322,45 @@ fn main() -> Result<(), eframe::Error> {
                 .with_inner_size([1200.0, 800.0]),
             ..Default::default()
         },
-        Box::new(|_| Box::<TrainingDashboard>::default()),
+        Box::new(|cc| {
+            Box::new(TrainingDashboard {
+                ctx: cc.egui_ctx.clone(),
+                ..Default::default()
+            })
+        }),
     )
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_live_event_reads_changes() {
+        let message = parse_live_event("event: employee_deleted\ndata: {\"type\":\"employee_deleted\",\"id\":7}");
+        assert!(matches!(message, Some(LiveMessage::Change(LiveChange::EmployeeDeleted { id: 7 }))));
+
+        let block = "event: user\ndata: {\"type\":\"user\",\"user\":{\"id\":\"u1\",\"username\":\"anna\",\"role\":\"manager\",\"active\":true}}";
+        match parse_live_event(block) {
+            Some(LiveMessage::Change(LiveChange::User { user })) => {
+                assert_eq!(user.username, "anna");
+                assert_eq!(user.role, Role::Manager);
+            }
+            _ => panic!("Benutzeränderung erwartet"),
+        }
+    }
+
+    #[test]
+    fn parse_live_event_resync_without_data() {
+        assert!(matches!(parse_live_event("event: resync\ndata: {}"), Some(LiveMessage::Resync)));
+        assert!(matches!(parse_live_event("event: resync"), Some(LiveMessage::Resync)));
+    }
+
+    #[test]
+    fn parse_live_event_ignores_keepalive_and_garbage() {
+        assert!(parse_live_event(": ping").is_none());
+        assert!(parse_live_event("event: employee").is_none());
+        assert!(parse_live_event("data: kein json").is_none());
+        assert!(parse_live_event("data: {\"type\":\"unbekannt\"}").is_none());
+    }
+}